@@ -1,11 +1,22 @@
 use crate::{
-    dbs::ops::{delete::Delete, remove::Remove},
+    dbs::{
+        graph::Graph,
+        ops::{delete::Delete, remove::Remove},
+        transaction::{self, Txn},
+        version::Versions,
+    },
+    doc::document::Cursor,
     err::Error,
-    ql::{direction::Direction, record::Record, value::Value},
+    kvs::log::Log,
+    ql::{
+        condition::Condition, direction::Direction, part::Part, record::Record, traits::Incoperate,
+        value::Value,
+    },
+    resp::Response,
 };
 use actix::{Actor, Addr, AsyncContext, Context, Handler, Message};
+use reblessive::tree::Stk;
 use std::{collections::BTreeMap, sync::Arc};
-use uuid::Uuid;
 
 #[non_exhaustive]
 #[derive(Debug, Clone)]
@@ -14,11 +25,13 @@ pub enum Entity {
         id: Record,
         fields: BTreeMap<Arc<str>, Value>,
         edges: BTreeMap<Record, Path>,
+        versions: Versions,
     },
     Edge {
         id: Record,
         fields: BTreeMap<Arc<str>, Value>,
         edges: BTreeMap<Record, Path>,
+        versions: Versions,
     },
 }
 
@@ -65,7 +78,9 @@ impl Actor for Entity {
             Entity::Node { edges, .. } => edges
                 .iter()
                 .for_each(|(_, path)| path.edge().do_send(Delete)),
-            Entity::Edge { id, fields, edges } => {
+            Entity::Edge {
+                id, fields, edges, ..
+            } => {
                 let in_key: Arc<str> = "in".into();
                 let out_key: Arc<str> = "out".into();
 
@@ -125,17 +140,80 @@ impl Entity {
         }
     }
 
-    pub fn get(&self, key: &Arc<str>) -> Option<&Value> {
-        self.fields().get(key)
+    pub fn versions(&self) -> &Versions {
+        match self {
+            Entity::Node { versions, .. } => versions,
+            Entity::Edge { versions, .. } => versions,
+        }
+    }
+
+    pub fn versions_mut(&mut self) -> &mut Versions {
+        match self {
+            Entity::Node { versions, .. } => versions,
+            Entity::Edge { versions, .. } => versions,
+        }
     }
 
-    pub fn evaluate(&self, val: &Value) -> Result<Value, Error> {
-        todo!()
-        /* Ok(match val {
-            Value::Idiom(v) => v.evaluate(&self.fields().clone().into())?,
-            Value::Expression(v) => v.evaluate(&self.fields().clone().into())?,
-            _ => val.to_owned(),
-        }) */
+    /// Marks the state the entity starts in as written by a transaction
+    pub fn with_txn(mut self, txn: &Txn) -> Self {
+        *self.versions_mut() = Versions::new(self.fields().clone(), txn);
+        self
+    }
+
+    /// Sets the fields of the entity without keeping a version of them
+    pub fn replace(&mut self, update: BTreeMap<Arc<str>, Value>) {
+        match self {
+            Entity::Node { fields, .. } => *fields = update,
+            Entity::Edge { fields, .. } => *fields = update,
+        }
+    }
+
+    /// Fails when another transaction has written to the entity and is
+    /// still open, or committed to it after the transaction started
+    pub fn guard(&self, txn: &Txn) -> Result<(), Error> {
+        let versions = self.versions();
+        let pending = versions.pending().is_some_and(|id| txn.id != Some(id));
+        if pending || (txn.id.is_some() && versions.conflicts(txn)) {
+            return Err(Error::TransactionConflict(self.id().to_string()));
+        }
+        Ok(())
+    }
+
+    /// Replaces the fields of the entity and persists them, unless another
+    /// writer got to it first
+    pub fn commit(
+        &mut self,
+        update: BTreeMap<Arc<str>, Value>,
+        txn: &Txn,
+    ) -> Result<Response, Error> {
+        self.guard(txn)?;
+        self.versions_mut().push(Some(update.clone()), txn);
+        self.replace(update.clone());
+        let id = self.id();
+        let put = Log::Put {
+            id: id.clone(),
+            fields: update.clone(),
+            at: self.versions().written(),
+        };
+        transaction::write(txn, id, put);
+        Ok(Response::Value(update.into()))
+    }
+
+    /// Deletes the entity as of the transaction, the fields are kept so its
+    /// earlier versions can still be read
+    pub fn delete(&mut self, txn: &Txn) -> Result<Response, Error> {
+        self.guard(txn)?;
+        if self.versions().visible(txn).is_none() {
+            return Ok(Response::None);
+        }
+        self.versions_mut().push(None, txn);
+        let (id, at) = (self.id(), self.versions().written());
+        transaction::write(txn, id, Log::Purge { id: id.clone(), at });
+        Ok(Response::None)
+    }
+
+    pub fn get(&self, key: &Arc<str>) -> Option<&Value> {
+        self.fields().get(key)
     }
 }
 
@@ -150,6 +228,7 @@ impl Entity {
         fields.insert("id".into(), id.clone().into());
         Entity::Node {
             id,
+            versions: Versions::new(fields.clone(), &Txn::default()),
             fields,
             edges: BTreeMap::new(),
         }
@@ -163,16 +242,16 @@ impl Entity {
     }
 
     pub fn new_edge(
-        edge: String,
+        id: Record,
         dest_id: Record,
         org_id: Record,
         destination: Addr<Entity>,
         origin: Addr<Entity>,
-        fields: Vec<(String, Value)>,
+        fields: Vec<(Arc<str>, Value)>,
     ) -> Self {
-        let mut fields: BTreeMap<Arc<str>, Value> =
-            fields.into_iter().map(|(k, v)| (k.into(), v)).collect();
+        let mut fields: BTreeMap<_, _> = fields.into_iter().collect();
 
+        fields.insert("id".into(), id.clone().into());
         fields.insert("in".into(), org_id.clone().into());
         fields.insert("out".into(), dest_id.clone().into());
 
@@ -182,7 +261,8 @@ impl Entity {
         ]);
 
         Entity::Edge {
-            id: Record::new(edge, Uuid::new_v4().to_string()),
+            id,
+            versions: Versions::new(fields.clone(), &Txn::default()),
             fields,
             edges,
         }
@@ -240,3 +320,41 @@ impl Handler<Configure> for Entity {
         self.bind_edges(self.id().clone(), ctx.address());
     }
 }
+
+/// A cursor over the fields of a version, reading linked records in the
+/// transaction
+fn cursor(fields: &BTreeMap<Arc<str>, Value>, txn: &Txn) -> Cursor {
+    Cursor::from(Value::from(fields.clone())).with_txn(*txn)
+}
+
+/// Evaluates a value against the fields of a version of an entity
+pub async fn evaluate(
+    stk: &mut Stk,
+    graph: &Addr<Graph>,
+    txn: &Txn,
+    fields: &BTreeMap<Arc<str>, Value>,
+    val: &Value,
+) -> Result<Value, Error> {
+    let val = match val {
+        Value::Idiom(v) if v.first().is_some_and(Part::is_field) => v.incorperate(fields).into(),
+        Value::Expression(v) => v.as_ref().incorperate(fields).into(),
+        v => v.to_owned(),
+    };
+    let cur = cursor(fields, txn);
+    stk.run(|stk| val.evaluate(stk, graph, Some(&cur))).await
+}
+
+/// Whether the fields of a version of an entity pass the filter
+pub async fn check(
+    stk: &mut Stk,
+    graph: &Addr<Graph>,
+    txn: &Txn,
+    fields: &BTreeMap<Arc<str>, Value>,
+    filter: &Condition,
+) -> Result<bool, Error> {
+    let cur = cursor(fields, txn);
+    let check = stk
+        .run(|stk| filter.evaluate(stk, graph, fields, Some(&cur)))
+        .await?;
+    Ok(check.is_truthy())
+}
@@ -0,0 +1,276 @@
+use crate::{
+    dbs::{
+        graph::Graph,
+        transaction::{self, Txn},
+    },
+    err::Error,
+    kvs::storage,
+    ql::query::{Queries, Query},
+    resp::QueryResponse,
+};
+use actix::Addr;
+use reblessive::TreeStack;
+use std::time::Instant;
+
+pub struct Executor {
+    graph: Addr<Graph>,
+}
+
+/// The transaction the queries are running in, holding the index of its
+/// first response
+enum Transaction {
+    None,
+    Open(usize),
+    Failed,
+}
+
+impl Executor {
+    pub fn new(graph: Addr<Graph>) -> Self {
+        Executor { graph }
+    }
+
+    pub async fn execute(&self, queries: Queries) -> Vec<QueryResponse> {
+        let mut stack = TreeStack::new();
+        let mut responses = Vec::with_capacity(queries.len());
+        let mut transaction = Transaction::None;
+        let mut txn = Txn::default();
+        for query in queries {
+            let now = Instant::now();
+            // Once a statement fails the rest of the transaction is skipped
+            if let Transaction::Failed = transaction {
+                match query {
+                    Query::Commit | Query::Cancel => transaction = Transaction::None,
+                    _ => responses.push(QueryResponse {
+                        time: now.elapsed(),
+                        result: Err(Error::QueryNotExecuted),
+                    }),
+                }
+                continue;
+            }
+            // Outside a transaction every query reads from its own snapshot,
+            // and its writes are checked and committed like a transaction of
+            // its own
+            if txn.id.is_none() {
+                txn = Txn::now();
+            }
+            let implicit = txn.id.is_none() && query.is_transactional();
+            if implicit {
+                if let Err(e) = transaction::begin(&mut txn).await {
+                    responses.push(QueryResponse {
+                        time: now.elapsed(),
+                        result: Err(e),
+                    });
+                    continue;
+                }
+            }
+            // Once the store failed nothing is changed that it could not store
+            let result = match query.is_write() {
+                true => storage::check().await,
+                false => Ok(()),
+            };
+            let result = match result {
+                Ok(()) => {
+                    stack
+                        .enter(|stk| query.compute(stk, &self.graph, None, &mut txn))
+                        .finish()
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            let result = match (implicit, result) {
+                (false, result) => result,
+                (true, Ok(value)) => transaction::commit(&self.graph, &mut txn)
+                    .await
+                    .map(|_| value),
+                (true, Err(e)) => {
+                    let _ = transaction::cancel(&self.graph, &mut txn).await;
+                    Err(e)
+                }
+            };
+            match (&query, &transaction, result) {
+                (Query::Begin, _, Ok(_)) => {
+                    transaction = Transaction::Open(responses.len());
+                    continue;
+                }
+                (Query::Commit, _, Ok(_)) => {
+                    transaction = Transaction::None;
+                    continue;
+                }
+                (Query::Cancel, Transaction::Open(start), Ok(_)) => {
+                    abort(&mut responses[*start..], || Error::QueryCancelled);
+                    transaction = Transaction::None;
+                    continue;
+                }
+                (Query::Commit, Transaction::Open(start), Err(e)) => {
+                    abort(&mut responses[*start..], || Error::QueryNotExecuted);
+                    transaction = Transaction::None;
+                    responses.push(QueryResponse {
+                        time: now.elapsed(),
+                        result: Err(e),
+                    });
+                }
+                (_, Transaction::Open(start), Err(e)) => {
+                    let _ = transaction::cancel(&self.graph, &mut txn).await;
+                    abort(&mut responses[*start..], || Error::QueryNotExecuted);
+                    transaction = Transaction::Failed;
+                    responses.push(QueryResponse {
+                        time: now.elapsed(),
+                        result: Err(e),
+                    });
+                }
+                (_, Transaction::Open(_), result) => responses.push(QueryResponse {
+                    time: now.elapsed(),
+                    result,
+                }),
+                (_, _, result) => {
+                    // A query only succeeds once its changes are durable
+                    let result = storage::flush().await.and(result);
+                    responses.push(QueryResponse {
+                        time: now.elapsed(),
+                        result,
+                    });
+                }
+            }
+        }
+        // A transaction left open is never committed
+        if let Transaction::Open(start) = transaction {
+            let _ = transaction::cancel(&self.graph, &mut txn).await;
+            abort(&mut responses[start..], || Error::QueryCancelled);
+        }
+        responses
+    }
+
+    /// Runs statements that are all expected to succeed
+    #[cfg(test)]
+    pub async fn setup(&self, queries: &str) {
+        let queries = crate::par::parse(queries).unwrap();
+        for response in self.execute(queries).await {
+            assert!(response.result.is_ok(), "{:?}", response.result);
+        }
+    }
+
+    /// Runs a single statement, returning its result as JSON
+    #[cfg(test)]
+    pub async fn run(&self, query: &str) -> Result<serde_json::Value, Error> {
+        let mut responses = self.execute(crate::par::parse(query).unwrap()).await;
+        assert_eq!(responses.len(), 1, "{query}");
+        let value = responses.remove(0).result?;
+        Ok(serde_json::to_value(value).unwrap())
+    }
+}
+
+/// Replaces the results of the statements rolled back with a transaction
+fn abort(responses: &mut [QueryResponse], error: impl Fn() -> Error) {
+    for response in responses.iter_mut().filter(|r| r.result.is_ok()) {
+        response.result = Err(error());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        dbs::ops::define::Define,
+        par,
+        ql::{array::Array, value::Value},
+    };
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn execute_queries_test() {
+        let graph = Graph::new().start();
+        graph
+            .send(Define::Table(String::from("a")))
+            .await
+            .unwrap()
+            .unwrap();
+        let queries = par::parse("SELECT * FROM a; SELECT * FROM b").unwrap();
+        let responses = Executor::new(graph).execute(queries).await;
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].result, Ok(Value::Array(Array::new())));
+        let json = serde_json::to_value(&responses[0]).unwrap();
+        assert_eq!(json["status"], "OK");
+        assert_eq!(json["result"], serde_json::json!([]));
+    }
+
+    #[actix_rt::test]
+    async fn execute_transaction_test() {
+        let graph = Graph::new().start();
+        let queries = par::parse(
+            "CREATE person:1 SET age = 1; CREATE person:2; RELATE person:1->knows:a->person:2;
+            BEGIN TRANSACTION;
+            CREATE person:3;
+            UPDATE person:1 SET age = 2;
+            RELATE person:3->knows:b->person:1;
+            DELETE person:2;
+            CANCEL TRANSACTION;
+            SELECT * FROM person ORDER BY id;
+            SELECT * FROM knows;
+            BEGIN; CREATE person:4; UPDATE person:1 SET age = 3; COMMIT;
+            SELECT * FROM person ORDER BY id;
+            BEGIN; CREATE person:5; CREATE person:1; CREATE person:6; COMMIT;
+            SELECT * FROM person:5, person:6;
+            COMMIT;
+            BEGIN; DELETE person:4",
+        )
+        .unwrap();
+        let responses = Executor::new(graph.clone()).execute(queries).await;
+        assert_eq!(responses.len(), 18);
+        for response in &responses[3..7] {
+            assert_eq!(response.result, Err(Error::QueryCancelled));
+        }
+        let json = serde_json::to_value(&responses).unwrap();
+        let people = serde_json::json!([
+            {"id": "person:1", "age": 1},
+            {"id": "person:2"},
+        ]);
+        assert_eq!(json[7]["result"], people);
+        let edge = serde_json::json!({"id": "knows:a", "in": "person:1", "out": "person:2"});
+        assert_eq!(json[8]["result"], serde_json::json!([edge]));
+        assert_eq!(json[9]["status"], "OK");
+        assert_eq!(json[10]["status"], "OK");
+        assert_eq!(json[11]["result"].as_array().unwrap().len(), 3);
+        assert_eq!(json[11]["result"][0]["age"], 3);
+        assert_eq!(responses[12].result, Err(Error::QueryNotExecuted));
+        assert_eq!(json[13]["status"], "ERR");
+        assert_eq!(responses[14].result, Err(Error::QueryNotExecuted));
+        assert_eq!(json[15]["result"], serde_json::json!([]));
+        assert_eq!(
+            responses[16].result,
+            Err(Error::NoTransaction(String::from("COMMIT")))
+        );
+        // A transaction that is never committed is cancelled
+        assert_eq!(responses[17].result, Err(Error::QueryCancelled));
+        let queries = par::parse("SELECT * FROM person:4").unwrap();
+        let responses = Executor::new(graph).execute(queries).await;
+        let json = serde_json::to_value(&responses).unwrap();
+        assert_eq!(json[0]["result"], serde_json::json!([{"id": "person:4"}]));
+    }
+
+    #[actix_rt::test]
+    async fn execute_concurrent_update_test() {
+        let graph = Graph::new().start();
+        let create = par::parse("CREATE counter:1 SET n = 0").unwrap();
+        Executor::new(graph.clone()).execute(create).await;
+        let increment = |graph: Addr<Graph>| async move {
+            let mut applied = 0;
+            for _ in 0..20 {
+                let queries = par::parse("UPDATE counter:1 SET n += 1").unwrap();
+                let responses = Executor::new(graph.clone()).execute(queries).await;
+                match &responses[0].result {
+                    Ok(_) => applied += 1,
+                    Err(e) => assert!(e.is_retryable()),
+                }
+            }
+            applied
+        };
+        let a = actix_rt::spawn(increment(graph.clone()));
+        let b = actix_rt::spawn(increment(graph.clone()));
+        let (a, b) = (a.await.unwrap(), b.await.unwrap());
+        // Every update that succeeded is kept, none overwrites another
+        let select = par::parse("SELECT n FROM counter:1").unwrap();
+        let responses = Executor::new(graph).execute(select).await;
+        let json = serde_json::to_value(&responses).unwrap();
+        assert_eq!(json[0]["result"][0]["n"], a + b);
+    }
+}
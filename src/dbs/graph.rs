@@ -1,10 +1,11 @@
-use crate::dbs::table::Table;
+use crate::{dbs::table::Table, idx::analyzer::Analyzer};
 use actix::{Actor, Addr, Context};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
 #[derive(Debug, Default)]
 pub struct Graph {
     pub tables: BTreeMap<String, Addr<Table>>,
+    pub analyzers: BTreeMap<String, Arc<Analyzer>>,
 }
 
 impl Actor for Graph {
@@ -15,6 +16,13 @@ impl Graph {
     pub fn new() -> Self {
         Graph::default()
     }
+
+    pub fn table(&mut self, name: &str) -> Addr<Table> {
+        self.tables
+            .entry(name.to_string())
+            .or_insert_with(|| Table::new(name).start())
+            .clone()
+    }
 }
 
 #[cfg(test)]
@@ -25,8 +33,9 @@ mod test {
     use crate::{
         dbs::{
             graph,
-            ops::{define::Define, retrieve::Retrieve},
+            ops::{create::Create, define::Define, retrieve::Retrieve},
         },
+        ql::{record::Record, value::Value},
         resp::Response,
     };
 
@@ -53,4 +62,21 @@ mod test {
         };
         assert_eq!(table.len(), 0);
     }
+
+    #[actix_rt::test]
+    async fn test_graph_table_exists() {
+        let addr = Graph::new().start();
+        let id = Record::new("a", 1);
+        let fields: Vec<(Arc<str>, Value)> = Vec::new();
+        addr.send(Create::new(id.clone(), fields))
+            .await
+            .unwrap()
+            .unwrap();
+        addr.send(Define::Table(String::from("a")))
+            .await
+            .unwrap()
+            .unwrap();
+        let response = addr.send(Retrieve::Record(id)).await.unwrap();
+        assert!(matches!(response, Response::Node(_)));
+    }
 }
@@ -0,0 +1,822 @@
+use crate::{
+    err::Error,
+    idx::{
+        hnsw::Hnsw,
+        search::{Matches, Search},
+    },
+    ql::{
+        condition::Condition, distance::Distance, expression::Expression, id::Id, ident::Ident,
+        idiom::Idiom, operator::Operator, part::Part, record::Record, value::Value,
+    },
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::Bound,
+    sync::Arc,
+};
+
+/// A secondary index over one or more fields of the records in a table,
+/// mapping the indexed values to the ids of the records holding them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index {
+    pub name: String,
+    pub fields: Vec<Idiom>,
+    pub unique: bool,
+    entries: BTreeMap<Vec<Value>, BTreeSet<Value>>,
+    keys: HashMap<Value, Vec<Value>>,
+}
+
+impl Index {
+    pub fn new(name: String, fields: Vec<Idiom>, unique: bool) -> Self {
+        Index {
+            name,
+            fields,
+            unique,
+            entries: BTreeMap::new(),
+            keys: HashMap::new(),
+        }
+    }
+
+    pub fn key(&self, fields: &BTreeMap<Arc<str>, Value>) -> Vec<Value> {
+        self.fields
+            .iter()
+            .map(|idiom| pick(fields, idiom))
+            .collect()
+    }
+
+    pub fn insert(&mut self, id: Value, fields: &BTreeMap<Arc<str>, Value>) {
+        self.remove(&id);
+        let key = self.key(fields);
+        self.entries
+            .entry(key.clone())
+            .or_default()
+            .insert(id.clone());
+        self.keys.insert(id, key);
+    }
+
+    pub fn remove(&mut self, id: &Value) {
+        let Some(key) = self.keys.remove(id) else {
+            return;
+        };
+        if let Some(ids) = self.entries.get_mut(&key) {
+            ids.remove(id);
+            if ids.is_empty() {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Checks no other record holds the key the fields give, when the index
+    /// is unique and the key has a value
+    pub fn check(&self, id: &Record, fields: &BTreeMap<Arc<str>, Value>) -> Result<(), Error> {
+        let mut key = self.key(fields);
+        if !self.unique || key.iter().all(Value::is_none) {
+            return Ok(());
+        }
+        let key_id = Value::from(id.id.clone());
+        let held = self.entries.get(&key);
+        let Some(held) = held.and_then(|ids| ids.iter().find(|held| **held != key_id)) else {
+            return Ok(());
+        };
+        Err(Error::IndexExists {
+            index: self.name.clone(),
+            value: match key.len() {
+                1 => key.remove(0),
+                _ => key.into(),
+            },
+            record: Record::new(id.table.clone(), Id::try_from(held.clone())?),
+        })
+    }
+
+    /// The ids of the records matching the scan, in index order
+    pub fn scan(&self, scan: &Scan) -> Vec<Value> {
+        let mut ids = Vec::new();
+        let start = (Bound::Included(scan.prefix.clone()), Bound::Unbounded);
+        for (key, matched) in self.entries.range(start) {
+            if !key.starts_with(&scan.prefix) {
+                break;
+            }
+            if let Some(val) = key.get(scan.prefix.len()) {
+                if scan
+                    .range
+                    .iter()
+                    .any(|(op, bound)| is_upper(op) && !compare(val, op, bound))
+                {
+                    break;
+                }
+                if !scan.range.iter().all(|(op, bound)| compare(val, op, bound)) {
+                    continue;
+                }
+            }
+            ids.extend(matched.iter().cloned());
+        }
+        ids
+    }
+
+    /// Plans a scan of this index from the comparisons in a condition, `None`
+    /// when the condition can not be answered from the index
+    pub fn plan(&self, cond: &Condition) -> Option<Scan> {
+        let mut preds = Vec::new();
+        predicates(&cond.0, &mut preds);
+
+        let mut scan = Scan::default();
+        for idiom in self.fields.iter() {
+            let eq = preds
+                .iter()
+                .find(|(field, op, _)| field == idiom && *op == Operator::Eq);
+            if let Some((_, _, val)) = eq {
+                scan.prefix.push(val.clone());
+                continue;
+            }
+            scan.range = preds
+                .iter()
+                .filter(|(field, op, _)| field == idiom && is_range(op))
+                .map(|(_, op, val)| (op.clone(), val.clone()))
+                .collect();
+            break;
+        }
+        (!scan.prefix.is_empty() || !scan.range.is_empty()).then_some(scan)
+    }
+}
+
+/// Equal values for a prefix of the index fields, followed by optional
+/// bounds on the next field
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scan {
+    pub prefix: Vec<Value>,
+    pub range: Vec<(Operator, Value)>,
+}
+
+impl Scan {
+    pub fn score(&self) -> usize {
+        self.prefix.len() * 2 + usize::from(!self.range.is_empty())
+    }
+}
+
+/// An index about to be filled with the records of a table
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Index(Index),
+    Search(Search),
+    Hnsw(Hnsw),
+}
+
+impl Kind {
+    pub fn name(&self) -> &str {
+        match self {
+            Kind::Index(index) => &index.name,
+            Kind::Search(search) => &search.name,
+            Kind::Hnsw(hnsw) => &hnsw.name,
+        }
+    }
+
+    pub fn check(&self, id: &Record, fields: &BTreeMap<Arc<str>, Value>) -> Result<(), Error> {
+        match self {
+            Kind::Index(index) => index.check(id, fields),
+            Kind::Search(_) => Ok(()),
+            Kind::Hnsw(hnsw) => hnsw.vector(&pick(fields, &hnsw.field)).map(drop),
+        }
+    }
+
+    pub fn insert(&mut self, id: Value, fields: &BTreeMap<Arc<str>, Value>) {
+        match self {
+            Kind::Index(index) => index.insert(id, fields),
+            Kind::Search(search) => search.insert(id, &pick(fields, &search.field)),
+            Kind::Hnsw(hnsw) => hnsw.insert(id, &pick(fields, &hnsw.field)),
+        }
+    }
+}
+
+/// The indexes defined on a table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Indexes {
+    pub btree: BTreeMap<String, Index>,
+    pub search: BTreeMap<String, Search>,
+    pub hnsw: BTreeMap<String, Hnsw>,
+}
+
+impl Indexes {
+    pub fn contains(&self, name: &str) -> bool {
+        self.btree.contains_key(name)
+            || self.search.contains_key(name)
+            || self.hnsw.contains_key(name)
+    }
+
+    pub fn define(&mut self, kind: Kind) {
+        match kind {
+            Kind::Index(index) => self.btree.insert(index.name.clone(), index).map(drop),
+            Kind::Search(search) => self.search.insert(search.name.clone(), search).map(drop),
+            Kind::Hnsw(hnsw) => self.hnsw.insert(hnsw.name.clone(), hnsw).map(drop),
+        };
+    }
+
+    pub fn insert(&mut self, id: &Value, fields: &BTreeMap<Arc<str>, Value>) {
+        for index in self.btree.values_mut() {
+            index.insert(id.clone(), fields);
+        }
+        for search in self.search.values_mut() {
+            search.insert(id.clone(), &pick(fields, &search.field));
+        }
+        for hnsw in self.hnsw.values_mut() {
+            hnsw.insert(id.clone(), &pick(fields, &hnsw.field));
+        }
+    }
+
+    /// Checks the fields a record is about to hold against the unique
+    /// indexes and the dimension of the vector indexes
+    pub fn check(&self, id: &Record, fields: &BTreeMap<Arc<str>, Value>) -> Result<(), Error> {
+        self.btree
+            .values()
+            .try_for_each(|index| index.check(id, fields))?;
+        self.hnsw
+            .values()
+            .try_for_each(|hnsw| hnsw.vector(&pick(fields, &hnsw.field)).map(drop))
+    }
+
+    pub fn remove(&mut self, id: &Value) {
+        for index in self.btree.values_mut() {
+            index.remove(id);
+        }
+        for search in self.search.values_mut() {
+            search.remove(id);
+        }
+        for hnsw in self.hnsw.values_mut() {
+            hnsw.remove(id);
+        }
+    }
+
+    /// The ids of the records that may match the condition, from a search
+    /// index when the condition matches text, otherwise from the index that
+    /// narrows it down the most
+    pub fn lookup(&self, cond: &Condition) -> Option<Vec<Value>> {
+        let mut preds = Vec::new();
+        predicates(&cond.0, &mut preds);
+        let searched = preds
+            .iter()
+            .find_map(|(idiom, op, query)| match (op, query) {
+                (Operator::Matches(_), Value::String(query)) => {
+                    let search = self.search.values().find(|s| s.field == *idiom)?;
+                    Some(search.matches(query))
+                }
+                _ => None,
+            });
+        if searched.is_some() {
+            return searched;
+        }
+        self.btree
+            .values()
+            .filter_map(|index| index.plan(cond).map(|scan| (index, scan)))
+            .max_by_key(|(_, scan)| scan.score())
+            .map(|(index, scan)| index.scan(&scan))
+    }
+
+    /// The ids of the nearest records, nearest first, when a vector index
+    /// with a matching distance covers the field
+    pub fn nearest(&self, knn: &Knn) -> Option<Vec<Value>> {
+        let hnsw = self.hnsw.values().find(|hnsw| {
+            hnsw.field == knn.field && knn.distance.is_none_or(|d| d == hnsw.distance)
+        })?;
+        let near = hnsw.search(&knn.vector, knn.k);
+        Some(near.into_iter().map(|(id, _)| id).collect())
+    }
+
+    /// How a record fares against each matches operator answered by a
+    /// search index
+    pub fn hits(&self, id: &Value, searches: &[Searched]) -> Matches {
+        searches
+            .iter()
+            .filter_map(|(reference, idiom, query)| {
+                let search = self.search.values().find(|s| s.field == *idiom)?;
+                Some((*reference, search.hit(id, query)))
+            })
+            .collect()
+    }
+}
+
+/// A KNN operator between a field and a query vector
+#[derive(Debug, Clone, PartialEq)]
+pub struct Knn {
+    pub field: Idiom,
+    pub k: usize,
+    pub distance: Option<Distance>,
+    pub vector: Vec<f64>,
+}
+
+impl Knn {
+    /// The first KNN operator among the predicates of a condition
+    pub fn find(cond: &Condition) -> Option<Knn> {
+        let mut preds = Vec::new();
+        predicates(&cond.0, &mut preds);
+        preds.into_iter().find_map(|(field, op, vector)| match op {
+            Operator::Knn(k, distance) => Some(Knn {
+                field,
+                k: k as usize,
+                distance,
+                vector: vector.to_vector()?,
+            }),
+            _ => None,
+        })
+    }
+}
+
+/// Replaces the KNN operator the table picked the nearest records for with
+/// true, as every record it yields already satisfies it
+pub fn planned(val: &Value) -> Value {
+    fn replace(val: &Value, done: &mut bool) -> Value {
+        let Value::Expression(expr) = val else {
+            return val.clone();
+        };
+        match expr.as_ref() {
+            Expression::Binary {
+                left,
+                op: Operator::And,
+                right,
+            } => {
+                let left = replace(left, done);
+                let right = replace(right, done);
+                Expression::Binary {
+                    left,
+                    op: Operator::And,
+                    right,
+                }
+                .into()
+            }
+            Expression::Binary {
+                left: Value::Idiom(idiom),
+                op: Operator::Knn(..),
+                right,
+            } if !*done && is_path(idiom) && right.to_vector().is_some() => {
+                *done = true;
+                Value::Bool(true)
+            }
+            _ => val.clone(),
+        }
+    }
+    replace(val, &mut false)
+}
+
+/// A matches operator between a field and a query string
+pub type Searched = (Option<u8>, Idiom, String);
+
+/// Collects the matches operators anywhere in a value
+pub fn searches(val: &Value, out: &mut Vec<Searched>) {
+    let Value::Expression(expr) = val else {
+        return;
+    };
+    match expr.as_ref() {
+        Expression::Unary { expr, .. } => searches(expr, out),
+        Expression::Binary {
+            left: Value::Idiom(idiom),
+            op: Operator::Matches(reference),
+            right: Value::String(query),
+        } if is_path(idiom) => out.push((*reference, idiom.clone(), query.to_string())),
+        Expression::Binary { left, right, .. } => {
+            searches(left, out);
+            searches(right, out);
+        }
+    }
+}
+
+/// Replaces the matches operators answered by a search index with whether
+/// the record matched
+pub fn resolve(val: &Value, matches: &Matches) -> Value {
+    let Value::Expression(expr) = val else {
+        return val.clone();
+    };
+    match expr.as_ref() {
+        Expression::Unary { op, expr } => Expression::Unary {
+            op: op.clone(),
+            expr: resolve(expr, matches),
+        }
+        .into(),
+        Expression::Binary {
+            op: Operator::Matches(reference),
+            ..
+        } if matches.contains_key(reference) => Value::Bool(matches[reference].matched),
+        Expression::Binary { left, op, right } => Expression::Binary {
+            left: resolve(left, matches),
+            op: op.clone(),
+            right: resolve(right, matches),
+        }
+        .into(),
+    }
+}
+
+/// Checks an index field only walks through object fields
+pub fn is_path(idiom: &Idiom) -> bool {
+    !idiom.is_empty() && idiom.iter().all(Part::is_field)
+}
+
+/// The value an index covers in the fields of a record
+pub fn pick(fields: &BTreeMap<Arc<str>, Value>, idiom: &Idiom) -> Value {
+    let mut parts = idiom.iter();
+    let mut val = match parts.next() {
+        Some(Part::Field(Ident(field))) => fields.get(field).cloned().unwrap_or_default(),
+        _ => return Value::None,
+    };
+    for part in parts {
+        val = match (val, part) {
+            (Value::Object(obj), Part::Field(Ident(field))) => obj.get(field).to_owned(),
+            _ => return Value::None,
+        };
+    }
+    val
+}
+
+fn predicates(val: &Value, preds: &mut Vec<(Idiom, Operator, Value)>) {
+    let Value::Expression(expr) = val else {
+        return;
+    };
+    let Expression::Binary { left, op, right } = expr.as_ref() else {
+        return;
+    };
+    match op {
+        Operator::And => {
+            predicates(left, preds);
+            predicates(right, preds);
+        }
+        Operator::Eq | Operator::Lt | Operator::Gt | Operator::LtEq | Operator::GtEq => {
+            match (left, right) {
+                (Value::Idiom(idiom), val) if is_path(idiom) && is_literal(val) => {
+                    preds.push((idiom.clone(), op.clone(), val.clone()))
+                }
+                (val, Value::Idiom(idiom)) if is_path(idiom) && is_literal(val) => {
+                    preds.push((idiom.clone(), flip(op), val.clone()))
+                }
+                _ => (),
+            }
+        }
+        Operator::Matches(_) => match (left, right) {
+            (Value::Idiom(idiom), Value::String(_)) if is_path(idiom) => {
+                preds.push((idiom.clone(), op.clone(), right.clone()))
+            }
+            _ => (),
+        },
+        Operator::Knn(..) => match (left, right.to_vector()) {
+            (Value::Idiom(idiom), Some(_)) if is_path(idiom) => {
+                preds.push((idiom.clone(), op.clone(), right.clone()))
+            }
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+fn is_literal(val: &Value) -> bool {
+    matches!(
+        val,
+        Value::None
+            | Value::Null
+            | Value::Bool(_)
+            | Value::Number(_)
+            | Value::String(_)
+            | Value::Uuid(_)
+            | Value::Datetime(_)
+            | Value::Record(_)
+    )
+}
+
+fn flip(op: &Operator) -> Operator {
+    match op {
+        Operator::Lt => Operator::Gt,
+        Operator::Gt => Operator::Lt,
+        Operator::LtEq => Operator::GtEq,
+        Operator::GtEq => Operator::LtEq,
+        op => op.clone(),
+    }
+}
+
+fn is_range(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::Lt | Operator::Gt | Operator::LtEq | Operator::GtEq
+    )
+}
+
+fn is_upper(op: &Operator) -> bool {
+    matches!(op, Operator::Lt | Operator::LtEq)
+}
+
+fn compare(val: &Value, op: &Operator, bound: &Value) -> bool {
+    match op {
+        Operator::Lt => val < bound,
+        Operator::LtEq => val <= bound,
+        Operator::Gt => val > bound,
+        Operator::GtEq => val >= bound,
+        _ => val == bound,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        dbs::{executor::Executor, graph::Graph},
+        err::Error,
+        idx::analyzer::Analyzer,
+        par,
+    };
+    use actix::Actor;
+
+    fn condition(input: &str) -> Condition {
+        let query = par::parse(&format!("SELECT * FROM a WHERE {input}")).unwrap();
+        let Some(crate::ql::query::Query::Select(select)) = query.0.into_iter().next() else {
+            panic!("select was not parsed");
+        };
+        select.conditions.unwrap()
+    }
+
+    fn idiom(field: &str) -> Idiom {
+        Idiom(vec![Part::Field(Ident::new(field))])
+    }
+
+    #[test]
+    fn index_scan_test() {
+        let mut index = Index::new(
+            String::from("idx"),
+            vec![idiom("city"), idiom("age")],
+            false,
+        );
+        for (id, city, age) in [(1, "a", 10), (2, "a", 20), (3, "b", 30), (4, "a", 30)] {
+            let fields = BTreeMap::from([("city".into(), city.into()), ("age".into(), age.into())]);
+            index.insert(id.into(), &fields);
+        }
+        let scan = |index: &Index, input| index.scan(&index.plan(&condition(input)).unwrap());
+        assert_eq!(
+            scan(&index, "city = 'a'"),
+            vec![Value::from(1), 2.into(), 4.into()]
+        );
+        assert_eq!(
+            scan(&index, "city = 'a' AND age > 10 AND age <= 30"),
+            vec![Value::from(2), 4.into()]
+        );
+        assert_eq!(
+            scan(&index, "'b' = city AND 20 < age"),
+            vec![Value::from(3)]
+        );
+        assert!(index.plan(&condition("age = 10")).is_none());
+        assert!(index.plan(&condition("city = 'a' OR age = 10")).is_none());
+
+        let fields = BTreeMap::from([("city".into(), "b".into())]);
+        index.insert(1.into(), &fields);
+        index.remove(&2.into());
+        assert_eq!(scan(&index, "city = 'a'"), vec![Value::from(4)]);
+        assert_eq!(scan(&index, "city = 'b'"), vec![Value::from(1), 3.into()]);
+    }
+
+    #[test]
+    fn planned_knn_test() {
+        let planned = |input| Condition(planned(&condition(input).0));
+        assert_eq!(
+            planned("pt <|2|> [0, 1] AND tag = 'a'"),
+            condition("true AND tag = 'a'")
+        );
+        assert_eq!(
+            planned("tag = 'a' AND pt <|2|> [0, 1] AND pt <|3|> [1, 1]"),
+            condition("tag = 'a' AND true AND pt <|3|> [1, 1]")
+        );
+        assert_eq!(
+            planned("pt <|2|> [0, 1] OR tag = 'a'"),
+            condition("pt <|2|> [0, 1] OR tag = 'a'")
+        );
+    }
+
+    #[test]
+    fn index_unique_test() {
+        let mut indexes = Indexes::default();
+        let index = Index::new(String::from("email"), vec![idiom("email")], true);
+        indexes.define(Kind::Index(index));
+        let fields = |email: Option<&str>| match email {
+            Some(email) => BTreeMap::from([("email".into(), email.into())]),
+            None => BTreeMap::new(),
+        };
+        indexes.insert(&1.into(), &fields(Some("a")));
+        indexes.insert(&2.into(), &fields(None));
+
+        let err = indexes.check(&Record::new("person", 3), &fields(Some("a")));
+        assert_eq!(
+            err,
+            Err(Error::IndexExists {
+                index: String::from("email"),
+                value: "a".into(),
+                record: Record::new("person", 1),
+            })
+        );
+        assert!(indexes
+            .check(&Record::new("person", 1), &fields(Some("a")))
+            .is_ok());
+        assert!(indexes
+            .check(&Record::new("person", 3), &fields(None))
+            .is_ok());
+        assert!(indexes
+            .check(&Record::new("person", 3), &fields(Some("b")))
+            .is_ok());
+    }
+
+    #[test]
+    fn index_search_test() {
+        let mut indexes = Indexes::default();
+        let analyzer = Arc::new(Analyzer::default());
+        let search = Search::new(String::from("ft"), idiom("text"), analyzer, 1.2, 0.75);
+        indexes.define(Kind::Search(search));
+        let fields = |text: &str| BTreeMap::from([("text".into(), text.into())]);
+        indexes.insert(&1.into(), &fields("hello world"));
+        indexes.insert(&2.into(), &fields("goodbye world"));
+
+        let cond = condition("text @@ 'world' AND age > 1");
+        assert_eq!(indexes.lookup(&cond), Some(vec![Value::from(1), 2.into()]));
+        let cond = condition("text @1@ 'hello' OR body @2@ 'hello'");
+        let mut searches = Vec::new();
+        super::searches(&cond.0, &mut searches);
+        assert_eq!(searches.len(), 2);
+        let hits = indexes.hits(&1.into(), &searches);
+        assert!(hits[&Some(1)].matched && !hits.contains_key(&Some(2)));
+        assert_eq!(
+            resolve(&cond.0, &hits).to_string(),
+            "true OR body @2@ hello"
+        );
+        assert!(!indexes.hits(&2.into(), &searches)[&Some(1)].matched);
+    }
+
+    fn ids(table: &str, ids: &[i64]) -> Result<serde_json::Value, Error> {
+        let ids: Vec<_> = ids
+            .iter()
+            .map(|id| serde_json::json!({"id": format!("{table}:{id}")}))
+            .collect();
+        Ok(serde_json::json!(ids))
+    }
+
+    #[actix_rt::test]
+    async fn define_index_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET email = 'a', age = 20;
+                DEFINE INDEX email ON person FIELDS email;
+                DEFINE INDEX age ON person FIELDS age;
+                CREATE person:2 SET email = 'b', age = 40;
+                CREATE person:3 SET email = 'c', age = 60",
+            )
+            .await;
+        let equal = executor
+            .run("SELECT id FROM person WHERE email = 'b'")
+            .await;
+        assert_eq!(equal, ids("person", &[2]));
+        let range = executor.run("SELECT id FROM person WHERE age >= 40 AND age < 60");
+        assert_eq!(range.await, ids("person", &[2]));
+
+        // The indexes follow updates and deletes
+        executor
+            .setup("UPDATE person:2 SET email = 'd'; DELETE person:3")
+            .await;
+        let either = executor.run("SELECT id FROM person WHERE email = 'b' OR email = 'd'");
+        assert_eq!(either.await, ids("person", &[2]));
+        let updated = executor
+            .run("SELECT id FROM person WHERE email = 'd'")
+            .await;
+        assert_eq!(updated, ids("person", &[2]));
+        let ordered = executor.run("SELECT id FROM person WHERE age > 30 ORDER BY age");
+        assert_eq!(ordered.await, ids("person", &[2]));
+
+        let again = executor
+            .run("DEFINE INDEX email ON person FIELDS email")
+            .await;
+        assert!(again.is_err());
+        let indexed = executor
+            .run("DEFINE INDEX tags ON person FIELDS tags[0]")
+            .await;
+        assert!(indexed.is_err());
+        let existing = executor
+            .run("SELECT id FROM person WHERE email = 'a'")
+            .await;
+        assert_eq!(existing, ids("person", &[1]));
+    }
+
+    #[actix_rt::test]
+    async fn unique_index_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET email = 'a', name = 'x';
+                CREATE person:2 SET email = 'a', name = 'y'",
+            )
+            .await;
+        let held = "Index email already contains a, held by person:1";
+        let unique = "DEFINE INDEX email ON person FIELDS email UNIQUE";
+        let duplicated = executor.run(unique).await;
+        assert_eq!(duplicated.unwrap_err().to_string(), held);
+        executor.setup("UPDATE person:2 SET email = 'b'").await;
+        executor.setup(unique).await;
+        executor
+            .setup("DEFINE INDEX pair ON person FIELDS name, email UNIQUE")
+            .await;
+
+        let created = executor.run("CREATE person:3 SET email = 'a'").await;
+        assert_eq!(created.unwrap_err().to_string(), held);
+        let updated = executor.run("UPDATE person:2 SET email = 'a'").await;
+        assert_eq!(updated.unwrap_err().to_string(), held);
+        // A record can keep its own value, and records without one never
+        // clash
+        executor
+            .setup(
+                "UPDATE person:1 SET email = 'a', age = 30;
+                CREATE person:4 SET email = 'c', name = 'x';
+                CREATE person:5; CREATE person:6",
+            )
+            .await;
+
+        executor
+            .setup(
+                "DEFINE INDEX since ON knows FIELDS since UNIQUE;
+                RELATE person:1->knows:1->person:2 SET since = 2020",
+            )
+            .await;
+        let related = executor.run("RELATE person:2->knows:2->person:1 SET since = 2020");
+        let held = "Index since already contains 2020, held by knows:1";
+        assert_eq!(related.await.unwrap_err().to_string(), held);
+        let emails = executor
+            .run("SELECT email FROM person WHERE email = 'a'")
+            .await;
+        assert_eq!(emails, Ok(serde_json::json!([{"email": "a"}])));
+    }
+
+    #[actix_rt::test]
+    async fn search_index_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE post:1 SET text = 'The runner was running', tag = 'a';
+                DEFINE ANALYZER english TOKENIZERS blank, class FILTERS lowercase, snowball(english);
+                DEFINE INDEX text ON post FIELDS text SEARCH ANALYZER english BM25(1.2, 0.75);
+                CREATE post:2 SET text = 'Runs every day and runs again', tag = 'b';
+                CREATE post:3 SET text = 'A quiet walk', tag = 'a'",
+            )
+            .await;
+        let scored = executor
+            .run("SELECT id, search::score(1) AS score FROM post WHERE text @1@ 'run' ORDER BY score DESC")
+            .await
+            .unwrap();
+        assert_eq!(scored[0]["id"], "post:2");
+        assert_eq!(scored[1]["id"], "post:1");
+        assert!(scored[0]["score"].as_f64() > scored[1]["score"].as_f64());
+        let highlighted = executor
+            .run("SELECT id, search::highlight('<b>', '</b>') AS text FROM post WHERE text @@ 'running' AND tag = 'a'")
+            .await;
+        let text = serde_json::json!([{"id": "post:1", "text": "The runner was <b>running</b>"}]);
+        assert_eq!(highlighted, Ok(text));
+
+        // The index follows updates and deletes
+        executor
+            .setup("UPDATE post:3 SET text = 'A quiet run'; DELETE post:2")
+            .await;
+        let matched = executor.run("SELECT id FROM post WHERE text @@ 'run' ORDER BY id");
+        assert_eq!(matched.await, ids("post", &[1, 3]));
+        // Fields without an index are matched by evaluating the operator
+        let unindexed = executor.run("SELECT id FROM post WHERE tag @@ 'A' ORDER BY id");
+        assert_eq!(unindexed.await, ids("post", &[1, 3]));
+
+        let missing = "DEFINE INDEX body ON post FIELDS body SEARCH ANALYZER missing";
+        assert!(executor.run(missing).await.is_err());
+        assert!(executor.run("DEFINE ANALYZER english").await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn vector_index_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE point:1 SET pt = [0, 0], tag = 'a';
+                CREATE point:2 SET pt = [1, 1], tag = 'b';
+                DEFINE INDEX pt ON point FIELDS pt HNSW DIMENSION 2 DIST EUCLIDEAN M 4 EFC 20;
+                CREATE point:3 SET pt = [5, 5], tag = 'a';
+                CREATE point:4 SET pt = [6, 6], tag = 'b'",
+            )
+            .await;
+        let invalid = executor.run("CREATE point:5 SET pt = [1, 2, 3]").await;
+        let dimension = "Index pt expects vectors of 2 numbers, found [1,2,3]";
+        assert_eq!(invalid.unwrap_err().to_string(), dimension);
+        let nearest = executor
+            .run("SELECT id FROM point WHERE pt <|2|> [5, 4]")
+            .await;
+        assert_eq!(nearest, ids("point", &[3, 4]));
+        let filtered = executor.run("SELECT id FROM point WHERE pt <|3|> [0, 1] AND tag = 'a'");
+        assert_eq!(filtered.await, ids("point", &[1, 3]));
+        let manhattan = executor
+            .run("SELECT id FROM point WHERE pt <|2,MANHATTAN|> [0, 1] AND tag = 'b'")
+            .await;
+        assert_eq!(manhattan, ids("point", &[2]));
+
+        executor.setup("DELETE point:3").await;
+        let deleted = executor
+            .run("SELECT id, vector::distance::euclidean(pt, [5, 4]) AS dist FROM point WHERE pt <|1|> [5, 4]")
+            .await;
+        let dist = serde_json::json!([{"id": "point:4", "dist": 2.23606797749979}]);
+        assert_eq!(deleted, Ok(dist));
+        let unindexed = executor
+            .run("SELECT id FROM point WHERE tag <|1|> [0, 0]")
+            .await;
+        assert_eq!(unindexed, Ok(serde_json::json!([])));
+        let bad = executor.run("DEFINE INDEX bad ON point FIELDS tag HNSW DIMENSION 2");
+        assert!(bad.await.is_err());
+    }
+}
@@ -1,135 +1,133 @@
 use crate::{
     dbs::{
-        self,
         entity::Entity,
-        graph::{self, Graph},
-        ops::{get::Get, walk::Walk},
-        table,
+        graph::Graph,
+        index::{self, Knn, Searched},
+        ops::{get::Get, hits::Hits, retrieve::Retrieve, walk::Walk},
+        transaction::Txn,
     },
+    doc::document::Cursor,
     err::Error,
     ql::{
-        edge::Edge, fields::Field, ident::Ident, idiom::Idioms, object::Object, path::Path,
-        record::Record, statements::statement::Statement, table::Table, value::Value,
+        array::Array,
+        condition::Condition,
+        edge::Edge,
+        expression::Expression,
+        fields::{Field, Fields},
+        group::Groups,
+        object::Object,
+        order::Orders,
+        path::Path,
+        record::Record,
+        statements::statement::Statement,
+        table::Table,
+        value::Value,
     },
     resp::Response,
 };
 use actix::Addr;
-use dbs::ops::retrieve::Retrieve;
 use reblessive::tree::Stk;
-use std::{collections::BTreeMap, mem, sync::Arc};
+use std::{collections::BTreeMap, mem};
 
 #[non_exhaustive]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Iterable {
     Value(Value),
     Edge(Edge),
+    Walk(Record, Vec<Path>),
     Record(Addr<Entity>),
     Table(Vec<Addr<Entity>>),
+    /// The records a table picked as nearest for the KNN operator of the
+    /// condition
+    Nearest(Vec<Addr<Entity>>),
 }
 
 impl Iterable {
     pub async fn process(
         self,
-        stk: &mut Stk,
         graph: &Addr<Graph>,
+        txn: &Txn,
         stm: &Statement<'_>,
     ) -> Result<Value, Error> {
+        let filter = stm.filter();
         match self {
-            Iterable::Value(value) => Self::process_value(value, stk, graph, stm).await,
-            Iterable::Edge(edge) => Self::process_edge(edge, graph).await,
-            Iterable::Record(record) => Self::process_record(record, stm).await,
-            Iterable::Table(table) => Self::process_table(table, stm).await,
+            Iterable::Value(value) => Self::process_value(value).await,
+            Iterable::Edge(edge) => Self::process_edge(edge, graph, txn, filter).await,
+            Iterable::Walk(from, path) => Self::process_walk(from, path, graph, txn, filter).await,
+            // A record the transaction can not see yields nothing, like one
+            // that does not exist
+            Iterable::Record(record) => Self::process_table(vec![record], graph, txn, filter).await,
+            Iterable::Table(table) => Self::process_table(table, graph, txn, filter).await,
+            Iterable::Nearest(table) => {
+                let filter = filter.map(|filter| Condition(index::planned(&filter.0)));
+                Self::process_table(table, graph, txn, filter.as_ref()).await
+            }
         }
     }
 
     async fn process_edge(
         Edge { dir, from, to }: Edge,
         graph: &Addr<Graph>,
+        txn: &Txn,
+        filter: Option<&Condition>,
+    ) -> Result<Value, Error> {
+        Self::process_walk(from, vec![Path::new(dir, to, None)], graph, txn, filter).await
+    }
+
+    async fn process_walk(
+        from: Record,
+        path: Vec<Path>,
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        filter: Option<&Condition>,
     ) -> Result<Value, Error> {
         let response = graph.send(Retrieve::Record(from.clone())).await.unwrap();
 
         let node = match response {
             Response::Node(addr) => addr,
-            Response::None => return Ok(Value::None),
+            Response::None => return Ok(Value::Array(Array::new())),
             _ => unreachable!(),
         };
 
-        todo!();
-        let mut responses: Vec<Value> = Vec::new();
-
-        // let walk = Walk::new(
-        //     vec![Path::new(dir.clone(), to, None)],
-        //     from.clone(),
-        //     Field::Single {
-        //         expr: Ident("id".into()).into(),
-        //         alias: None,
-        //     },
-        //     graph.clone(),
-        // );
-        // let response = node.send(walk).await.unwrap()?;
-        // responses.push(response.try_into()?);
+        let nodes = node
+            .send(Walk::new(path, from, graph.clone()).with_txn(*txn))
+            .await
+            .unwrap()?
+            .try_into()?;
+        Self::process_table(nodes, graph, txn, filter).await
+    }
 
-        Ok(responses.into())
+    async fn process_value(value: Value) -> Result<Value, Error> {
+        Ok(value)
     }
 
-    async fn process_value(
-        value: Value,
-        stk: &mut Stk,
+    async fn process_record(
+        record: Addr<Entity>,
         graph: &Addr<Graph>,
-        stm: &Statement<'_>,
+        txn: &Txn,
+        filter: Option<&Condition>,
     ) -> Result<Value, Error> {
-        let fields = stm.fields().ok_or(Error::InvalidStatement())?;
-        let mut object: BTreeMap<Arc<str>, Value> = BTreeMap::new();
-        for field in &fields.0 {
-            match field {
-                Field::WildCard => {
-                    object.insert(value.to_string().into(), value.clone());
-                }
-                Field::Single { expr, alias } => {
-                    // TODO need to fix whats going on here
-                    // let key = alias.clone().map_or(expr.to_string().into(), Into::into);
-                    // let value = stk
-                    //     .run(|stk| value.evaluate(stk, &expr, graph))
-                    //     .unwrap_or(Value::None);
-                    // object.insert(key, value);
-                }
-            }
-        }
-        Ok(Value::Object(Object(object)))
-    }
-
-    async fn process_record(record: Addr<Entity>, stm: &Statement<'_>) -> Result<Value, Error> {
-        let response = match stm {
-            Statement::Select(stm) => record
-                .send(Get::new(stm.fields.clone(), stm.conditions.clone()))
-                .await
-                .unwrap()?,
-        };
-        Ok(match response {
+        let get = Get::new(Field::WildCard.into(), filter.cloned(), graph.clone()).with_txn(*txn);
+        Ok(match record.send(get).await.unwrap()? {
             Response::Value(value) => value,
             Response::None => Value::None,
             _ => unreachable!(),
         })
     }
 
-    async fn process_table(table: Vec<Addr<Entity>>, stm: &Statement<'_>) -> Result<Value, Error> {
+    async fn process_table(
+        table: Vec<Addr<Entity>>,
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        filter: Option<&Condition>,
+    ) -> Result<Value, Error> {
         let mut values = vec![];
-        match stm {
-            Statement::Select(stm) => {
-                for addr in table {
-                    let fields = stm.fields.clone();
-                    let filter = stm.conditions.clone();
-                    let response = addr.send(Get::new(fields, filter)).await.unwrap()?;
-                    let val = match response {
-                        Response::Value(value) => value,
-                        Response::None => Value::None,
-                        _ => unreachable!(),
-                    };
-                    values.push(val);
-                }
+        for addr in table {
+            let val = Self::process_record(addr, graph, txn, filter).await?;
+            if !val.is_none() {
+                values.push(val);
             }
-        };
-
+        }
         Ok(values.into())
     }
 }
@@ -168,9 +166,15 @@ impl Iterator {
     pub async fn ingest_table(
         &mut self,
         Table(table): Table,
+        cond: Option<&Condition>,
         graph: &Addr<Graph>,
+        txn: &Txn,
     ) -> Result<(), Error> {
-        let retrieve = Retrieve::Table(table);
+        let nearest = cond.is_some_and(|cond| Knn::find(cond).is_some());
+        let retrieve = match cond {
+            Some(cond) => Retrieve::Where(table, cond.clone(), *txn),
+            None => Retrieve::Table(table),
+        };
         let response = graph.send(retrieve).await.unwrap();
         let table = match response {
             Response::Nodes(table) => table,
@@ -178,7 +182,10 @@ impl Iterator {
             _ => unreachable!(),
         };
 
-        self.ingest(Iterable::Table(table));
+        match nearest {
+            true => self.ingest(Iterable::Nearest(table)),
+            false => self.ingest(Iterable::Table(table)),
+        }
 
         Ok(())
     }
@@ -188,14 +195,19 @@ impl Iterator {
         Ok(())
     }
 
+    pub fn ingest_walk(&mut self, from: Record, path: Vec<Path>) -> Result<(), Error> {
+        self.ingest(Iterable::Walk(from, path));
+        Ok(())
+    }
+
     pub fn set_limit(&mut self, stm: &Statement<'_>) -> Result<(), Error> {
-        self.limit = stm.limit().map(Clone::clone);
+        self.limit = stm.limit().copied();
 
         Ok(())
     }
 
     pub fn set_start(&mut self, stm: &Statement<'_>) -> Result<(), Error> {
-        self.start = stm.start().map(Clone::clone);
+        self.start = stm.start().copied();
 
         Ok(())
     }
@@ -204,19 +216,39 @@ impl Iterator {
         &mut self,
         stk: &mut Stk,
         graph: &Addr<Graph>,
+        txn: &Txn,
         stm: &Statement<'_>,
     ) -> Result<Value, Error> {
         self.set_start(stm)?;
         self.set_limit(stm)?;
 
-        let mut values = vec![];
+        let mut docs = vec![];
         for val in mem::take(&mut self.entries) {
-            let val = val.process(stk, graph, stm).await?;
-            values.push(val);
+            docs.push(val.process(graph, txn, stm).await?);
         }
+        let docs: Value = docs.into();
+        let docs: Vec<Value> = docs.flatten().try_into()?;
+
+        let fields = stm.fields().ok_or(Error::InvalidStatement())?;
+        let mut values = match stm.group() {
+            Some(groups) => Self::group(stk, graph, txn, fields, groups, docs).await?,
+            None => {
+                let mut searches = Vec::new();
+                if let Some(cond) = stm.filter() {
+                    index::searches(&cond.0, &mut searches);
+                }
+                let mut values = Vec::with_capacity(docs.len());
+                for doc in docs {
+                    let cur = Self::cursor(graph, &searches, doc).await.with_txn(*txn);
+                    values.push(Self::project(stk, graph, fields, cur).await?);
+                }
+                values
+            }
+        };
 
-        let values: Value = values.into();
-        let mut values: Vec<Value> = values.flatten().try_into()?;
+        if let Some(orders) = stm.order() {
+            values = Self::order(stk, graph, txn, orders, values).await?;
+        }
 
         if let Some(i) = self.start {
             values = values.into_iter().skip(i).collect();
@@ -228,7 +260,351 @@ impl Iterator {
 
         Ok(values.into())
     }
+
+    /// A cursor over the document, along with its search index hits when
+    /// the filter matches text
+    async fn cursor(graph: &Addr<Graph>, searches: &[Searched], doc: Value) -> Cursor {
+        let id = match &doc {
+            Value::Object(Object(fields)) if !searches.is_empty() => fields.get("id"),
+            _ => None,
+        };
+        let Some(Value::Record(id)) = id else {
+            return Cursor::from(doc);
+        };
+        let hits = Hits(id.as_ref().clone(), searches.to_vec());
+        let matches = graph.send(hits).await.unwrap();
+        Cursor::from(doc).with_matches(matches)
+    }
+
+    async fn project(
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        fields: &Fields,
+        cur: Cursor,
+    ) -> Result<Value, Error> {
+        if let [Field::WildCard] = fields.as_slice() {
+            return Ok(cur.value);
+        }
+        let mut object = BTreeMap::new();
+        for field in fields.iter() {
+            match field {
+                Field::WildCard => {
+                    if let Value::Object(Object(fields)) = &cur.value {
+                        object.extend(fields.clone());
+                    }
+                }
+                Field::Single { expr, alias } => {
+                    let key = alias.clone().map_or(expr.to_string().into(), Into::into);
+                    let value = stk.run(|stk| expr.evaluate(stk, graph, Some(&cur))).await?;
+                    object.insert(key, value);
+                }
+            }
+        }
+        Ok(Value::Object(Object(object)))
+    }
+
+    async fn group(
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        fields: &Fields,
+        groups: &Groups,
+        docs: Vec<Value>,
+    ) -> Result<Vec<Value>, Error> {
+        let mut grouped: BTreeMap<Vec<Value>, Vec<Value>> = BTreeMap::new();
+        for doc in docs {
+            let cur = Cursor::from(doc).with_txn(*txn);
+            let mut key = Vec::with_capacity(groups.len());
+            for idiom in groups.iter() {
+                key.push(
+                    stk.run(|stk| idiom.evaluate(stk, graph, Some(&cur)))
+                        .await?,
+                );
+            }
+            grouped.entry(key).or_default().push(cur.value);
+        }
+
+        let mut values = Vec::with_capacity(grouped.len());
+        for docs in grouped.into_values() {
+            let mut object = BTreeMap::new();
+            for field in fields.iter() {
+                match field {
+                    Field::WildCard => {
+                        if let Some(Value::Object(Object(fields))) = docs.first() {
+                            object.extend(fields.clone());
+                        }
+                    }
+                    Field::Single { expr, alias } => {
+                        let key = alias.clone().map_or(expr.to_string().into(), Into::into);
+                        let value = stk
+                            .run(|stk| Self::aggregate(stk, graph, txn, expr, &docs))
+                            .await?;
+                        object.insert(key, value);
+                    }
+                }
+            }
+            values.push(Value::Object(Object(object)));
+        }
+        Ok(values)
+    }
+
+    /// Evaluates a field over every document in a group, aggregate functions
+    /// receive the values of their first argument across the whole group
+    async fn aggregate(
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        expr: &Value,
+        docs: &[Value],
+    ) -> Result<Value, Error> {
+        match expr {
+            Value::Function(func) if func.is_aggregate() => {
+                let mut args = Vec::with_capacity(func.args.len());
+                if let Some(arg) = func.args.first() {
+                    let mut values = Vec::with_capacity(docs.len());
+                    for doc in docs {
+                        let cur = Cursor::from(doc.clone()).with_txn(*txn);
+                        values.push(stk.run(|stk| arg.evaluate(stk, graph, Some(&cur))).await?);
+                    }
+                    args.push(values.into());
+                }
+                let cur = docs
+                    .first()
+                    .map(|doc| Cursor::from(doc.clone()).with_txn(*txn));
+                for arg in func.args.iter().skip(1) {
+                    args.push(
+                        stk.run(|stk| arg.evaluate(stk, graph, cur.as_ref()))
+                            .await?,
+                    );
+                }
+                match (func.name.as_str(), args.is_empty()) {
+                    ("count", true) => Ok((docs.len() as i64).into()),
+                    _ => func.run(args),
+                }
+            }
+            Value::Function(func) => {
+                let mut args = Vec::with_capacity(func.args.len());
+                for arg in func.args.iter() {
+                    args.push(
+                        stk.run(|stk| Self::aggregate(stk, graph, txn, arg, docs))
+                            .await?,
+                    );
+                }
+                func.run(args)
+            }
+            Value::Expression(expression) => match expression.as_ref() {
+                Expression::Binary { left, op, right } => {
+                    let left = stk
+                        .run(|stk| Self::aggregate(stk, graph, txn, left, docs))
+                        .await?;
+                    let right = stk
+                        .run(|stk| Self::aggregate(stk, graph, txn, right, docs))
+                        .await?;
+                    let expression = Expression::Binary {
+                        left,
+                        op: op.clone(),
+                        right,
+                    };
+                    stk.run(|stk| expression.evaluate(stk, graph, None)).await
+                }
+                Expression::Unary { op, expr } => {
+                    let expr = stk
+                        .run(|stk| Self::aggregate(stk, graph, txn, expr, docs))
+                        .await?;
+                    let expression = Expression::Unary {
+                        op: op.clone(),
+                        expr,
+                    };
+                    stk.run(|stk| expression.evaluate(stk, graph, None)).await
+                }
+            },
+            _ => {
+                let cur = docs
+                    .first()
+                    .map(|doc| Cursor::from(doc.clone()).with_txn(*txn));
+                stk.run(|stk| expr.evaluate(stk, graph, cur.as_ref())).await
+            }
+        }
+    }
+
+    async fn order(
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        orders: &Orders,
+        values: Vec<Value>,
+    ) -> Result<Vec<Value>, Error> {
+        let mut keyed = Vec::with_capacity(values.len());
+        for value in values {
+            let cur = Cursor::from(value).with_txn(*txn);
+            let mut key = Vec::with_capacity(orders.len());
+            for order in orders.iter() {
+                key.push(
+                    stk.run(|stk| order.idiom.evaluate(stk, graph, Some(&cur)))
+                        .await?,
+                );
+            }
+            keyed.push((key, cur.value));
+        }
+        keyed.sort_by(|(a, _), (b, _)| orders.compare(a, b));
+        Ok(keyed.into_iter().map(|(_, value)| value).collect())
+    }
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use crate::{
+        dbs::{executor::Executor, graph::Graph},
+        err::Error,
+        ql::operator::Operator,
+    };
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn select_where_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET name = 'a', age = 20;
+                CREATE person:2 SET name = 'b', age = 40, meta.tags = ['x']",
+            )
+            .await;
+        let older = executor.run("SELECT name FROM person WHERE age > 30").await;
+        assert_eq!(older, Ok(serde_json::json!([{"name": "b"}])));
+        let projected = executor.run("SELECT name, age * 2 AS double FROM person:1 WHERE age < 30");
+        let double = serde_json::json!([{"name": "a", "double": 40}]);
+        assert_eq!(projected.await, Ok(double));
+        let nested = executor
+            .run("SELECT * FROM person WHERE meta.tags[0] = 'x' OR name = 'c'")
+            .await
+            .unwrap();
+        assert_eq!(nested[0]["id"], "person:2");
+    }
+
+    #[actix_rt::test]
+    async fn order_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET name = 'item10', age = 30;
+                CREATE person:2 SET name = 'Item9', age = 20;
+                CREATE person:3 SET name = 'item1', age = 30",
+            )
+            .await;
+        let names = |names: [&str; 3]| {
+            let names = names.map(|name| serde_json::json!({ "name": name }));
+            Ok(serde_json::json!(names))
+        };
+        let ordered = executor.run("SELECT name FROM person ORDER BY name").await;
+        assert_eq!(ordered, names(["Item9", "item1", "item10"]));
+        let numeric = executor.run("SELECT name FROM person ORDER BY name COLLATE NUMERIC");
+        assert_eq!(numeric.await, names(["item1", "Item9", "item10"]));
+        let limited = executor
+            .run("SELECT name, age FROM person ORDER BY age DESC, name LIMIT 2")
+            .await;
+        let people =
+            serde_json::json!([{"name": "item1", "age": 30}, {"name": "item10", "age": 30}]);
+        assert_eq!(limited, Ok(people));
+    }
+
+    #[actix_rt::test]
+    async fn group_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET city = 'a', age = 10, tags = ['x', 'y'];
+                CREATE person:2 SET city = 'b', age = 20, tags = ['y'];
+                CREATE person:3 SET city = 'a', age = 30, tags = ['x']",
+            )
+            .await;
+        let grouped = executor
+            .run(
+                "SELECT city, count() AS total, math::sum(age) AS sum, math::max(age) AS max,
+                array::group(tags) AS tags FROM person GROUP BY city",
+            )
+            .await;
+        let cities = serde_json::json!([
+            {"city": "a", "total": 2, "sum": 40, "max": 30, "tags": ["x", "y"]},
+            {"city": "b", "total": 1, "sum": 20, "max": 20, "tags": ["y"]},
+        ]);
+        assert_eq!(grouped, Ok(cities));
+        let all = executor
+            .run(
+                "SELECT count() AS total, math::mean(age) AS mean, math::min(age) + 1 AS min
+                FROM person GROUP ALL",
+            )
+            .await;
+        let total = serde_json::json!([{"total": 3, "mean": 20.0, "min": 11}]);
+        assert_eq!(all, Ok(total));
+        let counted = executor
+            .run("SELECT city, count(age > 15) AS older FROM person GROUP city ORDER BY older DESC")
+            .await;
+        let older = serde_json::json!([{"city": "a", "older": 1}, {"city": "b", "older": 1}]);
+        assert_eq!(counted, Ok(older));
+    }
+
+    #[actix_rt::test]
+    async fn select_walk_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET name = 'a'; CREATE person:2 SET name = 'b';
+                CREATE person:3 SET name = 'c'; CREATE person:4 SET name = 'd';
+                RELATE person:1->knows:1->person:2; RELATE person:1->knows:2->person:3;
+                RELATE person:4->knows:3->person:1",
+            )
+            .await;
+        let out = executor
+            .run("SELECT * FROM person:1->knows->person ORDER BY id")
+            .await;
+        let people = serde_json::json!([
+            {"id": "person:2", "name": "b"},
+            {"id": "person:3", "name": "c"}
+        ]);
+        assert_eq!(out, Ok(people));
+        let filtered = executor.run("SELECT name FROM person:1->knows->person WHERE name = 'c'");
+        assert_eq!(filtered.await, Ok(serde_json::json!([{"name": "c"}])));
+        let incoming = executor
+            .run("SELECT name FROM person:1<-knows<-person")
+            .await;
+        assert_eq!(incoming, Ok(serde_json::json!([{"name": "d"}])));
+        let both = executor
+            .run("SELECT id FROM person:1<->knows<->person ORDER BY id")
+            .await;
+        let ids = serde_json::json!([{"id": "person:2"}, {"id": "person:3"}, {"id": "person:4"}]);
+        assert_eq!(both, Ok(ids));
+        let edges = executor
+            .run("SELECT id, out FROM person:1->knows ORDER BY id")
+            .await;
+        let knows = serde_json::json!([
+            {"id": "knows:1", "out": "person:2"},
+            {"id": "knows:2", "out": "person:3"}
+        ]);
+        assert_eq!(edges, Ok(knows));
+        let missing = executor.run("SELECT * FROM person:9->knows->person").await;
+        assert_eq!(missing, Ok(serde_json::json!([])));
+    }
+
+    #[actix_rt::test]
+    async fn unplanned_knn_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE point:1 SET pt = [0, 0], tag = 'a';
+                CREATE point:2 SET pt = [5, 5], tag = 'b'",
+            )
+            .await;
+        let nearest = executor
+            .run("SELECT id FROM point WHERE pt <|1|> [4, 4] AND tag = 'b'")
+            .await;
+        assert_eq!(nearest, Ok(serde_json::json!([{"id": "point:2"}])));
+        for query in [
+            "SELECT id FROM point WHERE pt <|1|> [4, 4] OR tag = 'a'",
+            "SELECT id FROM point WHERE !(pt <|1|> [4, 4])",
+            "SELECT id FROM point:1 WHERE pt <|1|> [4, 4]",
+        ] {
+            let unplanned = Error::UnplannedKnn(Operator::Knn(1, None));
+            assert_eq!(executor.run(query).await, Err(unplanned), "{query}");
+        }
+    }
+}
@@ -1,5 +1,10 @@
 pub(crate) mod entity;
+pub(crate) mod executor;
 pub(crate) mod graph;
+pub(crate) mod index;
 pub(crate) mod iterator;
 pub(crate) mod ops;
+pub(crate) mod schema;
 pub(crate) mod table;
+pub(crate) mod transaction;
+pub(crate) mod version;
@@ -1,38 +1,134 @@
 use crate::{
-    dbs::{entity::Entity, table::Table},
+    dbs::{
+        entity::Entity,
+        graph::Graph,
+        table::Table,
+        transaction::{self, Txn},
+    },
     err::Error,
-    ql::{id::Id, record::Record, value::Value},
+    kvs::log::Log,
+    ql::{record::Record, value::Value},
     resp::Response,
 };
-use actix::{Actor, Handler, Message};
-use std::sync::Arc;
+use actix::{
+    fut::{self, wrap_future},
+    Actor, AtomicResponse, Handler, Message, ResponseFuture,
+};
+use std::{collections::BTreeMap, sync::Arc};
 
 #[derive(Message)]
 #[rtype(result = "Result<Response, Error>")]
-pub struct Create(Id, Vec<(Arc<str>, Value)>);
+pub struct Create(Record, Vec<(Arc<str>, Value)>, Txn);
 
 impl Create {
-    pub fn new<T: Into<Arc<str>>>(id: Id, fields: Vec<(T, Value)>) -> Create {
+    pub fn new<T: Into<Arc<str>>>(id: Record, fields: Vec<(T, Value)>) -> Create {
         let fields = fields.into_iter().map(|(e, v)| (e.into(), v)).collect();
-        Create(id, fields)
+        Create(id, fields, Txn::default())
+    }
+
+    pub fn with_txn(mut self, txn: Txn) -> Self {
+        self.2 = txn;
+        self
+    }
+}
+
+impl Handler<Create> for Graph {
+    type Result = ResponseFuture<Result<Response, Error>>;
+
+    fn handle(&mut self, msg: Create, _ctx: &mut Self::Context) -> Self::Result {
+        let table = self.table(&msg.0.table);
+        Box::pin(async move { table.send(msg).await.unwrap() })
     }
 }
 
 impl Handler<Create> for Table {
+    // Applied in turn with other changes, so two records can not both claim
+    // the same unique value
+    type Result = AtomicResponse<Self, Result<Response, Error>>;
+
+    fn handle(&mut self, msg: Create, _ctx: &mut Self::Context) -> Self::Result {
+        let key: Value = msg.0.id.clone().into();
+        let Some(node) = self.nodes.read().unwrap().get(&key).cloned() else {
+            return AtomicResponse::new(Box::pin(fut::ready(self.create(key, msg))));
+        };
+        // A deleted record is created again on top of its history
+        let mut fields: BTreeMap<_, _> = msg.1.iter().cloned().collect();
+        fields.insert("id".into(), msg.0.clone().into());
+        let indexes = self.indexes.clone();
+        let changed = self.changed.clone();
+        AtomicResponse::new(Box::pin(wrap_future(async move {
+            indexes.read().unwrap().check(&msg.0, &fields)?;
+            let txn = msg.2;
+            let response = node.send(msg).await.unwrap()?;
+            indexes.write().unwrap().insert(&key, &fields);
+            changed.write().unwrap().write(&key, &txn);
+            Ok(response)
+        })))
+    }
+}
+
+impl Table {
+    fn create(&mut self, key: Value, Create(id, fields, txn): Create) -> Result<Response, Error> {
+        let node = Entity::new_node(id.clone(), fields).with_txn(&txn);
+        let fields = node.fields().clone();
+        let at = node.versions().written();
+        self.indexes.read().unwrap().check(&id, &fields)?;
+        let addr = node.start();
+        self.reindex(&key, &fields, &txn);
+        self.insert(key, addr);
+        let put = Log::Put {
+            id: id.clone(),
+            fields: fields.clone(),
+            at,
+        };
+        transaction::write(&txn, &id, put);
+        Ok(Response::Value(fields.into()))
+    }
+}
+
+impl Handler<Create> for Entity {
     type Result = Result<Response, Error>;
 
-    fn handle(&mut self, Create(id, fields): Create, _ctx: &mut Self::Context) -> Self::Result {
-        let table = self.name.clone();
-        if !self.contains(&id.clone().into()) {
-            let node = Entity::new_node(Record::new(table, id.clone()), fields);
-            let fields = node.fields().clone();
-            let addr = node.start();
-            self.insert(id.into(), addr);
-            return Ok(Response::Value(fields.into()));
+    fn handle(
+        &mut self,
+        Create(id, fields, txn): Create,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        if self.is_edge() || self.versions().visible(&txn).is_some() {
+            return Err(Error::CreateError {
+                table: id.table.to_string(),
+                id: id.id.to_string(),
+            });
         }
-        Err(Error::CreateError {
-            table: table.to_string(),
-            id: String::from(id.to_string()),
-        })
+        let mut fields: BTreeMap<_, _> = fields.into_iter().collect();
+        fields.insert("id".into(), id.into());
+        self.commit(fields, &txn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dbs::ops::retrieve::Retrieve;
+
+    #[actix_rt::test]
+    async fn create_test() {
+        let graph = Graph::new().start();
+        let id = Record::new("person", 1);
+        let response = graph
+            .send(Create::new(id.clone(), vec![("name", "a".into())]))
+            .await
+            .unwrap()
+            .unwrap();
+        let Response::Value(Value::Object(fields)) = response else {
+            panic!("record was not created");
+        };
+        assert_eq!(fields.get(&"id".into()), &id.clone().into());
+        let response = graph.send(Retrieve::Record(id.clone())).await.unwrap();
+        assert!(matches!(response, Response::Node(_)));
+        let response = graph
+            .send(Create::new(id, vec![("name", "b".into())]))
+            .await;
+        assert!(response.unwrap().is_err());
     }
 }
@@ -1,32 +1,257 @@
 use crate::{
-    dbs::{graph::Graph, table::Table},
+    dbs::{
+        graph::Graph,
+        index::{self, Kind},
+        ops::get::Visible,
+        table::Table,
+        transaction::Txn,
+    },
     err::Error::{self, DefineError},
+    idx::{analyzer::Analyzer, hnsw::Hnsw, search::Search},
+    kvs::{log::Log, storage},
+    ql::{definition::Definition, id::Id, idiom::Idiom, index::Index, part::Part, record::Record},
     resp::Response,
 };
-use actix::{Actor, Handler, Message};
-use core::panic;
+use actix::{fut::wrap_future, ActorFutureExt, AtomicResponse, Handler, Message, ResponseFuture};
+use std::{sync::Arc, time::Duration};
 
 #[derive(Message, Debug)]
 #[rtype(result = "Result<Response, Error>")]
 #[non_exhaustive]
 pub enum Define {
+    /// Defines a table, a table that exists already is left as it is
     Table(String),
-    Index,
+    Analyzer(Analyzer),
+    Index {
+        name: String,
+        table: String,
+        fields: Vec<Idiom>,
+        index: Index,
+    },
+    /// Sets how long a table keeps past versions of its records, defining
+    /// the table when it does not exist yet
+    History {
+        table: String,
+        history: Duration,
+    },
+    /// Sets whether a table only holds the fields defined on it
+    Schema {
+        table: String,
+        schemafull: bool,
+    },
+    Field {
+        table: String,
+        definition: Definition,
+        source: String,
+    },
+}
+
+/// Fills an index with the records already in a table before adding it
+#[derive(Message, Debug)]
+#[rtype(result = "Result<Response, Error>")]
+struct Build(Kind);
+
+#[derive(Message, Debug)]
+#[rtype(result = "()")]
+struct Retain(Duration);
+
+#[derive(Message, Debug)]
+#[rtype(result = "()")]
+enum Alter {
+    Full(bool),
+    Field(Definition),
 }
 
 impl Handler<Define> for Graph {
-    type Result = Result<Response, Error>;
+    type Result = ResponseFuture<Result<Response, Error>>;
 
     fn handle(&mut self, msg: Define, _ctx: &mut Self::Context) -> Self::Result {
-        let Define::Table(table) = msg else {
-            panic!("how did we get here");
-        };
+        match msg {
+            Define::Table(table) => {
+                self.table(&table);
+                storage::persist(Log::Define(table));
+                Box::pin(async { Ok(Response::None) })
+            }
+            Define::Analyzer(analyzer) => {
+                if self.analyzers.contains_key(&analyzer.name) {
+                    return Box::pin(async move { Err(DefineError(analyzer.name)) });
+                }
+                self.analyzers
+                    .insert(analyzer.name.clone(), Arc::new(analyzer.clone()));
+                storage::persist(Log::Analyzer(analyzer));
+                Box::pin(async { Ok(Response::None) })
+            }
+            Define::Index {
+                name,
+                table,
+                fields,
+                index,
+            } => {
+                let kind = match self.kind(&name, &fields, &index) {
+                    Ok(kind) => kind,
+                    Err(err) => return Box::pin(async move { Err(err) }),
+                };
+                let addr = self.table(&table);
+                Box::pin(async move {
+                    addr.send(Build(kind)).await.unwrap()?;
+                    storage::persist(Log::Index {
+                        table,
+                        name,
+                        fields,
+                        index,
+                    });
+                    Ok(Response::None)
+                })
+            }
+            Define::History { table, history } => {
+                let addr = self.table(&table);
+                Box::pin(async move {
+                    addr.send(Retain(history)).await.unwrap();
+                    storage::persist(Log::History { table, history });
+                    Ok(Response::None)
+                })
+            }
+            Define::Schema { table, schemafull } => {
+                let addr = self.table(&table);
+                Box::pin(async move {
+                    addr.send(Alter::Full(schemafull)).await.unwrap();
+                    storage::persist(Log::Schema { table, schemafull });
+                    Ok(Response::None)
+                })
+            }
+            Define::Field {
+                table,
+                definition,
+                source,
+            } => {
+                let is_path = |part: &Part| matches!(part, Part::Field(_) | Part::All);
+                if !definition.field.iter().all(is_path) {
+                    return Box::pin(async move { Err(DefineError(definition.field.to_string())) });
+                }
+                let addr = self.table(&table);
+                Box::pin(async move {
+                    let alter = Alter::Field(definition.clone());
+                    addr.send(alter).await.unwrap();
+                    storage::persist(Log::Field {
+                        table,
+                        definition,
+                        source,
+                    });
+                    Ok(Response::None)
+                })
+            }
+        }
+    }
+}
 
-        if !self.tables.contains_key(&table) {
-            self.tables.insert(table.clone(), Table::new(table).start());
-        } else {
-            return Err(DefineError(table));
+impl Graph {
+    fn kind(&self, name: &str, fields: &[Idiom], index: &Index) -> Result<Kind, Error> {
+        if !fields.iter().all(index::is_path) {
+            return Err(DefineError(name.to_string()));
         }
-        Ok(Response::None)
+        match index {
+            Index::Search { analyzer, k1, b } => {
+                let [field] = fields else {
+                    return Err(DefineError(name.to_string()));
+                };
+                let Some(analyzer) = self.analyzers.get(analyzer) else {
+                    return Err(DefineError(analyzer.clone()));
+                };
+                let search = Search::new(
+                    name.to_string(),
+                    field.clone(),
+                    analyzer.clone(),
+                    k1.to_float(),
+                    b.to_float(),
+                );
+                Ok(Kind::Search(search))
+            }
+            Index::Hnsw {
+                dimension,
+                distance,
+                m,
+                efc,
+            } => {
+                let [field] = fields else {
+                    return Err(DefineError(name.to_string()));
+                };
+                let hnsw = Hnsw::new(
+                    name.to_string(),
+                    field.clone(),
+                    *dimension as usize,
+                    *distance,
+                    *m as usize,
+                    *efc as usize,
+                );
+                Ok(Kind::Hnsw(hnsw))
+            }
+            index => Ok(Kind::Index(index::Index::new(
+                name.to_string(),
+                fields.to_vec(),
+                *index == Index::Uniq,
+            ))),
+        }
+    }
+}
+
+impl Handler<Retain> for Table {
+    type Result = ();
+
+    fn handle(&mut self, Retain(history): Retain, _ctx: &mut Self::Context) -> Self::Result {
+        self.history = Some(history);
+    }
+}
+
+impl Handler<Alter> for Table {
+    type Result = ();
+
+    fn handle(&mut self, msg: Alter, _ctx: &mut Self::Context) -> Self::Result {
+        let schema = Arc::make_mut(&mut self.schema);
+        match msg {
+            Alter::Full(full) => schema.full = full,
+            Alter::Field(definition) => schema.define(definition),
+        }
+    }
+}
+
+impl Handler<Build> for Table {
+    // Building the index holds up the table so no write slips past it
+    type Result = AtomicResponse<Self, Result<Response, Error>>;
+
+    fn handle(&mut self, msg: Build, _ctx: &mut Self::Context) -> Self::Result {
+        let Build(mut kind) = msg;
+        if self.indexes.read().unwrap().contains(kind.name()) {
+            let name = kind.name().to_string();
+            return AtomicResponse::new(Box::pin(wrap_future(
+                async move { Err(DefineError(name)) },
+            )));
+        }
+
+        let mut nodes: Vec<_> = self
+            .nodes
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, addr)| (key.clone(), addr.clone()))
+            .collect();
+        // Records are indexed in id order so the lowest id keeps a duplicate
+        nodes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let tb = self.name.clone();
+        let build = async move {
+            for (key, node) in nodes {
+                if let Some(fields) = node.send(Visible(Txn::default())).await.unwrap() {
+                    let id = Record::new(tb.clone(), Id::try_from(key.clone())?);
+                    kind.check(&id, &fields)?;
+                    kind.insert(key, &fields);
+                }
+            }
+            Ok(kind)
+        };
+        AtomicResponse::new(Box::pin(wrap_future(build).map(
+            |kind: Result<Kind, Error>, act: &mut Table, _ctx| {
+                act.indexes.write().unwrap().define(kind?);
+                Ok(Response::None)
+            },
+        )))
     }
 }
@@ -1,5 +1,12 @@
-use crate::{dbs::entity::Entity, err::Error, resp::Response};
-use actix::{ActorContext, Handler, Message};
+use crate::{
+    dbs::{entity::Entity, graph::Graph, ops::retrieve::Retrieve, table::Table, transaction::Txn},
+    err::Error,
+    ql::{record::Record, value::Value},
+    resp::Response,
+};
+use actix::{
+    fut::wrap_future, ActorContext, AsyncContext, AtomicResponse, Handler, Message, ResponseFuture,
+};
 
 #[derive(Message)]
 #[rtype(result = "Result<Response, Error>")]
@@ -14,14 +21,90 @@ impl Handler<Delete> for Entity {
     }
 }
 
+#[derive(Message)]
+#[rtype(result = "Result<Response, Error>")]
+pub struct Purge(pub Record, pub Txn);
+
+#[derive(Message)]
+#[rtype(result = "Vec<Record>")]
+pub struct Edges;
+
+impl Handler<Edges> for Entity {
+    type Result = Vec<Record>;
+
+    fn handle(&mut self, _msg: Edges, _ctx: &mut Self::Context) -> Self::Result {
+        match self {
+            Entity::Node { edges, .. } => edges.keys().cloned().collect(),
+            Entity::Edge { .. } => Vec::new(),
+        }
+    }
+}
+
+impl Handler<Purge> for Graph {
+    type Result = ResponseFuture<Result<Response, Error>>;
+
+    fn handle(&mut self, Purge(id, txn): Purge, ctx: &mut Self::Context) -> Self::Result {
+        let Some(table) = self.tables.get(id.table.as_ref()).cloned() else {
+            return Box::pin(async { Ok(Response::None) });
+        };
+        let graph = ctx.address();
+        Box::pin(async move {
+            if let Response::Node(node) = table.send(Retrieve::Record(id.clone())).await.unwrap() {
+                for edge in node.send(Edges).await.unwrap() {
+                    graph.send(Purge(edge, txn)).await.unwrap()?;
+                }
+            }
+            table.send(Purge(id, txn)).await.unwrap()
+        })
+    }
+}
+
+impl Handler<Purge> for Table {
+    // Deletes are applied in turn with other changes, so the indexes follow
+    // the latest state of each record
+    type Result = AtomicResponse<Self, Result<Response, Error>>;
+
+    fn handle(&mut self, msg: Purge, _ctx: &mut Self::Context) -> Self::Result {
+        let key: Value = msg.0.id.clone().into();
+        let node = self.nodes.read().unwrap().get(&key).cloned();
+        let indexes = self.indexes.clone();
+        let changed = self.changed.clone();
+        let deleted = self.deleted.clone();
+        AtomicResponse::new(Box::pin(wrap_future(async move {
+            let Some(node) = node else {
+                return Ok(Response::None);
+            };
+            let txn = msg.1;
+            let response = node.send(msg).await.unwrap()?;
+            indexes.write().unwrap().remove(&key);
+            changed.write().unwrap().write(&key, &txn);
+            deleted.write().unwrap().insert(key);
+            Ok(response)
+        })))
+    }
+}
+
+/// A deleted entity keeps running, as a version that ends its history, so
+/// transactions that started before the delete still read it
+impl Handler<Purge> for Entity {
+    type Result = Result<Response, Error>;
+
+    fn handle(&mut self, Purge(_, txn): Purge, _ctx: &mut Self::Context) -> Self::Result {
+        self.delete(&txn)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
 
     use super::*;
     use crate::{
-        dbs::{entity::Path, ops::relate::Relate},
-        ql::record::Record,
+        dbs::{
+            entity::Path,
+            ops::{create::Create, get::Get, relate::Connect},
+        },
+        ql::{fields::Field, record::Record},
     };
     use actix::Actor;
 
@@ -40,19 +123,25 @@ mod test {
 
     #[actix::test]
     async fn delete_test() {
+        let graph = Graph::new().start();
         let fields: Vec<(Arc<str>, _)> = Vec::new();
         let a_id = Record::new("a", "1");
         let b_id = Record::new("b", "2");
-        let a = Entity::new_node(a_id, fields.clone()).start();
+        let a = Entity::new_node(a_id.clone(), fields.clone()).start();
         let b = Entity::new_node(b_id.clone(), fields.clone()).start();
-        let _ = a
-            .send(Relate {
-                edge: "e_1".to_string(),
-                fields: vec![],
+        graph
+            .send(Connect {
+                id: Record::new("e_1", 1),
+                fields: Vec::new(),
                 org_id: b_id,
                 origin: b.clone(),
+                dest_id: a_id.clone(),
+                destination: a.clone(),
+                txn: Txn::default(),
             })
-            .await;
+            .await
+            .unwrap()
+            .unwrap();
         b.send(Delete).await.unwrap().unwrap();
         let res = a.send(GetEdges).await.unwrap().unwrap();
         assert!(res.is_empty())
@@ -60,33 +149,65 @@ mod test {
 
     #[actix::test]
     async fn delete_two_test() {
+        let graph = Graph::new().start();
         let fields: Vec<(Arc<str>, _)> = Vec::new();
         let a_id = Record::new("a", "1");
         let b_id = Record::new("b", "2");
         let c_id = Record::new("c", "2");
-        let a = Entity::new_node(a_id, fields.clone()).start();
+        let a = Entity::new_node(a_id.clone(), fields.clone()).start();
         let b = Entity::new_node(b_id.clone(), fields.clone()).start();
         let c = Entity::new_node(c_id.clone(), fields.clone()).start();
-        let _ = a
-            .send(Relate {
-                edge: "e_1".to_string(),
-                fields: vec![],
+        graph
+            .send(Connect {
+                id: Record::new("e_1", 1),
+                fields: Vec::new(),
                 org_id: b_id,
                 origin: b.clone(),
+                dest_id: a_id.clone(),
+                destination: a.clone(),
+                txn: Txn::default(),
             })
-            .await;
-        let _ = a
-            .send(Relate {
-                edge: "e_2".to_string(),
-                fields: vec![],
+            .await
+            .unwrap()
+            .unwrap();
+        graph
+            .send(Connect {
+                id: Record::new("e_2", 1),
+                fields: Vec::new(),
                 org_id: c_id,
                 origin: c.clone(),
+                dest_id: a_id.clone(),
+                destination: a.clone(),
+                txn: Txn::default(),
             })
-            .await;
+            .await
+            .unwrap()
+            .unwrap();
         b.send(Delete).await.unwrap().unwrap();
         let res = a.send(GetEdges).await.unwrap().unwrap();
         let (id, _) = res.first().unwrap();
         let table = id.table.to_string();
         assert_eq!("e_2", table)
     }
+
+    #[actix::test]
+    async fn purge_test() {
+        let graph = Graph::new().start();
+        let id = Record::new("a", 1);
+        graph
+            .send(Create::new(id.clone(), Vec::<(Arc<str>, _)>::new()))
+            .await
+            .unwrap()
+            .unwrap();
+        graph
+            .send(Purge(id.clone(), Txn::default()))
+            .await
+            .unwrap()
+            .unwrap();
+        let Response::Node(node) = graph.send(Retrieve::Record(id)).await.unwrap() else {
+            panic!("deleted record was dropped before its history");
+        };
+        let get = Get::new(Field::WildCard.into(), None, graph.clone());
+        assert_eq!(node.send(get).await.unwrap(), Ok(Response::None));
+    }
 }
@@ -0,0 +1,30 @@
+use crate::dbs::{graph::Graph, schema::Schema, table::Table};
+use actix::{Handler, Message, MessageResult, ResponseFuture};
+use std::sync::Arc;
+
+/// The schema of a table, a table that does not exist has an empty one
+#[derive(Message, Debug)]
+#[rtype(result = "Arc<Schema>")]
+pub struct Describe(pub String);
+
+impl Handler<Describe> for Graph {
+    type Result = ResponseFuture<Arc<Schema>>;
+
+    fn handle(&mut self, msg: Describe, _ctx: &mut Self::Context) -> Self::Result {
+        let table = self.tables.get(&msg.0).cloned();
+        Box::pin(async move {
+            match table {
+                Some(table) => table.send(msg).await.unwrap(),
+                None => Arc::default(),
+            }
+        })
+    }
+}
+
+impl Handler<Describe> for Table {
+    type Result = MessageResult<Describe>;
+
+    fn handle(&mut self, _msg: Describe, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.schema.clone())
+    }
+}
@@ -1,5 +1,11 @@
 use crate::{
-    dbs::entity::Entity,
+    dbs::{
+        entity::{self, Entity},
+        graph::Graph,
+        index,
+        ops::hits::Hits,
+        transaction::Txn,
+    },
     err::Error,
     ql::{
         condition::Condition,
@@ -8,48 +14,117 @@ use crate::{
     },
     resp::Response,
 };
-use actix::{Handler, Message};
-use std::collections::BTreeMap;
+use actix::{Addr, Handler, Message, ResponseFuture};
+use reblessive::TreeStack;
+use std::{collections::BTreeMap, sync::Arc};
 
 #[derive(Message, Clone)]
 #[rtype(result = "Result<Response, Error>")]
 pub struct Get {
     pub fields: Fields,
     pub filter: Option<Condition>,
+    pub graph: Addr<Graph>,
+    pub txn: Txn,
 }
 
 impl Get {
-    pub fn new(fields: Fields, filter: Option<Condition>) -> Self {
-        Get { fields, filter }
+    /// Record links in fields and filters are resolved through the graph
+    pub fn new(fields: Fields, filter: Option<Condition>, graph: Addr<Graph>) -> Self {
+        Get {
+            fields,
+            filter,
+            graph,
+            txn: Txn::default(),
+        }
+    }
+
+    /// Reads the entity as the transaction sees it, rather than its latest
+    /// committed state
+    pub fn with_txn(mut self, txn: Txn) -> Self {
+        self.txn = txn;
+        self
     }
 }
 
-impl Handler<Get> for Entity {
-    type Result = Result<Response, Error>;
+/// The fields of an entity as the transaction sees them, unset when it does
+/// not see the entity
+#[derive(Message)]
+#[rtype(result = "Option<BTreeMap<Arc<str>, Value>>")]
+pub struct Visible(pub Txn);
+
+impl Handler<Visible> for Entity {
+    type Result = Option<BTreeMap<Arc<str>, Value>>;
+
+    fn handle(&mut self, Visible(txn): Visible, _ctx: &mut Self::Context) -> Self::Result {
+        self.versions().visible(&txn).cloned()
+    }
+}
 
-    fn handle(&mut self, Get { fields, filter }: Get, _ctx: &mut Self::Context) -> Self::Result {
-        if let Some(filter) = filter {
-            // TODO have to configure filtering
-            // let check = filter.evaluate(&self.fields().clone().into())?;
+impl Handler<Get> for Entity {
+    type Result = ResponseFuture<Result<Response, Error>>;
 
-            // if !check.is_truthy() {
-            //     return Ok(Response::None);
-            // }
+    fn handle(
+        &mut self,
+        Get {
+            fields,
+            filter,
+            graph,
+            txn,
+        }: Get,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let Some(visible) = self.versions().visible(&txn) else {
+            return Box::pin(async { Ok(Response::None) });
         };
+        // Only the version the transaction sees is read
+        let visible = visible.clone();
+        let id = self.id().clone();
+        Box::pin(async move {
+            let simple = fields.iter().all(|f| matches!(f, Field::WildCard));
+            if filter.is_none() && simple {
+                return Ok(Response::Value(visible.into()));
+            }
 
-        let mut object = BTreeMap::new();
-        for field in fields {
-            match field {
-                Field::WildCard => object.append(&mut self.fields().clone()),
-                Field::Single { expr, alias } => {
-                    let key = alias.map_or(expr.to_string().into(), Into::into);
-                    let value = self.evaluate(&expr).unwrap_or(Value::None);
-                    object.insert(key, value);
+            // Matches operators a search index answers are settled up front
+            let mut searches = Vec::new();
+            if let Some(filter) = &filter {
+                index::searches(&filter.0, &mut searches);
+            }
+            let filter = match searches.is_empty() {
+                true => filter,
+                false => {
+                    let hits = Hits(id, searches);
+                    let matches = graph.send(hits).await.unwrap();
+                    filter.map(|filter| Condition(index::resolve(&filter.0, &matches)))
                 }
             };
-        }
+            let mut stack = TreeStack::new();
+            stack
+                .enter(|stk| async move {
+                    if let Some(filter) = filter {
+                        if !entity::check(stk, &graph, &txn, &visible, &filter).await? {
+                            return Ok(Response::None);
+                        }
+                    }
+
+                    let mut object = BTreeMap::new();
+                    for field in fields {
+                        match field {
+                            Field::WildCard => object.append(&mut visible.clone()),
+                            Field::Single { expr, alias } => {
+                                let key = alias.map_or(expr.to_string().into(), Into::into);
+                                let value =
+                                    entity::evaluate(stk, &graph, &txn, &visible, &expr).await?;
+                                object.insert(key, value);
+                            }
+                        };
+                    }
 
-        Ok(Response::Value(object.into()))
+                    Ok(Response::Value(object.into()))
+                })
+                .finish()
+                .await
+        })
     }
 }
 
@@ -61,7 +136,6 @@ mod test {
         part::Part, record::Record,
     };
     use actix::Actor;
-    use std::sync::Arc;
 
     #[actix_rt::test]
     async fn get_wildcard_alias_test() {
@@ -80,6 +154,7 @@ mod test {
                     },
                 ]),
                 None,
+                Graph::new().start(),
             ))
             .await
             .unwrap();
@@ -103,7 +178,11 @@ mod test {
         );
         let node = node.start();
         let res = node
-            .send(Get::new(Fields(vec![Field::WildCard]), None))
+            .send(Get::new(
+                Fields(vec![Field::WildCard]),
+                None,
+                Graph::new().start(),
+            ))
             .await
             .unwrap();
 
@@ -132,6 +211,7 @@ mod test {
                     op: Operator::Eq,
                     right: "c".into(),
                 })))),
+                Graph::new().start(),
             ))
             .await
             .unwrap();
@@ -161,6 +241,7 @@ mod test {
                     op: Operator::Eq,
                     right: "c".into(),
                 })))),
+                Graph::new().start(),
             ))
             .await
             .unwrap();
@@ -190,6 +271,7 @@ mod test {
                     },
                 ]),
                 None,
+                Graph::new().start(),
             ))
             .await
             .unwrap();
@@ -223,6 +305,7 @@ mod test {
                     },
                 ]),
                 None,
+                Graph::new().start(),
             ))
             .await
             .unwrap();
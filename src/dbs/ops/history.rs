@@ -0,0 +1,16 @@
+use crate::dbs::{entity::Entity, version::Version};
+use actix::{Handler, Message, MessageResult};
+
+/// The committed versions of an entity, oldest first
+#[derive(Message, Debug)]
+#[rtype(result = "Vec<Version>")]
+pub struct History;
+
+impl Handler<History> for Entity {
+    type Result = MessageResult<History>;
+
+    fn handle(&mut self, _msg: History, _ctx: &mut Self::Context) -> Self::Result {
+        let versions = self.versions().iter().filter(|v| v.txn.is_none());
+        MessageResult(versions.cloned().collect())
+    }
+}
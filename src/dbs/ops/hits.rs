@@ -0,0 +1,35 @@
+use crate::{
+    dbs::{graph::Graph, index::Searched, table::Table},
+    idx::search::Matches,
+    ql::{record::Record, value::Value},
+};
+use actix::{Handler, Message, MessageResult, ResponseFuture};
+
+/// How a record fares against the matches operators of a condition, for
+/// the operators a search index of its table answers
+#[derive(Message, Debug)]
+#[rtype(result = "Matches")]
+pub struct Hits(pub Record, pub Vec<Searched>);
+
+impl Handler<Hits> for Graph {
+    type Result = ResponseFuture<Matches>;
+
+    fn handle(&mut self, msg: Hits, _ctx: &mut Self::Context) -> Self::Result {
+        let table = self.tables.get(&*msg.0.table).cloned();
+        Box::pin(async move {
+            match table {
+                Some(table) => table.send(msg).await.unwrap(),
+                None => Matches::new(),
+            }
+        })
+    }
+}
+
+impl Handler<Hits> for Table {
+    type Result = MessageResult<Hits>;
+
+    fn handle(&mut self, Hits(id, searches): Hits, _ctx: &mut Self::Context) -> Self::Result {
+        let key = Value::from(id.id);
+        MessageResult(self.indexes.read().unwrap().hits(&key, &searches))
+    }
+}
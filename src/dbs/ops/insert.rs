@@ -1,6 +1,7 @@
 use crate::{
-    dbs::{entity::Entity, table::Table},
+    dbs::{entity::Entity, table::Table, transaction::Txn},
     err::Error::{self, CreateError},
+    kvs::{log::Log, storage},
     ql::{id::Id, record::Record, value::Value},
     resp::Response,
 };
@@ -24,10 +25,20 @@ impl Handler<Insert> for Table {
     fn handle(&mut self, Insert(id, fields): Insert, _ctx: &mut Self::Context) -> Self::Result {
         let table = self.name.clone();
         if !self.contains(&id.clone().into()) {
-            let node = Entity::new_node(Record::new(table, id.clone()), fields);
+            let record = Record::new(table, id.clone());
+            let node = Entity::new_node(record.clone(), fields);
             let fields = node.fields().clone();
+            let at = node.versions().written();
+            self.indexes.read().unwrap().check(&record, &fields)?;
             let addr = node.start();
-            self.insert(id.into(), addr);
+            let key = id.into();
+            self.reindex(&key, &fields, &Txn::default());
+            self.insert(key, addr);
+            storage::persist(Log::Put {
+                id: record,
+                fields: fields.clone(),
+                at,
+            });
             return Ok(Response::Value(fields.into()));
         }
 
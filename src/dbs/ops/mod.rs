@@ -1,10 +1,15 @@
 pub mod create;
 pub mod define;
 pub mod delete;
+pub mod describe;
 pub mod get;
+pub mod history;
+pub mod hits;
 pub mod insert;
+pub mod modify;
 pub mod relate;
 pub mod remove;
+pub mod restore;
 pub mod retrieve;
 pub mod update;
 pub mod walk;
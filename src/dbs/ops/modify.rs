@@ -0,0 +1,155 @@
+use crate::{
+    dbs::{entity::Entity, graph::Graph, index::Indexes, table::Table, transaction::Txn},
+    err::Error,
+    ql::{object::Object, record::Record, value::Value},
+    resp::Response,
+};
+use actix::{fut::wrap_future, AtomicResponse, Handler, Message, ResponseFuture};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+/// A change to the fields of an entity
+pub trait Change: Send + 'static {
+    /// The fields the entity holds once the change is applied
+    fn apply(&self, entity: &Entity) -> BTreeMap<Arc<str>, Value>;
+}
+
+/// Sends a change to a record through its table, so the table indexes
+/// follow the fields the record ends up with
+#[derive(Message)]
+#[rtype(result = "Result<Response, Error>")]
+pub struct Modify<M>(pub Record, pub M, pub Txn);
+
+impl<M> Handler<Modify<M>> for Graph
+where
+    M: Change,
+    Table: Handler<Modify<M>>,
+{
+    type Result = ResponseFuture<Result<Response, Error>>;
+
+    fn handle(&mut self, msg: Modify<M>, _ctx: &mut Self::Context) -> Self::Result {
+        let Some(table) = self.tables.get(msg.0.table.as_ref()).cloned() else {
+            return Box::pin(async move { Err(Error::RecordNotFound(msg.0.to_string())) });
+        };
+        Box::pin(async move { table.send(msg).await.unwrap() })
+    }
+}
+
+impl<M> Handler<Modify<M>> for Table
+where
+    M: Change,
+{
+    // Changes are applied one at a time so two records can not both claim
+    // the same unique value
+    type Result = AtomicResponse<Self, Result<Response, Error>>;
+
+    fn handle(
+        &mut self,
+        Modify(id, change, txn): Modify<M>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let key: Value = id.id.clone().into();
+        let node = self.nodes.read().unwrap().get(&key).cloned();
+        let indexes = self.indexes.clone();
+        let changed = self.changed.clone();
+        AtomicResponse::new(Box::pin(wrap_future(async move {
+            let Some(node) = node else {
+                return Err(Error::RecordNotFound(id.to_string()));
+            };
+            let checked = Checked {
+                change,
+                indexes: indexes.clone(),
+                txn,
+            };
+            let response = node.send(checked).await.unwrap()?;
+            if let Response::Value(Value::Object(Object(fields))) = &response {
+                indexes.write().unwrap().insert(&key, fields);
+                changed.write().unwrap().write(&key, &txn);
+            }
+            Ok(response)
+        })))
+    }
+}
+
+/// A change the entity only applies when its fields stay unique in the
+/// indexes of its table
+#[derive(Message)]
+#[rtype(result = "Result<Response, Error>")]
+struct Checked<M> {
+    change: M,
+    indexes: Arc<RwLock<Indexes>>,
+    txn: Txn,
+}
+
+impl<M> Handler<Checked<M>> for Entity
+where
+    M: Change,
+{
+    type Result = Result<Response, Error>;
+
+    fn handle(
+        &mut self,
+        Checked {
+            change,
+            indexes,
+            txn,
+        }: Checked<M>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let fields = change.apply(self);
+        indexes.read().unwrap().check(self.id(), &fields)?;
+        self.commit(fields, &txn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dbs::ops::{create::Create, remove::Remove, update::Update};
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn modify_test() {
+        let graph = Graph::new().start();
+        let id = Record::new("a", 1);
+        graph
+            .send(Create::new(id.clone(), vec![("x", 1.into())]))
+            .await
+            .unwrap()
+            .unwrap();
+        graph
+            .send(Modify(
+                id.clone(),
+                Update::new(vec![("y", 2.into())]),
+                Txn::default(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        let response = graph
+            .send(Modify(
+                id.clone(),
+                Remove::Field(String::from("x")),
+                Txn::default(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        let Response::Value(Value::Object(fields)) = response else {
+            panic!("record was not modified");
+        };
+        assert_eq!(fields.get(&"x".into()), &Value::None);
+        assert_eq!(fields.get(&"y".into()), &2.into());
+        let response = graph
+            .send(Modify(
+                Record::new("a", 2),
+                Update::new(vec![("y", 2.into())]),
+                Txn::default(),
+            ))
+            .await
+            .unwrap();
+        assert!(response.is_err());
+    }
+}
@@ -1,42 +1,84 @@
 use crate::{
-    dbs::entity::Entity,
+    dbs::{
+        entity::Entity,
+        graph::Graph,
+        table::Table,
+        transaction::{self, Txn},
+    },
     err::Error,
+    kvs::log::Log,
     ql::{record::Record, value::Value},
     resp::Response,
 };
-use actix::{Actor, Addr, AsyncContext, Handler, Message};
+use actix::{Actor, Addr, Handler, Message, ResponseFuture};
+use std::sync::Arc;
 
 #[derive(Message)]
 #[rtype(result = "Result<Response, Error>")]
-pub struct Relate {
-    pub edge: String,
-    pub fields: Vec<(String, Value)>,
+pub struct Connect {
+    pub id: Record,
+    pub fields: Vec<(Arc<str>, Value)>,
     pub org_id: Record,
     pub origin: Addr<Entity>,
+    pub dest_id: Record,
+    pub destination: Addr<Entity>,
+    pub txn: Txn,
 }
 
-impl Handler<Relate> for Entity {
+impl Handler<Connect> for Graph {
+    type Result = ResponseFuture<Result<Response, Error>>;
+
+    fn handle(&mut self, msg: Connect, _ctx: &mut Self::Context) -> Self::Result {
+        let table = self.table(&msg.id.table);
+        Box::pin(async move { table.send(msg).await.unwrap() })
+    }
+}
+
+impl Handler<Connect> for Table {
     type Result = Result<Response, Error>;
 
     fn handle(
         &mut self,
-        Relate {
-            edge,
+        Connect {
+            id,
             fields,
             org_id,
             origin,
-        }: Relate,
-        ctx: &mut Self::Context,
+            dest_id,
+            destination,
+            txn,
+        }: Connect,
+        _ctx: &mut Self::Context,
     ) -> Self::Result {
-        Entity::new_edge(
-            edge,
-            self.id().clone(),
-            org_id,
-            ctx.address(),
+        if self.contains(&id.id.clone().into()) {
+            return Err(Error::CreateError {
+                table: id.table.to_string(),
+                id: id.id.to_string(),
+            });
+        }
+        let key = id.id.clone().into();
+        let edge = Entity::new_edge(
+            id.clone(),
+            dest_id.clone(),
+            org_id.clone(),
+            destination,
             origin,
             fields,
         )
-        .start();
-        Ok(Response::None)
+        .with_txn(&txn);
+        let fields = edge.fields().clone();
+        let at = edge.versions().written();
+        self.indexes.read().unwrap().check(&id, &fields)?;
+        self.reindex(&key, &fields, &txn);
+        self.insert(key, edge.start());
+        let relate = Log::Relate {
+            id: id.clone(),
+            origin: org_id,
+            destination: dest_id,
+            fields: fields.clone(),
+            at,
+        };
+        transaction::write(&txn, &id, relate);
+        Ok(Response::Value(fields.into()))
     }
 }
@@ -1,6 +1,11 @@
-use crate::{dbs::entity::Entity, err::Error, ql::record::Record, resp::Response};
+use crate::{
+    dbs::{entity::Entity, ops::modify::Change, transaction::Txn},
+    err::Error,
+    ql::{record::Record, value::Value},
+    resp::Response,
+};
 use actix::{Handler, Message};
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 #[derive(Message)]
 #[rtype(result = "Result<Response, Error>")]
@@ -8,6 +13,17 @@ pub enum Remove {
     Edge(Record),
     Field(String),
 }
+
+impl Change for Remove {
+    fn apply(&self, entity: &Entity) -> BTreeMap<Arc<str>, Value> {
+        let mut fields = entity.fields().clone();
+        if let Remove::Field(field) = self {
+            fields.remove(field.as_str());
+        }
+        fields
+    }
+}
+
 impl Handler<Remove> for Entity {
     type Result = Result<Response, Error>;
 
@@ -18,16 +34,10 @@ impl Handler<Remove> for Entity {
                     edges.remove(&id);
                 }
             }
-            Remove::Field(field) => match self {
-                Entity::Node { fields, .. } => {
-                    let field: Arc<str> = field.into();
-                    fields.remove(&field);
-                }
-                Entity::Edge { fields, .. } => {
-                    let field: Arc<str> = field.into();
-                    fields.remove(&field);
-                }
-            },
+            Remove::Field(_) => {
+                let update = msg.apply(self);
+                return self.commit(update, &Txn::default());
+            }
         }
         Ok(Response::Value(self.fields().clone().into()))
     }
@@ -39,7 +49,7 @@ mod test {
 
     use super::*;
     use crate::{
-        dbs::ops::get::Get,
+        dbs::{graph::Graph, ops::get::Get},
         ql::{
             fields::{Field, Fields},
             value::Value,
@@ -59,7 +69,11 @@ mod test {
             .unwrap()
             .unwrap();
         let result: Value = node
-            .send(Get::new(Fields(vec![Field::WildCard]), None))
+            .send(Get::new(
+                Fields(vec![Field::WildCard]),
+                None,
+                Graph::new().start(),
+            ))
             .await
             .unwrap()
             .unwrap()
@@ -0,0 +1,63 @@
+use crate::{
+    dbs::{entity::Entity, graph::Graph, table::Table},
+    ql::{record::Record, value::Value},
+};
+use actix::{fut::wrap_future, AtomicResponse, Handler, Message, ResponseFuture};
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Puts back a committed version of a record at the time it was written,
+/// unset fields put back a delete, and returns the latest committed fields
+#[derive(Message)]
+#[rtype(result = "Option<BTreeMap<Arc<str>, Value>>")]
+pub struct Restore {
+    pub id: Record,
+    pub fields: Option<BTreeMap<Arc<str>, Value>>,
+    pub at: u64,
+}
+
+impl Handler<Restore> for Graph {
+    type Result = ResponseFuture<Option<BTreeMap<Arc<str>, Value>>>;
+
+    fn handle(&mut self, msg: Restore, _ctx: &mut Self::Context) -> Self::Result {
+        let table = self.table(&msg.id.table);
+        Box::pin(async move { table.send(msg).await.unwrap() })
+    }
+}
+
+impl Handler<Restore> for Table {
+    type Result = AtomicResponse<Self, Option<BTreeMap<Arc<str>, Value>>>;
+
+    fn handle(&mut self, msg: Restore, _ctx: &mut Self::Context) -> Self::Result {
+        let key: Value = msg.id.id.clone().into();
+        let node = self.nodes.read().unwrap().get(&key).cloned();
+        let indexes = self.indexes.clone();
+        let deleted = self.deleted.clone();
+        AtomicResponse::new(Box::pin(wrap_future(async move {
+            let node = node?;
+            let fields = node.send(msg).await.unwrap();
+            match &fields {
+                Some(fields) => {
+                    indexes.write().unwrap().insert(&key, fields);
+                    deleted.write().unwrap().remove(&key);
+                }
+                None => {
+                    indexes.write().unwrap().remove(&key);
+                    deleted.write().unwrap().insert(key);
+                }
+            }
+            fields
+        })))
+    }
+}
+
+impl Handler<Restore> for Entity {
+    type Result = Option<BTreeMap<Arc<str>, Value>>;
+
+    fn handle(&mut self, msg: Restore, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(fields) = &msg.fields {
+            self.replace(fields.clone());
+        }
+        self.versions_mut().restore(msg.fields, msg.at);
+        self.versions().committed().cloned()
+    }
+}
@@ -1,16 +1,28 @@
 use crate::{
-    dbs::{graph::Graph, table::Table},
-    ql::record::Record,
+    dbs::{
+        entity::Entity,
+        graph::Graph,
+        index::{self, Knn},
+        ops::get::Visible,
+        table::Table,
+        transaction::Txn,
+    },
+    err::Error,
+    ql::{condition::Condition, record::Record, value::Value},
     resp::Response,
 };
 use actix::{Addr, Handler, Message, ResponseFuture};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::collections::HashSet;
 
 #[derive(Message)]
 #[rtype(result = "Response")]
 pub enum Retrieve {
     Table(String),
     Record(Record),
+    /// The records of a table that may match a condition as the transaction
+    /// sees them, narrowed down by an index when one covers it
+    Where(String, Condition, Txn),
 }
 
 impl Retrieve {
@@ -18,6 +30,24 @@ impl Retrieve {
         Some(match self {
             Retrieve::Record(record) => graph.tables.get(&record.table.to_string())?,
             Retrieve::Table(table) => graph.tables.get(table)?,
+            Retrieve::Where(table, ..) => graph.tables.get(table)?,
+        })
+    }
+}
+
+impl TryFrom<Value> for Retrieve {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Value::Record(record) => Retrieve::Record(*record),
+            Value::Table(table) => Retrieve::Table(table.0),
+            value => {
+                return Err(Error::IncorrectValueType {
+                    expected: String::from("Table or Record"),
+                    result: value,
+                })
+            }
         })
     }
 }
@@ -28,7 +58,12 @@ impl Handler<Retrieve> for Graph {
     fn handle(&mut self, retrieve: Retrieve, _ctx: &mut Self::Context) -> Self::Result {
         let table = retrieve.table(self);
         let Some(table) = table else {
-            return Box::pin(async { Response::None });
+            return Box::pin(async move {
+                match retrieve {
+                    Retrieve::Table(_) | Retrieve::Where(..) => Response::Nodes(Vec::new()),
+                    Retrieve::Record(_) => Response::None,
+                }
+            });
         };
         let table = table.clone();
 
@@ -41,9 +76,53 @@ impl Handler<Retrieve> for Table {
 
     fn handle(&mut self, msg: Retrieve, _ctx: &mut Self::Context) -> Self::Result {
         let nodes = self.nodes.clone();
+        // A KNN operator no vector index answers is worked out by hand over
+        // the records the other predicates narrow down to
+        let (ids, knn, txn) = match &msg {
+            Retrieve::Where(_, cond, txn) => {
+                let indexes = self.indexes.read().unwrap();
+                // The indexes hold the latest version of each record, the
+                // records the transaction may see another version of are
+                // checked as well, and enough neighbours are asked for to
+                // make up for them
+                let changed = self.changed.read().unwrap().since(txn);
+                let with = |ids: Vec<Value>| merge(ids, &changed);
+                let (ids, knn) = match Knn::find(cond) {
+                    Some(knn) => {
+                        let wide = Knn {
+                            k: knn.k + changed.len(),
+                            ..knn.clone()
+                        };
+                        match indexes.nearest(&wide) {
+                            Some(ids) if changed.is_empty() => (Some(ids), None),
+                            Some(ids) => (Some(with(ids)), Some(knn)),
+                            None => (indexes.lookup(cond).map(with), Some(knn)),
+                        }
+                    }
+                    None => (indexes.lookup(cond).map(with), None),
+                };
+                (ids, knn, *txn)
+            }
+            _ => (None, None, Txn::default()),
+        };
         Box::pin(async move {
             match msg {
-                Retrieve::Table(_) => nodes
+                Retrieve::Where(..) if ids.is_some() || knn.is_some() => {
+                    let found: Vec<_> = {
+                        let nodes = nodes.read().unwrap();
+                        match ids {
+                            Some(ids) => {
+                                ids.iter().filter_map(|id| nodes.get(id).cloned()).collect()
+                            }
+                            None => nodes.values().cloned().collect(),
+                        }
+                    };
+                    match knn {
+                        Some(knn) => nearest(found, &knn, &txn).await.into(),
+                        None => found.into(),
+                    }
+                }
+                Retrieve::Table(_) | Retrieve::Where(..) => nodes
                     .read()
                     .unwrap()
                     .par_iter()
@@ -59,3 +138,28 @@ impl Handler<Retrieve> for Table {
         })
     }
 }
+
+/// The ids along with the changed ones they do not hold
+fn merge(mut ids: Vec<Value>, changed: &[Value]) -> Vec<Value> {
+    let held: HashSet<_> = ids.iter().cloned().collect();
+    ids.extend(changed.iter().filter(|id| !held.contains(id)).cloned());
+    ids
+}
+
+/// The k nodes whose vector, as the transaction sees it, is nearest to the
+/// query, nearest first
+async fn nearest(nodes: Vec<Addr<Entity>>, knn: &Knn, txn: &Txn) -> Vec<Addr<Entity>> {
+    let distance = knn.distance.unwrap_or_default();
+    let mut near = Vec::new();
+    for node in nodes {
+        let Some(fields) = node.send(Visible(*txn)).await.unwrap() else {
+            continue;
+        };
+        let vector = index::pick(&fields, &knn.field).to_vector();
+        if let Some(vector) = vector.filter(|v| v.len() == knn.vector.len()) {
+            near.push((distance.compute(&vector, &knn.vector), node));
+        }
+    }
+    near.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    near.into_iter().take(knn.k).map(|(_, node)| node).collect()
+}
@@ -1,34 +1,60 @@
-use crate::{dbs::entity::Entity, err::Error, ql::value::Value, resp::Response};
+use crate::{
+    dbs::{entity::Entity, ops::modify::Change, transaction::Txn},
+    err::Error,
+    ql::value::Value,
+    resp::Response,
+};
 use actix::{Handler, Message};
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 #[derive(Message)]
 #[rtype(result = "Result<Response, Error>")]
-pub struct Update(pub Vec<(Arc<str>, Value)>);
+#[non_exhaustive]
+pub enum Update {
+    Merge(Vec<(Arc<str>, Value)>),
+    Replace(Vec<(Arc<str>, Value)>),
+}
 
 impl Update {
     pub fn new<T: Into<Arc<str>>>(fields: Vec<(T, Value)>) -> Update {
         let fields = fields.into_iter().map(|(e, v)| (e.into(), v)).collect();
-        Update(fields)
+        Update::Merge(fields)
+    }
+
+    pub fn replace<T: Into<Arc<str>>>(fields: Vec<(T, Value)>) -> Update {
+        let fields = fields.into_iter().map(|(e, v)| (e.into(), v)).collect();
+        Update::Replace(fields)
+    }
+}
+
+impl Change for Update {
+    fn apply(&self, entity: &Entity) -> BTreeMap<Arc<str>, Value> {
+        let mut fields = entity.fields().clone();
+        match self {
+            Update::Merge(update) => fields.extend(update.iter().cloned()),
+            Update::Replace(update) => {
+                let mut update: BTreeMap<_, _> = update.iter().cloned().collect();
+                if entity.is_edge() {
+                    for key in ["in", "out"] {
+                        if let Some(val) = fields.remove(key) {
+                            update.insert(key.into(), val);
+                        }
+                    }
+                }
+                fields = update;
+            }
+        }
+        fields.insert("id".into(), entity.id().clone().into());
+        fields
     }
 }
 
 impl Handler<Update> for Entity {
     type Result = Result<Response, Error>;
 
-    fn handle(&mut self, Update(fields): Update, _ctx: &mut Self::Context) -> Self::Result {
-        for (field, value) in fields {
-            let fields = match self {
-                Entity::Node { fields, .. } => fields,
-                Entity::Edge { fields, .. } => fields,
-            };
-            if let Some(val) = fields.get_mut(&field) {
-                *val = value.into();
-            } else {
-                fields.insert(field, value);
-            };
-        }
-        Ok(Response::Value(self.fields().clone().into()))
+    fn handle(&mut self, msg: Update, _ctx: &mut Self::Context) -> Self::Result {
+        let update = msg.apply(self);
+        self.commit(update, &Txn::default())
     }
 }
 
@@ -38,7 +64,7 @@ mod test {
 
     use super::*;
     use crate::{
-        dbs::ops::get::Get,
+        dbs::{graph::Graph, ops::get::Get},
         ql::{
             fields::{Field, Fields},
             record::Record,
@@ -58,7 +84,11 @@ mod test {
         .unwrap()
         .unwrap();
         let result: Value = a
-            .send(Get::new(Fields(vec![Field::WildCard]), None))
+            .send(Get::new(
+                Fields(vec![Field::WildCard]),
+                None,
+                Graph::new().start(),
+            ))
             .await
             .unwrap()
             .unwrap()
@@ -1,34 +1,51 @@
 use crate::{
-    dbs::{entity::Entity, graph::Graph},
+    dbs::{
+        entity::{self, Entity},
+        graph::Graph,
+        ops::get::Get,
+        transaction::Txn,
+    },
     err::Error,
-    ql::{condition::Condition, path::Path, record::Record},
+    ql::{
+        fields::{Field, Fields},
+        path::Path,
+        record::Record,
+        recurse::{Collect, Recurse},
+        value::Value,
+    },
     resp::Response,
 };
-use actix::{Addr, Handler, Message, ResponseFuture};
-use reblessive::{tree::Stk, Stack, TreeStack};
-use std::{ops::Deref, sync::Arc};
+use actix::{Addr, AsyncContext, Handler, Message, ResponseFuture};
+use reblessive::TreeStack;
+use std::{collections::HashSet, sync::Arc};
 
 #[derive(Message, PartialEq, Eq)]
 #[rtype(result = "Result<Response, Error>")]
 pub struct Walk {
     pub path: Arc<Vec<Path>>,
-    pub filter: Option<Arc<Condition>>,
     pub idx: usize,
     pub origin: Record,
-    // pub graph: Addr<Graph>
+    /// Record links in path filters are resolved through the graph
+    pub graph: Addr<Graph>,
+    /// Only entities the transaction sees are walked through
+    pub txn: Txn,
 }
 
 impl Walk {
-    pub fn new(path: Vec<Path>, origin: Record) -> Self {
-        let filter = path.first().unwrap().filter.clone();
-
+    pub fn new(path: Vec<Path>, origin: Record, graph: Addr<Graph>) -> Self {
         Walk {
             path: Arc::new(path),
-            origin,
-            filter,
             idx: 0,
+            origin,
+            graph,
+            txn: Txn::default(),
         }
     }
+
+    pub fn with_txn(mut self, txn: Txn) -> Self {
+        self.txn = txn;
+        self
+    }
 }
 
 impl Handler<Walk> for Entity {
@@ -39,85 +56,130 @@ impl Handler<Walk> for Entity {
         Walk {
             path,
             idx,
-            filter,
             origin,
+            graph,
+            txn,
         }: Walk,
-        _ctx: &mut Self::Context,
+        ctx: &mut Self::Context,
     ) -> Self::Result {
-        let entity = self.clone();
+        // Entities the transaction does not see are passed over, along with
+        // their edges
+        let Some(visible) = self.versions().visible(&txn) else {
+            return Box::pin(async { Ok(Response::Nodes(Vec::new())) });
+        };
+        // Every entity a step reaches is checked against its filter, only
+        // the version the transaction sees is read
+        let filter = idx.checked_sub(1).and_then(|i| path[i].filter.clone());
+        let fields = filter.as_ref().map(|_| visible.clone());
+        let edges: Vec<Addr<Entity>> = match path.get(idx) {
+            Some(step) => self
+                .edges()
+                .iter()
+                .filter(|(rec, _)| *rec.table == *step.to.0 && *rec != &origin)
+                .filter_map(|(_, edge)| edge.valid_path(&step.dir, self).cloned())
+                .collect(),
+            None => Vec::new(),
+        };
+        let id = self.id().clone();
+        let addr = ctx.address();
         Box::pin(async move {
-            let step = path
-                .get(idx)
-                .ok_or(Error::EdgeIndexExceeded(idx, path.deref().clone()))?;
-
-            if idx == path.len() - 1 {
-                let edges = entity
-                    .edges()
-                    .iter()
-                    .filter_map(|(record, edge)| {
-                        if !(*record.table == *step.to.0 && record != &origin) {
-                            return None;
-                        }
-                        let Some(edge) = edge.valid_path(&step.dir, &entity) else {
-                            return None;
-                        };
-                        Some(edge.clone())
-                    })
-                    .collect::<Vec<Addr<Entity>>>();
-
-                return Ok(Response::Nodes(edges));
-            }
-            if let Some(filter) = filter {
-                // TODO have to configure filtering
+            if let (Some(filter), Some(fields)) = (filter, fields) {
                 let mut stack = TreeStack::new();
-                // let x = stack
-                //     .enter(|stk| filter.evaluate(stk, &graph, &entity.fields(), None))
-                //     .finish()
-                //     .await?;
-                // let mut stk = TreeStack::new();
-                // let check = stk
-                //     .run(|stk| filter.evaluate(stk, graph, &entity.fields().clone().into()))
-                //     .await?;
-                // if !check.is_truthy() {
-                //     return Ok(Response::None);
-                // }
-            }
-
-            let mut edges = Vec::new();
-
-            for (rec, edge) in entity.edges() {
-                if !(*rec.table == *step.to.0 && rec != &origin) {
-                    continue;
+                let check = stack
+                    .enter(|stk| entity::check(stk, &graph, &txn, &fields, &filter))
+                    .finish()
+                    .await?;
+                if !check {
+                    return Ok(Response::Nodes(Vec::new()));
                 }
+            }
+            if idx >= path.len() {
+                return Ok(Response::Nodes(vec![addr]));
+            }
 
-                let Some(edge) = edge.valid_path(&step.dir, &entity) else {
-                    continue;
-                };
-
-                let filter = path.get(idx).unwrap().filter.clone();
+            let mut nodes = Vec::new();
+            for edge in edges {
                 let walk = Walk {
                     path: path.clone(),
                     idx: idx + 1,
-                    filter,
-                    origin: entity.id().clone(),
+                    origin: id.clone(),
+                    graph: graph.clone(),
+                    txn,
                 };
-                match edge.send(walk).await.unwrap()? {
-                    Response::Nodes(v) => edges.append(&mut v.clone()),
-                    Response::None => (),
-                    _ => unreachable!(),
-                }
+                let reached: Vec<Addr<Entity>> = edge.send(walk).await.unwrap()?.try_into()?;
+                nodes.extend(reached);
             }
 
-            Ok(Response::Nodes(edges))
+            Ok(Response::Nodes(nodes))
         })
     }
 }
 
+/// Repeats `hops` from `node` breadth first, returning every path whose
+/// depth lies within the bounds of `recurse`. Collecting nodes visits each
+/// node once, collecting paths only refuses to revisit a node on the same path.
+pub async fn recurse(
+    graph: &Addr<Graph>,
+    txn: &Txn,
+    node: Addr<Entity>,
+    id: Record,
+    hops: Vec<Path>,
+    recurse: &Recurse,
+) -> Result<Vec<Vec<Addr<Entity>>>, Error> {
+    let mut found = Vec::new();
+    if recurse.min == 0 {
+        found.push(vec![node.clone()]);
+    }
+    let mut seen = HashSet::from([id.clone()]);
+    let mut frontier = vec![(vec![node], id)];
+    let mut depth = 0;
+    while !frontier.is_empty() && depth < recurse.max() {
+        depth += 1;
+        let mut next = Vec::new();
+        for (path, id) in frontier {
+            let node = path.last().unwrap();
+            let walk = Walk::new(hops.clone(), id, graph.clone()).with_txn(*txn);
+            let response = node.send(walk).await.unwrap()?;
+            let nodes: Vec<Addr<Entity>> = response.try_into()?;
+            for node in nodes {
+                let id = self::id(graph, &node, txn).await?;
+                let visited = match recurse.collect {
+                    Collect::Nodes => !seen.insert(id.clone()),
+                    Collect::Paths => path.contains(&node),
+                };
+                if visited {
+                    continue;
+                }
+                let mut path = path.clone();
+                path.push(node);
+                if depth >= recurse.min {
+                    found.push(path[1..].to_vec());
+                }
+                next.push((path, id));
+            }
+        }
+        frontier = next;
+    }
+    Ok(found)
+}
+
+async fn id(graph: &Addr<Graph>, node: &Addr<Entity>, txn: &Txn) -> Result<Record, Error> {
+    let get = Get::new(Fields(vec![Field::WildCard]), None, graph.clone()).with_txn(*txn);
+    let value: Value = node.send(get).await.unwrap()?.try_into()?;
+    match value {
+        Value::Object(fields) => fields.get(&"id".into()).clone().try_into(),
+        _ => Err(Error::FieldNotFound(String::from("id"))),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{
-        dbs::ops::{get::Get, relate::Relate},
+        dbs::{
+            executor::Executor,
+            ops::{get::Get, relate::Connect},
+        },
         ql::{direction::Direction, fields::Field, fields::Fields},
     };
     use actix::Actor;
@@ -130,23 +192,33 @@ mod test {
         let a = Entity::new_node(a_id.clone(), Vec::new()).start();
         let b = Entity::new_node(b_id.clone(), Vec::new()).start();
         let c = Entity::new_node(c_id.clone(), Vec::new()).start();
-
-        let _ = b
-            .send(Relate {
-                edge: "e_1".to_string(),
-                fields: vec![],
+        let graph = Graph::new().start();
+        graph
+            .send(Connect {
+                id: Record::new("e_1", 1),
+                fields: Vec::new(),
                 org_id: a_id.clone(),
                 origin: a.clone(),
+                dest_id: b_id.clone(),
+                destination: b.clone(),
+                txn: Txn::default(),
             })
-            .await;
-        let _ = c
-            .send(Relate {
-                edge: "e_2".to_string(),
-                fields: vec![],
+            .await
+            .unwrap()
+            .unwrap();
+        graph
+            .send(Connect {
+                id: Record::new("e_2", 1),
+                fields: Vec::new(),
                 org_id: b_id.clone(),
                 origin: b.clone(),
+                dest_id: c_id.clone(),
+                destination: c.clone(),
+                txn: Txn::default(),
             })
-            .await;
+            .await
+            .unwrap()
+            .unwrap();
         let path = vec![
             Path::new(Direction::In, String::from("e_1").into(), None),
             Path::new(Direction::In, String::from("b").into(), None),
@@ -154,14 +226,75 @@ mod test {
             Path::new(Direction::In, String::from("c").into(), None),
         ];
 
-        let response = a.send(Walk::new(path, a_id)).await.unwrap().unwrap();
+        let walk = Walk::new(path, a_id, graph.clone());
+        let response = a.send(walk).await.unwrap().unwrap();
         let Response::Nodes(nodes) = response else {
             panic!()
         };
         let node = nodes.first().unwrap();
-        let get = Get::new(Fields(vec![Field::WildCard]), None);
+        let get = Get::new(Fields(vec![Field::WildCard]), None, graph);
         let response = node.send(get).await.unwrap().unwrap();
 
         println!("response: {0:#?}", response);
     }
+
+    #[actix_rt::test]
+    async fn recurse_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1; CREATE person:2; CREATE person:3; CREATE person:4;
+                RELATE person:1->knows->person:2; RELATE person:2->knows->person:3;
+                RELATE person:3->knows->person:1; RELATE person:3->knows->person:4",
+            )
+            .await;
+        let all = executor.run("SELECT person:1{..}->knows->person AS v FROM person:1");
+        let ids = serde_json::json!([{"id": "person:2"}, {"id": "person:3"}, {"id": "person:4"}]);
+        assert_eq!(all.await, Ok(serde_json::json!([{ "v": ids }])));
+        let some = executor.run("SELECT person:1{2..3}->knows->person AS v FROM person:1");
+        let ids = serde_json::json!([{"id": "person:3"}, {"id": "person:4"}]);
+        assert_eq!(some.await, Ok(serde_json::json!([{ "v": ids }])));
+        let near = executor.run("SELECT person:1{0..1}->knows->person AS v FROM person:1");
+        let ids = serde_json::json!([{"id": "person:1"}, {"id": "person:2"}]);
+        assert_eq!(near.await, Ok(serde_json::json!([{ "v": ids }])));
+        let paths = executor.run("SELECT person:1{2+path}->knows->person AS v FROM person:1");
+        let ids = serde_json::json!([[{"id": "person:2"}, {"id": "person:3"}]]);
+        assert_eq!(paths.await, Ok(serde_json::json!([{ "v": ids }])));
+        let none = executor.run("SELECT person:4{..}->knows->person AS v FROM person:4");
+        assert_eq!(none.await, Ok(serde_json::json!([{"v": []}])));
+    }
+
+    #[actix_rt::test]
+    async fn walk_filter_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET name = 'a'; CREATE person:2 SET name = 'b';
+                CREATE person:3 SET name = 'c'; CREATE person:4 SET name = 'd';
+                RELATE person:1->knows->person:2 SET since = 2019;
+                RELATE person:1->knows->person:3 SET since = 2021;
+                RELATE person:3->knows->person:4 SET since = 2022;
+                RELATE person:2->knows->person:4 SET since = 2023",
+            )
+            .await;
+        let v = |ids: serde_json::Value| Ok(serde_json::json!([{ "v": ids }]));
+        let edge =
+            executor.run("SELECT person:1->knows(WHERE since > 2020)->person AS v FROM person:1");
+        assert_eq!(edge.await, v(serde_json::json!([{"id": "person:3"}])));
+        let last = executor
+            .run("SELECT person:1->knows->person->(knows WHERE since >= 2023)->person AS v FROM person:1")
+            .await;
+        assert_eq!(last, v(serde_json::json!([{"id": "person:4"}])));
+        // Every step is checked, not only the last one
+        let both = executor
+            .run("SELECT person:1->(knows WHERE since > 2020)->person->(knows WHERE since > 2022)->person AS v FROM person:1")
+            .await;
+        assert_eq!(both, v(serde_json::json!([])));
+        let out = executor.run("SELECT name FROM person:1->(knows WHERE out = person:2)->person");
+        assert_eq!(out.await, Ok(serde_json::json!([{"name": "b"}])));
+        let node = executor.run("SELECT name FROM person:1->knows->(person WHERE name = 'c')");
+        assert_eq!(node.await, Ok(serde_json::json!([{"name": "c"}])));
+        let back = executor.run("SELECT name FROM person:4<-(knows WHERE in = person:3)<-person");
+        assert_eq!(back.await, Ok(serde_json::json!([{"name": "c"}])));
+    }
 }
@@ -0,0 +1,391 @@
+use crate::{
+    dbs::{graph::Graph, transaction::Txn},
+    doc::document::Cursor,
+    err::Error,
+    ql::{
+        definition::Definition, ident::Ident, idiom::Idiom, object::Object, part::Part,
+        record::Record, value::Value,
+    },
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+
+/// The fields defined on a table and whether it holds any others
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub full: bool,
+    /// Sorted by field so a definition comes before those nested in it
+    pub fields: Vec<Definition>,
+}
+
+impl Schema {
+    /// Adds a definition, replacing the one of the same field
+    pub fn define(&mut self, definition: Definition) {
+        match self
+            .fields
+            .binary_search_by(|d| d.field.cmp(&definition.field))
+        {
+            Ok(i) => self.fields[i] = definition,
+            Err(i) => self.fields.insert(i, definition),
+        }
+    }
+
+    /// The document a record is about to hold, with its defined fields
+    /// defaulted, computed, coerced and asserted, and the undefined fields of
+    /// a schemafull table removed. `before` is the document the record held,
+    /// which is `None` while it is being created
+    pub async fn process(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        id: &Record,
+        before: Option<&Value>,
+        mut doc: Value,
+    ) -> Result<Value, Error> {
+        for definition in self.fields.iter() {
+            let mut paths = Vec::new();
+            expand(&doc, &definition.field, Vec::new(), &mut paths);
+            for path in paths {
+                let written = pick(&doc, &path).cloned().unwrap_or_default();
+                let old = before.and_then(|before| pick(before, &path));
+                // A read-only field keeps the value it has, without computing
+                // it again, and can only be given a value it never had
+                if let Some(old) = old.filter(|old| definition.readonly && !old.is_none()) {
+                    if !written.is_none() && written != *old {
+                        return Err(Error::FieldReadonly {
+                            record: id.to_string(),
+                            field: Idiom(path).to_string(),
+                        });
+                    }
+                    doc.set(&path, old.clone())?;
+                    continue;
+                }
+                let cursor = |doc: &Value, val: &Value| {
+                    Cursor::from(doc.clone())
+                        .with_txn(*txn)
+                        .with_param("value", val.clone())
+                        .with_param("before", old.cloned().unwrap_or_default())
+                };
+                let mut val = written.clone();
+                if let (Value::None, Some(default)) = (&val, &definition.default) {
+                    let cur = cursor(&doc, &val);
+                    val = stk
+                        .run(|stk| default.evaluate(stk, graph, Some(&cur)))
+                        .await?;
+                }
+                if let Some(value) = &definition.value {
+                    let cur = cursor(&doc, &val);
+                    val = stk
+                        .run(|stk| value.evaluate(stk, graph, Some(&cur)))
+                        .await?;
+                }
+                if let Some(kind) = &definition.kind {
+                    val = kind.coerce(val).map_err(|value| Error::FieldCoerce {
+                        record: id.to_string(),
+                        field: Idiom(path.clone()).to_string(),
+                        kind: kind.to_string(),
+                        value,
+                    })?;
+                }
+                if let Some(check) = &definition.assert {
+                    let cur = cursor(&doc, &val);
+                    let ok = stk
+                        .run(|stk| check.evaluate(stk, graph, Some(&cur)))
+                        .await?;
+                    if !ok.is_truthy() {
+                        return Err(Error::FieldAssert {
+                            record: id.to_string(),
+                            field: Idiom(path).to_string(),
+                            value: val,
+                            check: check.to_string(),
+                        });
+                    }
+                }
+                if !(written.is_none() && val.is_none()) {
+                    doc.set(&path, val)?;
+                }
+            }
+        }
+        if self.full {
+            self.prune(&mut doc, &mut Vec::new());
+        }
+        Ok(doc)
+    }
+
+    /// Removes the fields no definition covers, a defined field with nothing
+    /// defined inside it is kept whole
+    fn prune(&self, doc: &mut Value, path: &mut Vec<Part>) {
+        let nested = |path: &[Part]| {
+            self.fields
+                .iter()
+                .filter(|d| covers(&d.field, path))
+                .any(|d| d.field.len() > path.len())
+        };
+        match doc {
+            Value::Object(Object(fields)) => {
+                fields.retain(|key, val| {
+                    path.push(Part::Field(Ident(key.clone())));
+                    let keep = (path.len() == 1 && &**key == "id")
+                        || self.fields.iter().any(|d| covers(&d.field, path));
+                    if keep && nested(path) {
+                        self.prune(val, path);
+                    }
+                    path.pop();
+                    keep
+                });
+            }
+            Value::Array(values) => {
+                for (i, val) in values.iter_mut().enumerate() {
+                    path.push(Part::Index((i as i64).into()));
+                    self.prune(val, path);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether a concrete path leads to or into a defined field
+fn covers(field: &Idiom, path: &[Part]) -> bool {
+    field.len() >= path.len()
+        && field
+            .iter()
+            .zip(path)
+            .all(|(f, p)| f == p || *f == Part::All)
+}
+
+/// The concrete paths in a document a defined field refers to, with every
+/// `*` replaced by the elements or keys present
+fn expand(doc: &Value, field: &[Part], path: Vec<Part>, paths: &mut Vec<Vec<Part>>) {
+    let Some((part, rest)) = field.split_first() else {
+        paths.push(path);
+        return;
+    };
+    let next = |part: Part| {
+        let mut path = path.clone();
+        path.push(part);
+        path
+    };
+    match (part, pick(doc, &path)) {
+        (Part::Field(_), _) => expand(doc, rest, next(part.clone()), paths),
+        (Part::All, Some(Value::Array(values))) => {
+            for i in 0..values.len() {
+                expand(doc, rest, next(Part::Index((i as i64).into())), paths);
+            }
+        }
+        (Part::All, Some(Value::Object(Object(fields)))) => {
+            for key in fields.keys() {
+                expand(doc, rest, next(Part::Field(Ident(key.clone()))), paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn pick<'a>(doc: &'a Value, path: &[Part]) -> Option<&'a Value> {
+    path.iter().try_fold(doc, |val, part| match (val, part) {
+        (Value::Object(Object(fields)), Part::Field(Ident(field))) => fields.get(field),
+        (Value::Array(values), Part::Index(i)) => values.get(i.to_usize()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dbs::{executor::Executor, graph::Graph},
+        err::Error,
+    };
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn schema_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "DEFINE TABLE person SCHEMAFULL;
+                DEFINE FIELD age ON person TYPE int DEFAULT 0 ASSERT $value >= 0;
+                DEFINE FIELD name ON person TYPE option<string>;
+                DEFINE FIELD address ON person TYPE object DEFAULT {};
+                DEFINE FIELD address.city ON person TYPE string DEFAULT 'london';
+                DEFINE FIELD tags ON person TYPE array DEFAULT [];
+                DEFINE FIELD tags.* ON person TYPE string",
+            )
+            .await;
+
+        // Values are coerced and undefined fields dropped
+        let person = serde_json::json!({
+            "id": "person:1", "age": 2, "address": {"city": "paris"}, "tags": []
+        });
+        let created = executor
+            .run("CREATE person:1 SET age = 2.0, extra = true, address = { city: 'paris', zip: 1 }")
+            .await;
+        assert_eq!(created, Ok(serde_json::json!([person])));
+        let defaulted = executor
+            .run("CREATE person:2 SET tags = ['a', 'b']")
+            .await
+            .unwrap();
+        assert_eq!(defaulted[0]["age"], 0);
+        assert_eq!(defaulted[0]["address"]["city"], "london");
+        assert_eq!(defaulted[0]["tags"], serde_json::json!(["a", "b"]));
+
+        let negative = executor.run("CREATE person:3 SET age = -1").await;
+        assert!(matches!(
+            negative,
+            Err(Error::FieldAssert { ref field, .. }) if field == "age"
+        ));
+        let text = executor.run("CREATE person:4 SET age = 'x'").await;
+        assert!(matches!(
+            text,
+            Err(Error::FieldCoerce { ref field, ref kind, .. }) if field == "age" && kind == "int"
+        ));
+        let tags = executor.run("CREATE person:5 SET tags = ['a', 1]").await;
+        assert!(matches!(
+            tags,
+            Err(Error::FieldCoerce { ref field, .. }) if field == "tags[1]"
+        ));
+        let fraction = executor.run("UPDATE person:1 SET age = 1.5").await;
+        assert!(matches!(fraction, Err(Error::FieldCoerce { .. })));
+        let updated = executor
+            .run("UPDATE person:2 SET name = 'b', other = 1")
+            .await
+            .unwrap();
+        assert_eq!(updated[0]["name"], "b");
+        assert!(updated[0].get("other").is_none());
+
+        // Schemaless tables keep fields that are not defined
+        executor.setup("DEFINE FIELD age ON post TYPE int").await;
+        let post = executor
+            .run("CREATE post:1 SET age = 1, title = 'kept'")
+            .await;
+        assert_eq!(post.unwrap()[0]["title"], "kept");
+        let people = executor
+            .run("SELECT * FROM person ORDER BY id")
+            .await
+            .unwrap();
+        assert_eq!(people.as_array().unwrap().len(), 2);
+        assert_eq!(people[0]["age"], 2);
+    }
+
+    #[actix_rt::test]
+    async fn computed_field_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "DEFINE FIELD full_name ON person VALUE string::concat(first, ' ', last);
+                DEFINE FIELD updated_at ON person TYPE datetime VALUE time::now();
+                DEFINE FIELD email ON person READONLY",
+            )
+            .await;
+        let created = executor
+            .run("CREATE person:1 SET first = 'a', last = 'b', email = 'a@b', full_name = 'x'")
+            .await
+            .unwrap();
+        assert_eq!(created[0]["full_name"], "a b");
+        let updated = executor
+            .run("UPDATE person:1 SET last = 'c'")
+            .await
+            .unwrap();
+        assert_eq!(updated[0]["full_name"], "a c");
+        assert!(created[0]["updated_at"].is_string());
+        assert_ne!(created[0]["updated_at"], updated[0]["updated_at"]);
+
+        let readonly = executor.run("UPDATE person:1 SET email = 'c@d'").await;
+        assert!(matches!(
+            readonly,
+            Err(Error::FieldReadonly { ref field, .. }) if field == "email"
+        ));
+        let other = executor.run("UPDATE person:1 SET nickname = 'n'").await;
+        assert_eq!(other.unwrap()[0]["nickname"], "n");
+        // A read-only field without a value yet can still be set
+        executor.setup("CREATE person:2 SET first = 'd'").await;
+        let set = executor.run("UPDATE person:2 SET email = 'd@e'").await;
+        assert_eq!(set.unwrap()[0]["email"], "d@e");
+        let kept = executor.run("SELECT email FROM person:1").await;
+        assert_eq!(kept, Ok(serde_json::json!([{"email": "a@b"}])));
+    }
+
+    #[actix_rt::test]
+    async fn computed_readonly_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup("DEFINE FIELD created ON person TYPE datetime VALUE time::now() READONLY")
+            .await;
+        let created = executor
+            .run("CREATE person:1 SET name = 'a'")
+            .await
+            .unwrap();
+        assert!(created[0]["created"].is_string());
+        // The value is not computed again, so the field does not change
+        let updated = executor.run("UPDATE person:1 SET name = 'b'").await;
+        let updated = updated.unwrap();
+        assert_eq!(updated[0]["name"], "b");
+        assert_eq!(updated[0]["created"], created[0]["created"]);
+        let readonly = executor
+            .run("UPDATE person:1 SET created = d'2020-01-01'")
+            .await;
+        assert!(matches!(
+            readonly,
+            Err(Error::FieldReadonly { ref field, .. }) if field == "created"
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn relate_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1; CREATE person:2; DEFINE TABLE knows SCHEMAFULL;
+                DEFINE FIELD since ON knows TYPE int DEFAULT 2000 ASSERT $value > 1900",
+            )
+            .await;
+
+        // Edges are coerced and pruned like records, keeping the records
+        // they link
+        let related = executor
+            .run("RELATE person:1->knows:1->person:2 SET since = 2020.0, extra = true")
+            .await;
+        let edge = serde_json::json!({
+            "id": "knows:1", "in": "person:1", "out": "person:2", "since": 2020
+        });
+        assert_eq!(related, Ok(serde_json::json!([edge])));
+        let defaulted = executor.run("RELATE person:2->knows:2->person:1").await;
+        assert_eq!(defaulted.unwrap()[0]["since"], 2000);
+        let asserted = executor.run("RELATE person:1->knows:3->person:2 SET since = 1800");
+        assert!(matches!(
+            asserted.await,
+            Err(Error::FieldAssert { ref record, .. }) if record == "knows:3"
+        ));
+        let coerced = executor.run("RELATE person:1->knows:4->person:2 SET since = 'x'");
+        assert!(matches!(coerced.await, Err(Error::FieldCoerce { .. })));
+        let edges = executor.run("SELECT id FROM knows ORDER BY id").await;
+        let ids = serde_json::json!([{"id": "knows:1"}, {"id": "knows:2"}]);
+        assert_eq!(edges, Ok(ids));
+    }
+
+    #[actix_rt::test]
+    async fn computed_edge_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1; CREATE person:2;
+                DEFINE FIELD label ON likes VALUE string::concat('weight ', weight);
+                DEFINE FIELD note ON likes READONLY",
+            )
+            .await;
+        let related = executor
+            .run("RELATE person:1->likes:1->person:2 SET weight = 1, note = 'a', label = 'x'")
+            .await
+            .unwrap();
+        assert_eq!(related[0]["label"], "weight 1");
+        let updated = executor.run("UPDATE likes:1 SET weight = 2").await.unwrap();
+        assert_eq!(updated[0]["label"], "weight 2");
+        let readonly = executor.run("UPDATE likes:1 SET note = 'b'").await;
+        assert!(matches!(
+            readonly,
+            Err(Error::FieldReadonly { ref record, ref field }) if record == "likes:1" && field == "note"
+        ));
+    }
+}
@@ -1,19 +1,33 @@
 use crate::{
-    dbs::entity::Entity,
-    ql::{record::Record, value::Value},
-    resp::Response,
+    dbs::{
+        entity::Entity,
+        index::Indexes,
+        schema::Schema,
+        transaction::Txn,
+        version::{self, Version},
+    },
+    ql::value::Value,
 };
-use actix::{Actor, Addr, Context, Handler, Message, ResponseFuture};
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use actix::{Actor, Addr, Context};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 #[derive(Debug)]
 pub struct Table {
     pub name: Arc<str>,
     pub nodes: Arc<RwLock<HashMap<Value, Addr<Entity>>>>,
+    pub indexes: Arc<RwLock<Indexes>>,
+    pub changed: Arc<RwLock<Changed>>,
+    /// The records that were deleted, they are dropped once no transaction
+    /// reads them
+    pub deleted: Arc<RwLock<HashSet<Value>>>,
+    /// How long past versions of the records are kept, when unset only the
+    /// versions open transactions read are kept
+    pub history: Option<Duration>,
+    pub schema: Arc<Schema>,
 }
 
 impl Actor for Table {
@@ -25,6 +39,11 @@ impl Table {
         Table {
             name: name.into(),
             nodes: Arc::new(RwLock::new(HashMap::new())),
+            indexes: Arc::new(RwLock::new(Indexes::default())),
+            changed: Arc::new(RwLock::new(Changed::default())),
+            deleted: Arc::new(RwLock::new(HashSet::new())),
+            history: None,
+            schema: Arc::new(Schema::default()),
         }
     }
 
@@ -33,11 +52,96 @@ impl Table {
         nodes.insert(value, node);
     }
 
+    pub fn remove(&mut self, value: &Value) -> Option<Addr<Entity>> {
+        self.indexes.write().unwrap().remove(value);
+        let mut nodes = self.nodes.write().unwrap();
+        nodes.remove(value)
+    }
+
+    pub fn reindex(&self, value: &Value, fields: &BTreeMap<Arc<str>, Value>, txn: &Txn) {
+        let mut indexes = self.indexes.write().unwrap();
+        indexes.insert(value, fields);
+        self.changed.write().unwrap().write(value, txn);
+    }
+
     pub fn contains(&self, value: &Value) -> bool {
         let nodes = self.nodes.read().unwrap();
         nodes.contains_key(value)
     }
 }
 
+/// The records whose latest version the indexes hold was written after the
+/// time kept for them, or is not committed yet. Readers that started before
+/// then do not see that version, so these records are checked by hand
+#[derive(Debug, Default)]
+pub struct Changed(HashMap<Value, u64>);
+
+impl Changed {
+    pub fn write(&mut self, value: &Value, txn: &Txn) {
+        let at = match txn.id {
+            Some(_) => u64::MAX,
+            None => version::now(),
+        };
+        self.0.insert(value.clone(), at);
+    }
+
+    /// Stamps a record with the time its version was committed and forgets
+    /// the records every open transaction sees the latest version of
+    pub fn settle(&mut self, value: &Value, at: u64, horizon: u64) {
+        self.0.insert(value.clone(), at);
+        self.0.retain(|_, at| *at > horizon);
+    }
+
+    /// Stamps a record with the version it is left with once a transaction
+    /// is reverted
+    pub fn revert(&mut self, value: &Value, latest: Option<&Version>) {
+        match latest {
+            Some(version) => {
+                let at = version.txn.map_or(version.at, |_| u64::MAX);
+                self.0.insert(value.clone(), at);
+            }
+            None => {
+                self.0.remove(value);
+            }
+        }
+    }
+
+    /// The records the transaction may see another version of than the
+    /// indexes hold
+    pub fn since(&self, txn: &Txn) -> Vec<Value> {
+        self.0
+            .iter()
+            .filter(|(_, &at)| at == u64::MAX || at > txn.at)
+            .map(|(value, _)| value.clone())
+            .collect()
+    }
+}
+
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    #[test]
+    fn changed_test() {
+        let mut changed = Changed::default();
+        let (a, b): (Value, Value) = (1.into(), 2.into());
+        let open = Txn {
+            id: Some(1),
+            at: 10,
+        };
+        changed.write(&a, &open);
+        changed.write(&b, &open);
+        // Uncommitted versions are checked by every reader
+        assert_eq!(changed.since(&Txn::default()).len(), 2);
+        changed.settle(&a, 20, 5);
+        let before = Txn { id: None, at: 15 };
+        let after = Txn { id: None, at: 25 };
+        assert_eq!(changed.since(&before).len(), 2);
+        assert_eq!(changed.since(&after), vec![b.clone()]);
+        changed.revert(&b, None);
+        assert!(changed.since(&after).is_empty());
+        // Once no open transaction started before the commit it is forgotten
+        changed.settle(&b, 30, 30);
+        assert!(changed.since(&before).is_empty());
+    }
+}
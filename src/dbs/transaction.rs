@@ -0,0 +1,792 @@
+use crate::{
+    dbs::{
+        entity::Entity,
+        graph::Graph,
+        ops::{delete::Delete, retrieve::Retrieve},
+        table::Table,
+        version::{self, Version},
+    },
+    err::Error,
+    kvs::{log::Log, storage},
+    ql::{record::Record, value::Value},
+    resp::Response,
+};
+use actix::{
+    fut::wrap_future, Actor, Addr, AtomicResponse, Context, Handler, Message, MessageResult,
+    ResponseFuture, Supervised, SystemService,
+};
+use std::collections::BTreeMap;
+
+/// The transaction a query runs in and the time its reads are taken at,
+/// without a transaction every write is committed as soon as it is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Txn {
+    pub id: Option<u64>,
+    pub at: u64,
+}
+
+impl Default for Txn {
+    fn default() -> Self {
+        Txn {
+            id: None,
+            at: u64::MAX,
+        }
+    }
+}
+
+impl Txn {
+    /// Reads what is committed at this moment
+    pub fn now() -> Self {
+        Txn {
+            id: None,
+            at: version::now(),
+        }
+    }
+}
+
+/// Collects the changes of every open transaction
+#[derive(Default)]
+pub struct Journal {
+    next: u64,
+    open: BTreeMap<u64, Changes>,
+}
+
+#[derive(Default)]
+struct Changes {
+    /// The time the transaction reads at
+    at: u64,
+    redo: Vec<Log>,
+    /// The records written to, in the order they were first written
+    writes: Vec<Record>,
+}
+
+impl Actor for Journal {
+    type Context = Context<Self>;
+}
+
+impl Supervised for Journal {}
+
+impl SystemService for Journal {}
+
+#[derive(Message)]
+#[rtype(result = "Txn")]
+struct Begin;
+
+impl Handler<Begin> for Journal {
+    type Result = MessageResult<Begin>;
+
+    fn handle(&mut self, _msg: Begin, _ctx: &mut Self::Context) -> Self::Result {
+        self.next += 1;
+        let at = version::now();
+        let changes = Changes {
+            at,
+            ..Default::default()
+        };
+        self.open.insert(self.next, changes);
+        MessageResult(Txn {
+            id: Some(self.next),
+            at,
+        })
+    }
+}
+
+/// The time the oldest open transaction reads at, every version committed
+/// since is seen by all of them
+#[derive(Message)]
+#[rtype(result = "u64")]
+struct Horizon;
+
+impl Handler<Horizon> for Journal {
+    type Result = u64;
+
+    fn handle(&mut self, _msg: Horizon, _ctx: &mut Self::Context) -> Self::Result {
+        let oldest = self.open.values().map(|changes| changes.at).min();
+        oldest.unwrap_or_else(version::now)
+    }
+}
+
+/// A change to a record, without a log the record is only checked for
+/// conflicting writes on commit
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Write {
+    txn: u64,
+    id: Record,
+    redo: Option<Log>,
+}
+
+impl Handler<Write> for Journal {
+    type Result = ();
+
+    fn handle(&mut self, msg: Write, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(changes) = self.open.get_mut(&msg.txn) {
+            changes.redo.extend(msg.redo);
+            if !changes.writes.contains(&msg.id) {
+                changes.writes.push(msg.id);
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<Changes>")]
+struct Finish(u64);
+
+impl Handler<Finish> for Journal {
+    type Result = MessageResult<Finish>;
+
+    fn handle(&mut self, Finish(txn): Finish, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.open.remove(&txn))
+    }
+}
+
+/// Whether another writer committed to the entity since the transaction
+/// started
+#[derive(Message)]
+#[rtype(result = "bool")]
+struct Validate(Txn);
+
+impl Handler<Validate> for Entity {
+    type Result = bool;
+
+    fn handle(&mut self, Validate(txn): Validate, _ctx: &mut Self::Context) -> Self::Result {
+        self.versions().conflicts(&txn)
+    }
+}
+
+/// Commits the versions a transaction wrote to a record, then drops the
+/// versions its table no longer keeps and the deleted records no
+/// transaction reads
+#[derive(Message, Clone)]
+#[rtype(result = "()")]
+struct Settle {
+    record: Record,
+    txn: u64,
+    at: u64,
+    /// The versions older than the one current at this time are dropped,
+    /// it starts out as the time the oldest open transaction reads at
+    cutoff: u64,
+}
+
+impl Handler<Settle> for Graph {
+    type Result = ResponseFuture<()>;
+
+    fn handle(&mut self, msg: Settle, _ctx: &mut Self::Context) -> Self::Result {
+        let table = self.tables.get(msg.record.table.as_ref()).cloned();
+        Box::pin(async move {
+            if let Some(table) = table {
+                table.send(msg).await.unwrap();
+            }
+        })
+    }
+}
+
+impl Handler<Settle> for Table {
+    // Deleted records are dropped in turn with other changes, so none is
+    // created again while it is dropped
+    type Result = AtomicResponse<Self, ()>;
+
+    fn handle(&mut self, mut msg: Settle, _ctx: &mut Self::Context) -> Self::Result {
+        let key: Value = msg.record.id.clone().into();
+        let node = self.nodes.read().unwrap().get(&key).cloned();
+        self.changed
+            .write()
+            .unwrap()
+            .settle(&key, msg.at, msg.cutoff);
+        if let Some(history) = self.history {
+            let retained = msg.at.saturating_sub(history.as_nanos() as u64);
+            msg.cutoff = msg.cutoff.min(retained);
+        }
+        let cutoff = msg.cutoff;
+        let deleted: Vec<_> = {
+            let nodes = self.nodes.read().unwrap();
+            let deleted = self.deleted.read().unwrap();
+            let node = |key: &Value| nodes.get(key).cloned();
+            deleted.iter().map(|key| (key.clone(), node(key))).collect()
+        };
+        let nodes = self.nodes.clone();
+        let indexes = self.indexes.clone();
+        let swept = self.deleted.clone();
+        AtomicResponse::new(Box::pin(wrap_future(async move {
+            if let Some(node) = node {
+                node.send(msg).await.unwrap();
+            }
+            for (key, node) in deleted {
+                let state = match &node {
+                    Some(node) => node.send(Sweep(cutoff)).await.unwrap(),
+                    None => Swept::Gone,
+                };
+                match state {
+                    Swept::Kept => continue,
+                    Swept::Restored => (),
+                    Swept::Gone => {
+                        nodes.write().unwrap().remove(&key);
+                        indexes.write().unwrap().remove(&key);
+                        if let Some(node) = node {
+                            node.do_send(Delete);
+                        }
+                    }
+                }
+                swept.write().unwrap().remove(&key);
+            }
+        })))
+    }
+}
+
+impl Handler<Settle> for Entity {
+    type Result = ();
+
+    fn handle(&mut self, msg: Settle, _ctx: &mut Self::Context) -> Self::Result {
+        self.versions_mut().settle(msg.txn, msg.at);
+        self.versions_mut().prune(msg.cutoff);
+    }
+}
+
+/// What is left of a deleted record once the versions older than the
+/// cutoff are dropped
+#[derive(Debug, PartialEq, Eq)]
+enum Swept {
+    /// The record was created again
+    Restored,
+    /// Its deletion or earlier versions are still read, or edges still link
+    /// to it
+    Kept,
+    /// Nothing is left to read
+    Gone,
+}
+
+#[derive(Message)]
+#[rtype(result = "Swept")]
+struct Sweep(u64);
+
+impl Handler<Sweep> for Entity {
+    type Result = MessageResult<Sweep>;
+
+    fn handle(&mut self, Sweep(cutoff): Sweep, _ctx: &mut Self::Context) -> Self::Result {
+        // A deletion older than the cutoff is all that is left once pruned
+        self.versions_mut().prune(cutoff);
+        let linked = self.is_node() && !self.edges().is_empty();
+        MessageResult(match self.versions().latest() {
+            Some(v) if v.txn.is_none() && v.fields.is_some() => Swept::Restored,
+            Some(v) if v.txn.is_none() && v.at <= cutoff && !linked => Swept::Gone,
+            Some(_) => Swept::Kept,
+            None => Swept::Gone,
+        })
+    }
+}
+
+/// Drops the versions a transaction wrote to a record, the table indexes
+/// follow the state the record is left in and a record left without any
+/// version is removed
+#[derive(Message, Clone)]
+#[rtype(result = "Option<Version>")]
+struct Revert(Record, u64);
+
+impl Handler<Revert> for Graph {
+    type Result = ResponseFuture<Option<Version>>;
+
+    fn handle(&mut self, msg: Revert, _ctx: &mut Self::Context) -> Self::Result {
+        let table = self.tables.get(msg.0.table.as_ref()).cloned();
+        Box::pin(async move { table?.send(msg).await.unwrap() })
+    }
+}
+
+impl Handler<Revert> for Table {
+    type Result = AtomicResponse<Self, Option<Version>>;
+
+    fn handle(&mut self, msg: Revert, _ctx: &mut Self::Context) -> Self::Result {
+        let key: Value = msg.0.id.clone().into();
+        let nodes = self.nodes.clone();
+        let indexes = self.indexes.clone();
+        let changed = self.changed.clone();
+        AtomicResponse::new(Box::pin(wrap_future(async move {
+            let node = nodes.read().unwrap().get(&key).cloned()?;
+            let latest = node.send(msg).await.unwrap();
+            let mut indexes = indexes.write().unwrap();
+            match latest.as_ref().and_then(|v| v.fields.as_ref()) {
+                Some(fields) => indexes.insert(&key, fields),
+                None => indexes.remove(&key),
+            }
+            changed.write().unwrap().revert(&key, latest.as_ref());
+            if latest.is_none() {
+                nodes.write().unwrap().remove(&key);
+                node.do_send(Delete);
+            }
+            latest
+        })))
+    }
+}
+
+impl Handler<Revert> for Entity {
+    type Result = Option<Version>;
+
+    fn handle(&mut self, Revert(_, txn): Revert, _ctx: &mut Self::Context) -> Self::Result {
+        self.versions_mut().revert(txn);
+        let latest = self.versions().latest()?.clone();
+        if let Some(fields) = &latest.fields {
+            self.replace(fields.clone());
+        }
+        Some(latest)
+    }
+}
+
+/// Fails when the record was written by another writer since the
+/// transaction started, otherwise it is checked again on commit. Records
+/// that are read to be linked to are guarded so they can not be deleted
+/// under the link
+#[derive(Message)]
+#[rtype(result = "Result<(), Error>")]
+pub struct Guard(pub Txn);
+
+impl Handler<Guard> for Entity {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, Guard(txn): Guard, _ctx: &mut Self::Context) -> Self::Result {
+        self.guard(&txn)?;
+        if self.versions().visible(&txn).is_none() {
+            return Err(Error::RecordNotFound(self.id().to_string()));
+        }
+        if let Some(id) = txn.id {
+            Journal::from_registry().do_send(Write {
+                txn: id,
+                id: self.id().clone(),
+                redo: None,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Records a change, inside a transaction it is only stored once the
+/// transaction commits
+pub fn write(txn: &Txn, id: &Record, redo: Log) {
+    match txn.id {
+        Some(txn) => Journal::from_registry().do_send(Write {
+            txn,
+            id: id.clone(),
+            redo: Some(redo),
+        }),
+        None => storage::persist(redo),
+    }
+}
+
+/// Opens a transaction, its reads see what was committed before it started
+pub async fn begin(txn: &mut Txn) -> Result<(), Error> {
+    if txn.id.is_some() {
+        return Err(Error::TransactionOpen);
+    }
+    *txn = Journal::from_registry().send(Begin).await.unwrap();
+    Ok(())
+}
+
+/// Stores the changes of the open transaction and waits until they are
+/// durable before others see them, unless another writer committed to the
+/// same records first. Changes that could not be stored stay pending
+pub async fn commit(graph: &Addr<Graph>, txn: &mut Txn) -> Result<(), Error> {
+    let Some(id) = txn.id else {
+        return Err(Error::NoTransaction(String::from("COMMIT")));
+    };
+    let changes = finish(id).await;
+    for record in &changes.writes {
+        // A record that has gone was removed by another writer
+        let conflicted = match graph.send(Retrieve::Record(record.clone())).await.unwrap() {
+            Response::Node(node) => node.send(Validate(*txn)).await.unwrap(),
+            _ => true,
+        };
+        if conflicted {
+            let conflict = Error::TransactionConflict(record.to_string());
+            txn.id = None;
+            rollback(graph, id, changes).await?;
+            return Err(conflict);
+        }
+    }
+    txn.id = None;
+    let at = version::now();
+    if !changes.redo.is_empty() {
+        let mut redo = Log::Commit(changes.redo);
+        redo.stamp(at);
+        storage::persist(redo);
+        storage::flush().await?;
+    }
+    let horizon = Journal::from_registry().send(Horizon).await.unwrap();
+    for record in changes.writes {
+        let settle = Settle {
+            record,
+            txn: id,
+            at,
+            cutoff: horizon,
+        };
+        graph.send(settle).await.unwrap();
+    }
+    Ok(())
+}
+
+/// Discards the changes of the open transaction
+pub async fn cancel(graph: &Addr<Graph>, txn: &mut Txn) -> Result<(), Error> {
+    let Some(id) = txn.id.take() else {
+        return Err(Error::NoTransaction(String::from("CANCEL")));
+    };
+    rollback(graph, id, finish(id).await).await
+}
+
+async fn finish(txn: u64) -> Changes {
+    let changes = Journal::from_registry().send(Finish(txn)).await.unwrap();
+    changes.unwrap_or_default()
+}
+
+/// Drops what the transaction wrote to the records it changed, newest first
+/// so edges are dropped before the records they link
+async fn rollback(graph: &Addr<Graph>, txn: u64, changes: Changes) -> Result<(), Error> {
+    for record in changes.writes.into_iter().rev() {
+        graph.send(Revert(record, txn)).await.unwrap();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        par,
+        ql::query::{Queries, Query},
+    };
+    use reblessive::TreeStack;
+
+    async fn run(graph: &Addr<Graph>, txn: &mut Txn, queries: &str) -> Vec<Result<Value, Error>> {
+        let Queries(queries) = par::parse(queries).unwrap();
+        let mut stack = TreeStack::new();
+        let mut results = Vec::new();
+        for query in queries {
+            if txn.id.is_none() {
+                txn.at = version::now();
+            }
+            let result = stack
+                .enter(|stk| query.compute(stk, graph, None, txn))
+                .finish()
+                .await;
+            if !matches!(query, Query::Begin | Query::Commit | Query::Cancel) {
+                results.push(result);
+            }
+        }
+        results
+    }
+
+    fn json(result: &Result<Value, Error>) -> serde_json::Value {
+        serde_json::to_value(result.as_ref().unwrap()).unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn snapshot_test() {
+        let graph = Graph::new().start();
+        let (mut a, mut b) = (Txn::default(), Txn::default());
+        run(
+            &graph,
+            &mut a,
+            "CREATE person:1 SET x = 1; CREATE person:2 SET x = 1",
+        )
+        .await;
+        run(
+            &graph,
+            &mut a,
+            "BEGIN; UPDATE person:1 SET x = 2; CREATE person:3",
+        )
+        .await;
+        run(&graph, &mut b, "UPDATE person:2 SET x = 2").await;
+
+        // Neither sees what the other has not committed
+        let select = "SELECT id, x FROM person ORDER BY id";
+        let inside = serde_json::json!([
+            {"id": "person:1", "x": 2},
+            {"id": "person:2", "x": 1},
+            {"id": "person:3", "x": null},
+        ]);
+        assert_eq!(json(&run(&graph, &mut a, select).await[0]), inside);
+        let outside = serde_json::json!([
+            {"id": "person:1", "x": 1},
+            {"id": "person:2", "x": 2},
+        ]);
+        assert_eq!(json(&run(&graph, &mut b, select).await[0]), outside);
+
+        run(&graph, &mut a, "CANCEL").await;
+        run(&graph, &mut a, "BEGIN; UPDATE person:1 SET x = 3; COMMIT").await;
+        let committed = serde_json::json!([
+            {"id": "person:1", "x": 3},
+            {"id": "person:2", "x": 2},
+        ]);
+        assert_eq!(json(&run(&graph, &mut b, select).await[0]), committed);
+    }
+
+    #[actix_rt::test]
+    async fn conflict_test() {
+        let graph = Graph::new().start();
+        let (mut a, mut b) = (Txn::default(), Txn::default());
+        run(
+            &graph,
+            &mut a,
+            "CREATE person:1 SET x = 1; CREATE person:2 SET x = 1",
+        )
+        .await;
+
+        // A record written by an open transaction can not be written by another
+        run(&graph, &mut a, "BEGIN; UPDATE person:1 SET x = 2").await;
+        let results = run(&graph, &mut b, "UPDATE person:1 SET x = 3").await;
+        let conflict = Error::TransactionConflict(String::from("person:1"));
+        assert!(results[0].as_ref().unwrap_err().is_retryable());
+        assert_eq!(results[0], Err(conflict));
+        run(&graph, &mut a, "COMMIT").await;
+
+        // The first writer to commit wins, a record committed to since the
+        // transaction started can not be written by it
+        run(&graph, &mut a, "BEGIN").await;
+        run(&graph, &mut b, "UPDATE person:2 SET x = 3").await;
+        let results = run(
+            &graph,
+            &mut a,
+            "UPDATE person:1 SET x = 4; UPDATE person:2 SET x = 4",
+        )
+        .await;
+        let conflict = Error::TransactionConflict(String::from("person:2"));
+        assert_eq!(results[1], Err(conflict));
+        cancel(&graph, &mut a).await.unwrap();
+        let results = run(&graph, &mut b, "SELECT id, x FROM person ORDER BY id").await;
+        let rolled = serde_json::json!([
+            {"id": "person:1", "x": 2},
+            {"id": "person:2", "x": 3},
+        ]);
+        assert_eq!(json(&results[0]), rolled);
+    }
+
+    #[actix_rt::test]
+    async fn read_test() {
+        let graph = Graph::new().start();
+        let mut txn = Txn::default();
+        run(&graph, &mut txn, "CREATE person:1 SET name = 'a'").await;
+        run(
+            &graph,
+            &mut txn,
+            "BEGIN; CREATE person:2 SET name = 'b'; CREATE post:1 SET author = person:2;
+            RELATE person:1->knows->person:2",
+        )
+        .await;
+        let v = |v: serde_json::Value| serde_json::json!([{ "v": v }]);
+
+        // Reads inside a transaction see the records it wrote
+        let walked = run(
+            &graph,
+            &mut txn,
+            "SELECT ->knows->person.name AS v FROM person:1",
+        );
+        assert_eq!(json(&walked.await[0]), v(serde_json::json!(["b"])));
+        let selected = run(&graph, &mut txn, "SELECT name FROM person:1->knows->person");
+        assert_eq!(json(&selected.await[0]), serde_json::json!([{"name": "b"}]));
+        let linked = run(&graph, &mut txn, "SELECT author.name AS v FROM post:1");
+        assert_eq!(json(&linked.await[0]), v("b".into()));
+        let degree = "SELECT graph::degree('person', 'knows') AS v FROM person:1";
+        let degree = json(&run(&graph, &mut txn, degree).await[0]);
+        assert_eq!(degree[0]["v"][1]["degree"], 1);
+        let path =
+            "SELECT graph::shortest_path(person:1, person:2, 'knows', '->') AS v FROM person:1";
+        let path = json(&run(&graph, &mut txn, path).await[0]);
+        assert_eq!(path[0]["v"].as_array().unwrap().len(), 3);
+
+        // And no longer see the records it deleted
+        run(&graph, &mut txn, "DELETE person:2").await;
+        let walked = run(
+            &graph,
+            &mut txn,
+            "SELECT ->knows->person.name AS v FROM person:1",
+        );
+        assert_eq!(json(&walked.await[0]), v(serde_json::json!([])));
+        let linked = run(&graph, &mut txn, "SELECT author.name AS v FROM post:1");
+        assert_eq!(json(&linked.await[0]), v(serde_json::Value::Null));
+        run(&graph, &mut txn, "COMMIT").await;
+    }
+
+    #[actix_rt::test]
+    async fn missing_record_test() {
+        let graph = Graph::new().start();
+        let mut txn = Txn::default();
+        begin(&mut txn).await.unwrap();
+        let id = Record::new("person", 1);
+        write(
+            &txn,
+            &id,
+            Log::Purge {
+                id: id.clone(),
+                at: 0,
+            },
+        );
+        let err = commit(&graph, &mut txn).await.unwrap_err();
+        assert_eq!(err, Error::TransactionConflict(String::from("person:1")));
+    }
+
+    #[actix_rt::test]
+    async fn delete_test() {
+        let graph = Graph::new().start();
+        let (mut a, mut b) = (Txn::default(), Txn::default());
+        run(
+            &graph,
+            &mut a,
+            "CREATE person:1 SET x = 1; CREATE person:2 SET x = 1; CREATE person:3",
+        )
+        .await;
+        let select = "SELECT id, x FROM person:1, person:2";
+
+        // A record another transaction wrote to can not be deleted
+        run(&graph, &mut a, "BEGIN; UPDATE person:1 SET x = 2").await;
+        let results = run(&graph, &mut b, "DELETE person:1").await;
+        let conflict = Error::TransactionConflict(String::from("person:1"));
+        assert_eq!(results[0], Err(conflict));
+        run(&graph, &mut a, "COMMIT").await;
+
+        // An open delete hides the record from its own transaction only, and
+        // keeps other writers off it
+        run(&graph, &mut a, "BEGIN; DELETE person:2").await;
+        let inside = serde_json::json!([{"id": "person:1", "x": 2}]);
+        assert_eq!(json(&run(&graph, &mut a, select).await[0]), inside);
+        let outside = serde_json::json!([
+            {"id": "person:1", "x": 2},
+            {"id": "person:2", "x": 1},
+        ]);
+        assert_eq!(json(&run(&graph, &mut b, select).await[0]), outside);
+        let results = run(&graph, &mut b, "UPDATE person:2 SET x = 3").await;
+        let conflict = Error::TransactionConflict(String::from("person:2"));
+        assert_eq!(results[0], Err(conflict));
+        let results = run(&graph, &mut b, "RELATE person:3->knows->person:2").await;
+        let conflict = Error::TransactionConflict(String::from("person:2"));
+        assert_eq!(results[0], Err(conflict));
+
+        // A cancelled delete brings the record back, a committed one removes
+        // it for everyone
+        run(&graph, &mut a, "CANCEL").await;
+        assert_eq!(json(&run(&graph, &mut b, select).await[0]), outside);
+        run(&graph, &mut a, "BEGIN; DELETE person:2; COMMIT").await;
+        assert_eq!(json(&run(&graph, &mut b, select).await[0]), inside);
+        let results = run(
+            &graph,
+            &mut b,
+            "CREATE person:2 SET x = 4; SELECT x FROM person:2",
+        )
+        .await;
+        assert_eq!(json(&results[1]), serde_json::json!([{"x": 4}]));
+    }
+
+    #[actix_rt::test]
+    async fn index_test() {
+        let graph = Graph::new().start();
+        let (mut a, mut b) = (Txn::default(), Txn::default());
+        run(
+            &graph,
+            &mut a,
+            "DEFINE INDEX email ON person FIELDS email;
+            CREATE person:1 SET email = 'x'; CREATE person:2 SET email = 'z'",
+        )
+        .await;
+        let select = |email: &str| format!("SELECT id FROM person WHERE email = '{email}'");
+        let one = serde_json::json!([{"id": "person:1"}]);
+        let none = serde_json::json!([]);
+
+        // The index holds the uncommitted value, other readers still find
+        // the record by the committed one
+        run(&graph, &mut a, "BEGIN; UPDATE person:1 SET email = 'y'").await;
+        assert_eq!(json(&run(&graph, &mut b, &select("x")).await[0]), one);
+        assert_eq!(json(&run(&graph, &mut b, &select("y")).await[0]), none);
+        assert_eq!(json(&run(&graph, &mut a, &select("y")).await[0]), one);
+        assert_eq!(json(&run(&graph, &mut a, &select("x")).await[0]), none);
+
+        // A transaction that started before the commit keeps finding it by
+        // the value it had then
+        run(&graph, &mut b, "BEGIN").await;
+        run(&graph, &mut a, "COMMIT").await;
+        assert_eq!(json(&run(&graph, &mut b, &select("x")).await[0]), one);
+        run(&graph, &mut b, "CANCEL").await;
+        assert_eq!(json(&run(&graph, &mut b, &select("y")).await[0]), one);
+
+        run(&graph, &mut a, "BEGIN; DELETE person:2").await;
+        let two = serde_json::json!([{"id": "person:2"}]);
+        assert_eq!(json(&run(&graph, &mut b, &select("z")).await[0]), two);
+        run(&graph, &mut a, "CANCEL").await;
+        assert_eq!(json(&run(&graph, &mut b, &select("z")).await[0]), two);
+    }
+
+    #[actix_rt::test]
+    async fn prune_test() {
+        let graph = Graph::new().start();
+        let (mut a, mut b) = (Txn::default(), Txn::default());
+        let history = "SELECT record::history(person:1) AS v FROM person:1";
+        let versions =
+            |result: &Result<Value, Error>| json(result)[0]["v"].as_array().unwrap().len();
+        run(&graph, &mut a, "CREATE person:1 SET x = 1").await;
+
+        // Versions an open transaction reads are kept until it finishes
+        run(&graph, &mut b, "BEGIN").await;
+        run(
+            &graph,
+            &mut a,
+            "BEGIN; UPDATE person:1 SET x = 2; UPDATE person:1 SET x = 3; COMMIT",
+        )
+        .await;
+        assert_eq!(versions(&run(&graph, &mut a, history).await[0]), 3);
+        let select = "SELECT x FROM person:1";
+        assert_eq!(
+            json(&run(&graph, &mut b, select).await[0]),
+            serde_json::json!([{"x": 1}])
+        );
+        run(&graph, &mut b, "CANCEL").await;
+        run(&graph, &mut a, "BEGIN; UPDATE person:1 SET x = 4; COMMIT").await;
+        assert_eq!(versions(&run(&graph, &mut a, history).await[0]), 1);
+    }
+
+    #[actix_rt::test]
+    async fn sweep_test() {
+        let graph = Graph::new().start();
+        let (mut a, mut b) = (Txn::default(), Txn::default());
+        run(
+            &graph,
+            &mut a,
+            "CREATE person:1; CREATE person:2; RELATE person:1->knows:1->person:2",
+        )
+        .await;
+        let exists = |record: Record| {
+            let graph = graph.clone();
+            async move {
+                let response = graph.send(Retrieve::Record(record)).await.unwrap();
+                matches!(response, Response::Node(_))
+            }
+        };
+
+        // A deleted record is kept while an open transaction reads it
+        run(&graph, &mut b, "BEGIN").await;
+        run(&graph, &mut a, "BEGIN; DELETE person:1; COMMIT").await;
+        let select = "SELECT id FROM person:1";
+        let one = serde_json::json!([{"id": "person:1"}]);
+        assert_eq!(json(&run(&graph, &mut b, select).await[0]), one);
+        assert!(exists(Record::new("person", 1)).await);
+        assert!(exists(Record::new("knows", 1)).await);
+
+        // And dropped along with its edges with the next change to its table
+        // once it is not
+        run(&graph, &mut b, "CANCEL").await;
+        run(
+            &graph,
+            &mut a,
+            "BEGIN; CREATE knows:2; CREATE person:3; COMMIT",
+        )
+        .await;
+        assert!(!exists(Record::new("knows", 1)).await);
+        assert!(!exists(Record::new("person", 1)).await);
+        let select = "SELECT ->knows->person AS v FROM person:2";
+        let none = serde_json::json!([{"v": []}]);
+        assert_eq!(json(&run(&graph, &mut a, select).await[0]), none);
+
+        // Within the retention of its table the deletion is kept as a version
+        run(&graph, &mut a, "DEFINE TABLE post HISTORY 1h").await;
+        run(&graph, &mut a, "BEGIN; CREATE post:1 SET n = 1; COMMIT").await;
+        run(&graph, &mut a, "BEGIN; DELETE post:1; COMMIT").await;
+        run(&graph, &mut a, "BEGIN; CREATE post:2; COMMIT").await;
+        assert!(exists(Record::new("post", 1)).await);
+        let select = "SELECT record::history(post:1).value AS v FROM post:2";
+        let history = serde_json::json!([{"v": [{"id": "post:1", "n": 1}, null]}]);
+        assert_eq!(json(&run(&graph, &mut a, select).await[0]), history);
+    }
+}
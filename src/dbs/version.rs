@@ -0,0 +1,225 @@
+use crate::{dbs::transaction::Txn, ql::value::Value};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+static CLOCK: AtomicU64 = AtomicU64::new(0);
+
+/// Nanoseconds since the epoch, every call returns a later time than the last
+pub fn now() -> u64 {
+    let wall = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    let next = |last: u64| wall.max(last + 1);
+    let last = CLOCK
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| Some(next(last)))
+        .unwrap();
+    next(last)
+}
+
+/// A state of an entity, the transaction is set while it is still open and
+/// the fields are unset once the entity is deleted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Version {
+    pub at: u64,
+    pub txn: Option<u64>,
+    pub fields: Option<BTreeMap<Arc<str>, Value>>,
+}
+
+/// The states of an entity that are still read or within the retention of
+/// its table, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Versions(Vec<Version>);
+
+impl Versions {
+    pub fn new(fields: BTreeMap<Arc<str>, Value>, txn: &Txn) -> Self {
+        let mut versions = Versions::default();
+        versions.push(Some(fields), txn);
+        versions
+    }
+
+    pub fn push(&mut self, fields: Option<BTreeMap<Arc<str>, Value>>, txn: &Txn) {
+        self.0.push(Version {
+            at: now(),
+            txn: txn.id,
+            fields,
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Version> {
+        self.0.iter()
+    }
+
+    pub fn latest(&self) -> Option<&Version> {
+        self.0.last()
+    }
+
+    /// The time the latest state was written at
+    pub fn written(&self) -> u64 {
+        self.0.last().map_or(0, |v| v.at)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The open transaction that wrote the latest state
+    pub fn pending(&self) -> Option<u64> {
+        self.0.last().and_then(|v| v.txn)
+    }
+
+    /// The latest state the transaction sees, its own writes along with
+    /// what was committed before it started
+    pub fn visible(&self, txn: &Txn) -> Option<&BTreeMap<Arc<str>, Value>> {
+        self.0
+            .iter()
+            .rev()
+            .find(|v| match v.txn {
+                Some(id) => txn.id == Some(id),
+                None => v.at <= txn.at,
+            })
+            .and_then(|v| v.fields.as_ref())
+    }
+
+    /// The latest committed state
+    pub fn committed(&self) -> Option<&BTreeMap<Arc<str>, Value>> {
+        self.0
+            .iter()
+            .rev()
+            .find(|v| v.txn.is_none())
+            .and_then(|v| v.fields.as_ref())
+    }
+
+    /// Whether another writer committed a state after the transaction started
+    pub fn conflicts(&self, txn: &Txn) -> bool {
+        self.0
+            .iter()
+            .any(|v| v.at > txn.at && v.txn.is_none_or(|id| txn.id != Some(id)))
+    }
+
+    /// Commits the states written by a transaction at the time it commits
+    pub fn settle(&mut self, id: u64, at: u64) {
+        for version in self.0.iter_mut().filter(|v| v.txn == Some(id)) {
+            version.at = at;
+            version.txn = None;
+        }
+    }
+
+    /// Drops the committed states older than the cutoff, keeping the one
+    /// that was current at the cutoff so reads from then on still see it
+    pub fn prune(&mut self, cutoff: u64) {
+        let current = self
+            .0
+            .iter()
+            .rposition(|v| v.txn.is_none() && v.at <= cutoff);
+        if let Some(current) = current {
+            let mut index = 0;
+            self.0.retain(|v| {
+                let keep = index >= current || v.txn.is_some();
+                index += 1;
+                keep
+            });
+        }
+    }
+
+    /// Puts back a committed state at the time it was written, in place of
+    /// the states written after it
+    pub fn restore(&mut self, fields: Option<BTreeMap<Arc<str>, Value>>, at: u64) {
+        self.0.retain(|v| v.at < at);
+        self.0.push(Version {
+            at,
+            txn: None,
+            fields,
+        });
+    }
+
+    /// Drops the states written by a transaction
+    pub fn revert(&mut self, id: u64) {
+        self.0.retain(|v| v.txn != Some(id));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dbs::{executor::Executor, graph::Graph},
+        ql::datetime::Datetime,
+    };
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn version_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "DEFINE TABLE person HISTORY 1h; DEFINE INDEX age ON person FIELDS age;
+                CREATE person:1 SET age = 1; CREATE person:2 SET age = 1",
+            )
+            .await;
+        let at = Datetime::now();
+        executor
+            .setup(
+                "UPDATE person:1 SET age = 2; UPDATE person:1 SET age = 3;
+                CREATE person:3 SET age = 1",
+            )
+            .await;
+
+        let query = format!("SELECT id, age FROM person ORDER BY id VERSION {at}");
+        let then = serde_json::json!([{"id": "person:1", "age": 1}, {"id": "person:2", "age": 1}]);
+        assert_eq!(executor.run(&query).await, Ok(then));
+        let query = format!("SELECT id FROM person WHERE age = 1 ORDER BY id VERSION {at}");
+        let ids = serde_json::json!([{"id": "person:1"}, {"id": "person:2"}]);
+        assert_eq!(executor.run(&query).await, Ok(ids));
+        let now = executor.run("SELECT age FROM person:1").await;
+        assert_eq!(now, Ok(serde_json::json!([{"age": 3}])));
+        let before = executor.run("SELECT * FROM person:1 VERSION d'2000-01-01T00:00:00Z'");
+        assert_eq!(before.await, Ok(serde_json::json!([])));
+        let invalid = executor.run("SELECT * FROM person VERSION 1").await;
+        assert!(invalid.is_err());
+
+        let history = executor
+            .run("SELECT record::history(person:1) AS v FROM person:1")
+            .await
+            .unwrap();
+        let history = history[0]["v"].as_array().unwrap();
+        let ages: Vec<_> = history.iter().map(|v| v["value"]["age"].clone()).collect();
+        assert_eq!(ages, [1, 2, 3].map(serde_json::Value::from));
+        let times: Vec<_> = history.iter().map(|v| v["at"].as_str().unwrap()).collect();
+        assert!(times.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[actix_rt::test]
+    async fn history_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "DEFINE TABLE person HISTORY 0s;
+                CREATE person:1 SET age = 1; UPDATE person:1 SET age = 2;
+                UPDATE person:1 SET age = 3; CREATE post:1 SET n = 1; UPDATE post:1 SET n = 2",
+            )
+            .await;
+        let history = |id: &str| {
+            let executor = &executor;
+            let query = format!("SELECT record::history({id}) AS v FROM person:1");
+            async move {
+                let result = executor.run(&query).await.unwrap();
+                result[0]["v"].as_array().unwrap().clone()
+            }
+        };
+        // Only the current version is within a retention of zero
+        let person = history("person:1").await;
+        assert_eq!(person.len(), 1);
+        assert_eq!(person[0]["value"]["age"], 3);
+        // Tables without a retention only keep the versions open
+        // transactions read
+        assert_eq!(history("post:1").await.len(), 1);
+        assert_eq!(history("person:9").await.len(), 0);
+        let text = executor.run("SELECT record::history('person:1') AS v FROM person:1");
+        assert!(text.await.is_err());
+    }
+}
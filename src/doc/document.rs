@@ -1,5 +1,9 @@
-use crate::ql::{record::Record, table::Table, value::Value};
-use std::sync::Arc;
+use crate::{
+    dbs::transaction::Txn,
+    idx::search::Matches,
+    ql::{record::Record, table::Table, value::Value},
+};
+use std::{collections::BTreeMap, sync::Arc};
 
 #[non_exhaustive]
 #[derive(Debug, Clone)]
@@ -12,10 +16,44 @@ pub struct Document {
 #[derive(Debug, Clone)]
 pub struct Cursor {
     pub value: Value,
+    /// The search index hits of the record, keyed by matches operator
+    pub matches: Matches,
+    /// The values `$` params refer to
+    pub params: BTreeMap<Arc<str>, Value>,
+    /// The transaction linked records are read in
+    pub txn: Txn,
+}
+
+impl Cursor {
+    pub fn with_matches(mut self, matches: Matches) -> Self {
+        self.matches = matches;
+        self
+    }
+
+    pub fn with_param<S: Into<Arc<str>>>(mut self, name: S, value: Value) -> Self {
+        self.params.insert(name.into(), value);
+        self
+    }
+
+    pub fn with_txn(mut self, txn: Txn) -> Self {
+        self.txn = txn;
+        self
+    }
+}
+
+/// The transaction records are read in while evaluating with a cursor, the
+/// latest committed state without one
+pub fn txn(cur: Option<&Cursor>) -> Txn {
+    cur.map(|cur| cur.txn).unwrap_or_default()
 }
 
 impl From<Value> for Cursor {
     fn from(value: Value) -> Self {
-        Cursor { value }
+        Cursor {
+            value,
+            matches: Matches::new(),
+            params: BTreeMap::new(),
+            txn: Txn::default(),
+        }
     }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    ql::{operator::Operator, part::Part, path::Path, value::Value},
+    ql::{operator::Operator, part::Part, path::Path, record::Record, value::Value},
     resp::Response,
 };
 use serde::Serialize;
@@ -67,6 +67,91 @@ pub enum Error {
 
     #[error("Table not found: {0}")]
     InvalidTable(String),
+
+    #[error("Record not found: {0}")]
+    RecordNotFound(String),
+
+    #[error("Unknown function: {0}")]
+    InvalidFunction(String),
+
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+
+    #[error("Invalid patch: {0}")]
+    InvalidPatch(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Index {index} already contains {value}, held by {record}")]
+    IndexExists {
+        index: String,
+        value: Value,
+        record: Record,
+    },
+
+    #[error("Index {index} expects vectors of {dimension} numbers, found {value}")]
+    InvalidVector {
+        index: String,
+        dimension: usize,
+        value: Value,
+    },
+
+    #[error("The KNN operator {0} must be a predicate at the top level of the WHERE clause when selecting from a table")]
+    UnplannedKnn(Operator),
+
+    #[error("Found {value} for field `{field}`, with record `{record}`, but expected a {kind}")]
+    FieldCoerce {
+        record: String,
+        field: String,
+        kind: String,
+        value: Value,
+    },
+
+    #[error("Found {value} for field `{field}`, with record `{record}`, but field must conform to: {check}")]
+    FieldAssert {
+        record: String,
+        field: String,
+        value: Value,
+        check: String,
+    },
+
+    #[error(
+        "Found changed value for field `{field}`, with record `{record}`, but field is readonly"
+    )]
+    FieldReadonly { record: String, field: String },
+
+    #[error("A transaction is already open")]
+    TransactionOpen,
+
+    #[error("Can not {0} without starting a transaction")]
+    NoTransaction(String),
+
+    #[error("Definitions can not be changed inside a transaction")]
+    DefineInTransaction,
+
+    #[error("Failed to commit transaction due to a write conflict on {0}, the transaction can be retried")]
+    TransactionConflict(String),
+
+    #[error("The query was not executed due to a cancelled transaction")]
+    QueryCancelled,
+
+    #[error("The query was not executed due to a failed transaction")]
+    QueryNotExecuted,
+
+    #[error("Parse error at line {line}, column {column}: {message}")]
+    ParseError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl Error {
+    /// Whether running the same queries again may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Error::TransactionConflict(_))
+    }
 }
 
 impl Serialize for Error {
@@ -83,3 +168,9 @@ impl From<serde_content::Error> for Error {
         Self::Serialization(err.to_string())
     }
 }
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Storage(err.to_string())
+    }
+}
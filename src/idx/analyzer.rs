@@ -0,0 +1,242 @@
+use crate::ql::{
+    filter::{Filter, Language},
+    tokenizer::Tokenizer,
+};
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// A span of the analyzed text along with the terms it was turned into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub start: usize,
+    pub end: usize,
+    pub terms: Vec<String>,
+}
+
+/// Splits text into tokens and runs each token through a chain of filters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Analyzer {
+    pub name: String,
+    pub tokenizers: Vec<Tokenizer>,
+    pub filters: Vec<Filter>,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Analyzer {
+            name: String::from("default"),
+            tokenizers: vec![Tokenizer::Blank, Tokenizer::Class],
+            filters: vec![Filter::Lowercase],
+        }
+    }
+}
+
+impl Analyzer {
+    pub fn new(name: String, tokenizers: Vec<Tokenizer>, filters: Vec<Filter>) -> Self {
+        Analyzer {
+            name,
+            tokenizers,
+            filters,
+        }
+    }
+
+    pub fn analyze(&self, text: &str) -> Vec<Token> {
+        let mut spans = vec![(0, text.len())];
+        for tokenizer in self.tokenizers.iter() {
+            spans = spans
+                .into_iter()
+                .flat_map(|(start, end)| split(*tokenizer, text, start, end))
+                .collect();
+        }
+        spans
+            .into_iter()
+            // Spans without letters or digits carry nothing to search for
+            .filter(|(start, end)| text[*start..*end].chars().any(char::is_alphanumeric))
+            .filter_map(|(start, end)| {
+                let mut terms = vec![text[start..end].to_string()];
+                for filter in self.filters.iter() {
+                    terms = terms.iter().flat_map(|term| apply(filter, term)).collect();
+                }
+                (!terms.is_empty()).then_some(Token { start, end, terms })
+            })
+            .collect()
+    }
+
+    pub fn terms(&self, text: &str) -> Vec<String> {
+        self.analyze(text)
+            .into_iter()
+            .flat_map(|token| token.terms)
+            .collect()
+    }
+
+    /// Checks the text holds every term of the query, used when no search
+    /// index covers the text being matched
+    pub fn matches(&self, text: &str, query: &str) -> bool {
+        let terms: BTreeSet<_> = self.terms(text).into_iter().collect();
+        let query = self.terms(query);
+        !query.is_empty() && query.iter().all(|term| terms.contains(term))
+    }
+
+    /// Wraps every token holding one of the terms in the prefix and suffix
+    pub fn highlight(&self, text: &str, terms: &BTreeSet<String>, pre: &str, post: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for token in self.analyze(text) {
+            if token.start < last || !token.terms.iter().any(|term| terms.contains(term)) {
+                continue;
+            }
+            out.push_str(&text[last..token.start]);
+            out.push_str(pre);
+            out.push_str(&text[token.start..token.end]);
+            out.push_str(post);
+            last = token.end;
+        }
+        out.push_str(&text[last..]);
+        out
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Class {
+    Alpha,
+    Digit,
+    Space,
+    Other,
+}
+
+impl Class {
+    fn of(c: char) -> Self {
+        match c {
+            c if c.is_alphabetic() => Class::Alpha,
+            c if c.is_numeric() => Class::Digit,
+            c if c.is_whitespace() => Class::Space,
+            _ => Class::Other,
+        }
+    }
+}
+
+fn split(tokenizer: Tokenizer, text: &str, start: usize, end: usize) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut from = start;
+    let mut prev: Option<char> = None;
+    for (i, c) in text[start..end].char_indices() {
+        let i = start + i;
+        let (cut, drop) = match tokenizer {
+            Tokenizer::Blank => (c.is_whitespace(), c.is_whitespace()),
+            Tokenizer::Punct => (c.is_ascii_punctuation(), c.is_ascii_punctuation()),
+            Tokenizer::Class => {
+                let cut = prev.is_some_and(|p| Class::of(p) != Class::of(c));
+                (cut, false)
+            }
+            Tokenizer::Camel => {
+                let cut = prev.is_some_and(|p| p.is_lowercase() && c.is_uppercase());
+                (cut, false)
+            }
+        };
+        if cut {
+            if from < i {
+                spans.push((from, i));
+            }
+            from = match drop {
+                true => i + c.len_utf8(),
+                false => i,
+            };
+        }
+        prev = Some(c);
+    }
+    if from < end {
+        spans.push((from, end));
+    }
+    spans
+        .into_iter()
+        .filter(|(start, end)| !text[*start..*end].trim().is_empty())
+        .collect()
+}
+
+fn apply(filter: &Filter, term: &str) -> Vec<String> {
+    match filter {
+        Filter::Ascii => vec![deunicode::deunicode(term)],
+        Filter::Lowercase => vec![term.to_lowercase()],
+        Filter::Uppercase => vec![term.to_uppercase()],
+        Filter::Snowball(lang) => vec![stemmer(*lang).stem(term).into_owned()],
+        Filter::EdgeNgram(min, max) => {
+            let chars: Vec<char> = term.chars().collect();
+            let (min, max) = (*min as usize, (*max as usize).min(chars.len()));
+            (min.max(1)..=max)
+                .map(|len| chars[..len].iter().collect())
+                .collect()
+        }
+    }
+}
+
+fn stemmer(lang: Language) -> Stemmer {
+    Stemmer::create(match lang {
+        Language::Arabic => Algorithm::Arabic,
+        Language::Danish => Algorithm::Danish,
+        Language::Dutch => Algorithm::Dutch,
+        Language::English => Algorithm::English,
+        Language::French => Algorithm::French,
+        Language::German => Algorithm::German,
+        Language::Greek => Algorithm::Greek,
+        Language::Hungarian => Algorithm::Hungarian,
+        Language::Italian => Algorithm::Italian,
+        Language::Norwegian => Algorithm::Norwegian,
+        Language::Portuguese => Algorithm::Portuguese,
+        Language::Romanian => Algorithm::Romanian,
+        Language::Russian => Algorithm::Russian,
+        Language::Spanish => Algorithm::Spanish,
+        Language::Swedish => Algorithm::Swedish,
+        Language::Tamil => Algorithm::Tamil,
+        Language::Turkish => Algorithm::Turkish,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn analyzer(tokenizers: Vec<Tokenizer>, filters: Vec<Filter>) -> Analyzer {
+        Analyzer::new(String::from("test"), tokenizers, filters)
+    }
+
+    #[test]
+    fn analyzer_tokenizer_test() {
+        let blank = analyzer(vec![Tokenizer::Blank], vec![]);
+        assert_eq!(blank.terms("Hello  World!"), vec!["Hello", "World!"]);
+        let class = analyzer(vec![Tokenizer::Blank, Tokenizer::Class], vec![]);
+        assert_eq!(class.terms("abc123 x-y"), vec!["abc", "123", "x", "y"]);
+        let camel = analyzer(vec![Tokenizer::Camel, Tokenizer::Punct], vec![]);
+        assert_eq!(camel.terms("helloWorld,foo"), vec!["hello", "World", "foo"]);
+        let tokens = blank.analyze(" héllo wörld");
+        assert_eq!((tokens[1].start, tokens[1].end), (8, 14));
+    }
+
+    #[test]
+    fn analyzer_filter_test() {
+        let filters = vec![
+            Filter::Lowercase,
+            Filter::Ascii,
+            Filter::Snowball(Language::English),
+        ];
+        let stem = analyzer(vec![Tokenizer::Blank], filters);
+        assert_eq!(stem.terms("Running Cafés"), vec!["run", "cafe"]);
+        let ngram = analyzer(vec![Tokenizer::Blank], vec![Filter::EdgeNgram(2, 4)]);
+        assert_eq!(ngram.terms("hello a"), vec!["he", "hel", "hell"]);
+        assert!(stem.matches("The runners were running", "RUN"));
+        assert!(!stem.matches("The runners were running", "run fast"));
+    }
+
+    #[test]
+    fn analyzer_highlight_test() {
+        let stem = analyzer(
+            vec![Tokenizer::Blank, Tokenizer::Class],
+            vec![Filter::Lowercase, Filter::Snowball(Language::English)],
+        );
+        let terms = BTreeSet::from([String::from("run")]);
+        assert_eq!(
+            stem.highlight("Running, he runs.", &terms, "<b>", "</b>"),
+            "<b>Running</b>, he <b>runs</b>."
+        );
+    }
+}
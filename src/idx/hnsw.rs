@@ -0,0 +1,308 @@
+use crate::{
+    err::Error,
+    ql::{distance::Distance, idiom::Idiom, value::Value},
+};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+/// A record found during a search, ordered by its distance to the query
+#[derive(Debug, Clone, PartialEq)]
+struct Near(f64, Value);
+
+impl Eq for Near {}
+
+impl PartialOrd for Near {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Near {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| self.1.cmp(&other.1))
+    }
+}
+
+/// An approximate nearest neighbour index over a vector field, a stack of
+/// proximity graphs where each layer holds a shrinking sample of the records
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hnsw {
+    pub name: String,
+    pub field: Idiom,
+    pub dimension: usize,
+    pub distance: Distance,
+    m: usize,
+    efc: usize,
+    vectors: HashMap<Value, Vec<f64>>,
+    layers: Vec<HashMap<Value, Vec<Value>>>,
+    entry: Option<Value>,
+    seed: u64,
+}
+
+impl Hnsw {
+    pub fn new(
+        name: String,
+        field: Idiom,
+        dimension: usize,
+        distance: Distance,
+        m: usize,
+        efc: usize,
+    ) -> Self {
+        Hnsw {
+            name,
+            field,
+            dimension,
+            distance,
+            m: m.max(2),
+            efc: efc.max(1),
+            vectors: HashMap::new(),
+            layers: Vec::new(),
+            entry: None,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// The vector a field holds, records without one are left out of the index
+    pub fn vector(&self, value: &Value) -> Result<Option<Vec<f64>>, Error> {
+        match value {
+            Value::None | Value::Null => Ok(None),
+            value => match value.to_vector() {
+                Some(vector) if vector.len() == self.dimension => Ok(Some(vector)),
+                _ => Err(Error::InvalidVector {
+                    index: self.name.clone(),
+                    dimension: self.dimension,
+                    value: value.clone(),
+                }),
+            },
+        }
+    }
+
+    pub fn insert(&mut self, id: Value, value: &Value) {
+        self.remove(&id);
+        let Ok(Some(vector)) = self.vector(value) else {
+            return;
+        };
+        let level = self.level();
+        while self.layers.len() <= level {
+            self.layers.push(HashMap::new());
+        }
+        self.vectors.insert(id.clone(), vector.clone());
+        let Some(mut entry) = self.entry.clone() else {
+            for layer in self.layers[..=level].iter_mut() {
+                layer.insert(id.clone(), Vec::new());
+            }
+            self.entry = Some(id);
+            return;
+        };
+
+        let top = self.top(&entry);
+        for layer in (level + 1..=top).rev() {
+            entry = self.greedy(&vector, entry, layer);
+        }
+        let mut entries = vec![entry];
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&vector, &entries, self.efc, layer);
+            let neighbours: Vec<_> = found.iter().take(self.m).map(|n| n.1.clone()).collect();
+            for neighbour in neighbours.iter() {
+                if let Some(links) = self.layers[layer].get_mut(neighbour) {
+                    links.push(id.clone());
+                }
+                self.prune(neighbour, layer);
+            }
+            self.layers[layer].insert(id.clone(), neighbours);
+            entries = found.into_iter().map(|n| n.1).collect();
+        }
+        for layer in self.layers[top + 1..=level.max(top)].iter_mut() {
+            layer.insert(id.clone(), Vec::new());
+        }
+        if level > top {
+            self.entry = Some(id);
+        }
+    }
+
+    pub fn remove(&mut self, id: &Value) {
+        if self.vectors.remove(id).is_none() {
+            return;
+        }
+        for layer in 0..self.layers.len() {
+            let Some(neighbours) = self.layers[layer].remove(id) else {
+                continue;
+            };
+            for links in self.layers[layer].values_mut() {
+                links.retain(|link| link != id);
+            }
+            // Former neighbours are linked to each other so the graph stays
+            // connected around the gap
+            for neighbour in neighbours.iter() {
+                if let Some(links) = self.layers[layer].get_mut(neighbour) {
+                    for other in neighbours.iter() {
+                        if other != neighbour && !links.contains(other) {
+                            links.push(other.clone());
+                        }
+                    }
+                }
+                self.prune(neighbour, layer);
+            }
+        }
+        while self.layers.last().is_some_and(HashMap::is_empty) {
+            self.layers.pop();
+        }
+        if self.entry.as_ref() == Some(id) {
+            self.entry = self.layers.last().and_then(|l| l.keys().next().cloned());
+        }
+    }
+
+    /// The ids of the k records nearest to the query, nearest first
+    pub fn search(&self, query: &[f64], k: usize) -> Vec<(Value, f64)> {
+        let Some(mut entry) = self.entry.clone() else {
+            return Vec::new();
+        };
+        if query.len() != self.dimension {
+            return Vec::new();
+        }
+        for layer in (1..self.layers.len()).rev() {
+            entry = self.greedy(query, entry, layer);
+        }
+        self.search_layer(query, &[entry], self.efc.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|Near(dist, id)| (id, dist))
+            .collect()
+    }
+
+    fn dist(&self, query: &[f64], id: &Value) -> f64 {
+        self.distance.compute(query, &self.vectors[id])
+    }
+
+    /// The highest layer a record is linked in
+    fn top(&self, id: &Value) -> usize {
+        self.layers
+            .iter()
+            .rposition(|layer| layer.contains_key(id))
+            .unwrap_or_default()
+    }
+
+    /// Picks the layer a new record reaches, each layer up is m times sparser
+    fn level(&mut self) -> usize {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        let uniform = (self.seed >> 11) as f64 / (1u64 << 53) as f64;
+        (-(1.0 - uniform).ln() / (self.m as f64).ln()).floor() as usize
+    }
+
+    fn greedy(&self, query: &[f64], mut entry: Value, layer: usize) -> Value {
+        let mut best = self.dist(query, &entry);
+        loop {
+            let mut moved = false;
+            for neighbour in self.layers[layer].get(&entry).into_iter().flatten() {
+                let dist = self.dist(query, neighbour);
+                if dist < best {
+                    (best, entry, moved) = (dist, neighbour.clone(), true);
+                }
+            }
+            if !moved {
+                return entry;
+            }
+        }
+    }
+
+    fn search_layer(&self, query: &[f64], entries: &[Value], ef: usize, layer: usize) -> Vec<Near> {
+        let mut visited: HashSet<Value> = entries.iter().cloned().collect();
+        let mut candidates = BinaryHeap::new();
+        let mut found = BinaryHeap::new();
+        for entry in entries {
+            let near = Near(self.dist(query, entry), entry.clone());
+            candidates.push(Reverse(near.clone()));
+            found.push(near);
+        }
+        while let Some(Reverse(near)) = candidates.pop() {
+            if found.len() >= ef && found.peek().is_some_and(|far: &Near| near.0 > far.0) {
+                break;
+            }
+            for neighbour in self.layers[layer].get(&near.1).into_iter().flatten() {
+                if !visited.insert(neighbour.clone()) {
+                    continue;
+                }
+                let dist = self.dist(query, neighbour);
+                if found.len() < ef || found.peek().is_some_and(|far| dist < far.0) {
+                    candidates.push(Reverse(Near(dist, neighbour.clone())));
+                    found.push(Near(dist, neighbour.clone()));
+                    if found.len() > ef {
+                        found.pop();
+                    }
+                }
+            }
+        }
+        found.into_sorted_vec()
+    }
+
+    /// Keeps only the closest links of a record once it has too many
+    fn prune(&mut self, id: &Value, layer: usize) {
+        let max = match layer {
+            0 => self.m * 2,
+            _ => self.m,
+        };
+        let Some(links) = self.layers[layer].get(id) else {
+            return;
+        };
+        if links.len() <= max {
+            return;
+        }
+        let vector = &self.vectors[id];
+        let mut near: Vec<_> = links
+            .iter()
+            .map(|link| Near(self.dist(vector, link), link.clone()))
+            .collect();
+        near.sort();
+        let links = near.into_iter().take(max).map(|n| n.1).collect();
+        self.layers[layer].insert(id.clone(), links);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ql::{ident::Ident, part::Part};
+
+    fn point(i: i64) -> Value {
+        vec![Value::from((i * 7) % 31), Value::from((i * 13) % 29)].into()
+    }
+
+    #[test]
+    fn hnsw_search_test() {
+        let field = Idiom(vec![Part::Field(Ident::new("pt"))]);
+        let mut hnsw = Hnsw::new(String::from("pt"), field, 2, Distance::Euclidean, 4, 40);
+        for i in 0..300 {
+            hnsw.insert(i.into(), &point(i));
+        }
+        hnsw.insert(300.into(), &Value::None);
+        assert!(hnsw.vector(&vec![Value::from(1)].into()).is_err());
+
+        let query = [10.0, 10.0];
+        let exact = |hnsw: &Hnsw| {
+            let mut all: Vec<_> = hnsw
+                .vectors
+                .iter()
+                .map(|(id, v)| Near(Distance::Euclidean.compute(&query, v), id.clone()))
+                .collect();
+            all.sort();
+            all.into_iter().take(5).map(|n| n.0).collect::<Vec<_>>()
+        };
+        let found: Vec<_> = hnsw.search(&query, 5).into_iter().map(|n| n.1).collect();
+        assert_eq!(found, exact(&hnsw));
+
+        for i in 0..150 {
+            hnsw.remove(&(i * 2).into());
+        }
+        let found = hnsw.search(&query, 5);
+        assert!(found.iter().all(|(id, _)| hnsw.vectors.contains_key(id)));
+        let dists: Vec<_> = found.into_iter().map(|n| n.1).collect();
+        assert_eq!(dists, exact(&hnsw));
+        assert!(hnsw.search(&[1.0], 5).is_empty());
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod analyzer;
+pub(crate) mod hnsw;
+pub(crate) mod search;
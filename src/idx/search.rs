@@ -0,0 +1,186 @@
+use crate::{
+    idx::analyzer::Analyzer,
+    ql::{idiom::Idiom, value::Value},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Doc {
+    terms: BTreeMap<String, u32>,
+    len: u32,
+}
+
+/// A full-text index over a single field, scoring matches with BM25
+#[derive(Debug, Clone, PartialEq)]
+pub struct Search {
+    pub name: String,
+    pub field: Idiom,
+    pub analyzer: Arc<Analyzer>,
+    k1: f64,
+    b: f64,
+    docs: HashMap<Value, Doc>,
+    postings: BTreeMap<String, BTreeSet<Value>>,
+    total: u64,
+}
+
+/// How a record fares against a query on a search index
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub matched: bool,
+    pub score: f64,
+    pub field: Idiom,
+    pub terms: BTreeSet<String>,
+    pub analyzer: Arc<Analyzer>,
+}
+
+/// The hits of a record against each matches operator in a condition, keyed
+/// by the reference of the operator
+pub type Matches = BTreeMap<Option<u8>, Hit>;
+
+impl Search {
+    pub fn new(name: String, field: Idiom, analyzer: Arc<Analyzer>, k1: f64, b: f64) -> Self {
+        Search {
+            name,
+            field,
+            analyzer,
+            k1,
+            b,
+            docs: HashMap::new(),
+            postings: BTreeMap::new(),
+            total: 0,
+        }
+    }
+
+    pub fn insert(&mut self, id: Value, text: &Value) {
+        self.remove(&id);
+        let mut doc = Doc::default();
+        for term in self.texts(text).iter().flat_map(|t| self.analyzer.terms(t)) {
+            *doc.terms.entry(term).or_default() += 1;
+            doc.len += 1;
+        }
+        for term in doc.terms.keys() {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(id.clone());
+        }
+        self.total += doc.len as u64;
+        self.docs.insert(id, doc);
+    }
+
+    pub fn remove(&mut self, id: &Value) {
+        let Some(doc) = self.docs.remove(id) else {
+            return;
+        };
+        for term in doc.terms.keys() {
+            if let Some(ids) = self.postings.get_mut(term) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.total -= doc.len as u64;
+    }
+
+    /// The ids of the records holding every term of the query
+    pub fn matches(&self, query: &str) -> Vec<Value> {
+        let terms = self.query(query);
+        let mut terms = terms.iter();
+        let Some(first) = terms.next() else {
+            return Vec::new();
+        };
+        let mut ids = self.postings.get(first).cloned().unwrap_or_default();
+        for term in terms {
+            let Some(with) = self.postings.get(term) else {
+                return Vec::new();
+            };
+            ids.retain(|id| with.contains(id));
+        }
+        ids.into_iter().collect()
+    }
+
+    pub fn hit(&self, id: &Value, query: &str) -> Hit {
+        let terms = self.query(query);
+        let doc = self.docs.get(id);
+        let matched = !terms.is_empty()
+            && doc.is_some_and(|doc| terms.iter().all(|t| doc.terms.contains_key(t)));
+        let score = match doc {
+            Some(doc) if matched => self.score(doc, &terms),
+            _ => 0.0,
+        };
+        Hit {
+            matched,
+            score,
+            field: self.field.clone(),
+            terms,
+            analyzer: self.analyzer.clone(),
+        }
+    }
+
+    fn score(&self, doc: &Doc, terms: &BTreeSet<String>) -> f64 {
+        let count = self.docs.len() as f64;
+        let average = self.total as f64 / count;
+        terms
+            .iter()
+            .map(|term| {
+                let freq = doc.terms.get(term).copied().unwrap_or_default() as f64;
+                let found = self.postings.get(term).map_or(0, BTreeSet::len) as f64;
+                let idf = (1.0 + (count - found + 0.5) / (found + 0.5)).ln();
+                let norm = 1.0 - self.b + self.b * doc.len as f64 / average;
+                idf * freq * (self.k1 + 1.0) / (freq + self.k1 * norm)
+            })
+            .sum()
+    }
+
+    fn query(&self, query: &str) -> BTreeSet<String> {
+        self.analyzer.terms(query).into_iter().collect()
+    }
+
+    fn texts<'a>(&self, value: &'a Value) -> Vec<&'a str> {
+        match value {
+            Value::String(v) => vec![&*v.0],
+            Value::Array(v) => v.iter().flat_map(|v| self.texts(v)).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ql::{filter::Filter, ident::Ident, part::Part, tokenizer::Tokenizer};
+
+    #[test]
+    fn search_score_test() {
+        let analyzer = Analyzer::new(
+            String::from("simple"),
+            vec![Tokenizer::Blank],
+            vec![Filter::Lowercase],
+        );
+        let field = Idiom(vec![Part::Field(Ident::new("text"))]);
+        let mut search = Search::new(String::from("ft"), field, Arc::new(analyzer), 1.2, 0.75);
+        search.insert(1.into(), &"the quick brown fox".into());
+        search.insert(2.into(), &"the lazy dog and the lazy cat".into());
+        search.insert(3.into(), &vec![Value::from("a quick dog"), 5.into()].into());
+
+        assert_eq!(search.matches("QUICK"), vec![Value::from(1), 3.into()]);
+        assert_eq!(search.matches("quick dog"), vec![Value::from(3)]);
+        assert!(search.matches("quick cat").is_empty());
+
+        let lazy = search.hit(&2.into(), "lazy");
+        assert!(lazy.matched && lazy.score > 0.0);
+        assert!(!search.hit(&1.into(), "lazy").matched);
+        let short = search.hit(&3.into(), "dog").score;
+        let long = search.hit(&2.into(), "dog").score;
+        assert!(short > long);
+
+        search.remove(&3.into());
+        assert_eq!(search.matches("quick"), vec![Value::from(1)]);
+        search.insert(1.into(), &"slow".into());
+        assert!(search.matches("quick").is_empty());
+    }
+}
@@ -0,0 +1,409 @@
+use crate::{
+    err::Error,
+    idx::analyzer::Analyzer,
+    kvs::log::Log,
+    par,
+    ql::{
+        array::Array, datetime::Datetime, definition::Definition, id::Id, ident::Ident,
+        idiom::Idiom, index::Index, number::Number, object::Object, part::Part, query::Query,
+        record::Record, statements::define::Define, table::Table, uuid::Uuid, value::Value,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+/// The on-disk form of a value, unlike the JSON responses it keeps records,
+/// uuids, datetimes and the int and float distinction intact
+#[derive(Debug, Serialize, Deserialize)]
+enum Stored {
+    None,
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Uuid(String),
+    Datetime(i64),
+    Record(StoredRecord),
+    Array(Vec<Stored>),
+    Object(BTreeMap<String, Stored>),
+    Table(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum StoredId {
+    Number(i64),
+    String(String),
+    Uuid(String),
+    Array(Vec<Stored>),
+    Object(BTreeMap<String, Stored>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRecord(String, StoredId);
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Entry {
+    Define(String),
+    Analyzer(Analyzer),
+    History {
+        table: String,
+        history: Duration,
+    },
+    Schema {
+        table: String,
+        schemafull: bool,
+    },
+    /// Field definitions are stored as the statement that defined them
+    Field {
+        table: String,
+        source: String,
+    },
+    Index {
+        table: String,
+        name: String,
+        fields: Vec<Vec<String>>,
+        index: Index,
+    },
+    Put(StoredRecord, BTreeMap<String, Stored>, u64),
+    Relate {
+        id: StoredRecord,
+        origin: StoredRecord,
+        destination: StoredRecord,
+        fields: BTreeMap<String, Stored>,
+        at: u64,
+    },
+    Purge(StoredRecord, u64),
+    Commit(Vec<Entry>),
+}
+
+pub fn encode(log: &Log) -> Result<String, Error> {
+    serde_json::to_string(&entry(log)?).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+pub fn decode(line: &str) -> Result<Log, Error> {
+    let entry: Entry =
+        serde_json::from_str(line).map_err(|e| Error::Serialization(e.to_string()))?;
+    log(entry)
+}
+
+fn entry(log: &Log) -> Result<Entry, Error> {
+    Ok(match log {
+        Log::Define(table) => Entry::Define(table.clone()),
+        Log::Analyzer(analyzer) => Entry::Analyzer(analyzer.clone()),
+        Log::History { table, history } => Entry::History {
+            table: table.clone(),
+            history: *history,
+        },
+        Log::Schema { table, schemafull } => Entry::Schema {
+            table: table.clone(),
+            schemafull: *schemafull,
+        },
+        Log::Field { table, source, .. } => Entry::Field {
+            table: table.clone(),
+            source: source.clone(),
+        },
+        Log::Index {
+            table,
+            name,
+            fields,
+            index,
+        } => Entry::Index {
+            table: table.clone(),
+            name: name.clone(),
+            fields: fields.iter().map(encode_idiom).collect::<Result<_, _>>()?,
+            index: index.clone(),
+        },
+        Log::Put { id, fields, at } => Entry::Put(id.try_into()?, encode_fields(fields)?, *at),
+        Log::Relate {
+            id,
+            origin,
+            destination,
+            fields,
+            at,
+        } => Entry::Relate {
+            id: id.try_into()?,
+            origin: origin.try_into()?,
+            destination: destination.try_into()?,
+            fields: encode_fields(fields)?,
+            at: *at,
+        },
+        Log::Purge { id, at } => Entry::Purge(id.try_into()?, *at),
+        Log::Commit(logs) => Entry::Commit(logs.iter().map(entry).collect::<Result<_, _>>()?),
+    })
+}
+
+fn log(entry: Entry) -> Result<Log, Error> {
+    Ok(match entry {
+        Entry::Define(table) => Log::Define(table),
+        Entry::Analyzer(analyzer) => Log::Analyzer(analyzer),
+        Entry::History { table, history } => Log::History { table, history },
+        Entry::Schema { table, schemafull } => Log::Schema { table, schemafull },
+        Entry::Field { table, source } => Log::Field {
+            table,
+            definition: decode_definition(&source)?,
+            source,
+        },
+        Entry::Index {
+            table,
+            name,
+            fields,
+            index,
+        } => Log::Index {
+            table,
+            name,
+            fields: fields.into_iter().map(decode_idiom).collect(),
+            index,
+        },
+        Entry::Put(id, fields, at) => Log::Put {
+            id: id.try_into()?,
+            fields: decode_fields(fields)?,
+            at,
+        },
+        Entry::Relate {
+            id,
+            origin,
+            destination,
+            fields,
+            at,
+        } => Log::Relate {
+            id: id.try_into()?,
+            origin: origin.try_into()?,
+            destination: destination.try_into()?,
+            fields: decode_fields(fields)?,
+            at,
+        },
+        Entry::Purge(id, at) => Log::Purge {
+            id: id.try_into()?,
+            at,
+        },
+        Entry::Commit(entries) => {
+            Log::Commit(entries.into_iter().map(log).collect::<Result<_, _>>()?)
+        }
+    })
+}
+
+fn decode_definition(source: &str) -> Result<Definition, Error> {
+    match par::parse(source)?.as_slice() {
+        [Query::Define(Define::Field { definition, .. })] => Ok(definition.clone()),
+        _ => Err(Error::Serialization(format!(
+            "Invalid field definition: {source}"
+        ))),
+    }
+}
+
+fn encode_fields(fields: &BTreeMap<Arc<str>, Value>) -> Result<BTreeMap<String, Stored>, Error> {
+    fields
+        .iter()
+        .map(|(k, v)| Ok((k.to_string(), v.try_into()?)))
+        .collect()
+}
+
+fn decode_fields(fields: BTreeMap<String, Stored>) -> Result<BTreeMap<Arc<str>, Value>, Error> {
+    fields
+        .into_iter()
+        .map(|(k, v)| Ok((k.into(), v.try_into()?)))
+        .collect()
+}
+
+fn encode_idiom(idiom: &Idiom) -> Result<Vec<String>, Error> {
+    idiom
+        .iter()
+        .map(|part| match part {
+            Part::Field(Ident(field)) => Ok(field.to_string()),
+            part => Err(Error::Serialization(format!("{part} can not be stored"))),
+        })
+        .collect()
+}
+
+fn decode_idiom(fields: Vec<String>) -> Idiom {
+    Idiom(
+        fields
+            .iter()
+            .map(|field| Part::Field(Ident::new(field.as_str())))
+            .collect(),
+    )
+}
+
+fn parse_uuid(v: &str) -> Result<uuid::Uuid, Error> {
+    uuid::Uuid::try_parse(v).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+impl TryFrom<&Value> for Stored {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Value::None => Stored::None,
+            Value::Null => Stored::Null,
+            Value::Bool(v) => Stored::Bool(*v),
+            Value::Number(Number::Int(v)) => Stored::Int(*v),
+            Value::Number(Number::Float(v)) => Stored::Float(*v),
+            Value::String(v) => Stored::String(v.as_string()),
+            Value::Uuid(v) => Stored::Uuid(v.0.to_string()),
+            Value::Datetime(v) => Stored::Datetime(v.0),
+            Value::Record(v) => Stored::Record(v.as_ref().try_into()?),
+            Value::Array(v) => {
+                Stored::Array(v.iter().map(TryInto::try_into).collect::<Result<_, _>>()?)
+            }
+            Value::Object(v) => Stored::Object(encode_fields(v)?),
+            Value::Table(Table(v)) => Stored::Table(v.clone()),
+            v => return Err(Error::Serialization(format!("{v} can not be stored"))),
+        })
+    }
+}
+
+impl TryFrom<Stored> for Value {
+    type Error = Error;
+
+    fn try_from(stored: Stored) -> Result<Self, Self::Error> {
+        Ok(match stored {
+            Stored::None => Value::None,
+            Stored::Null => Value::Null,
+            Stored::Bool(v) => Value::Bool(v),
+            Stored::Int(v) => Value::Number(Number::Int(v)),
+            Stored::Float(v) => Value::Number(Number::Float(v)),
+            Stored::String(v) => Value::from(v),
+            Stored::Uuid(v) => Value::Uuid(Uuid(parse_uuid(&v)?)),
+            Stored::Datetime(v) => Value::Datetime(Datetime(v)),
+            Stored::Record(v) => Value::Record(Box::new(v.try_into()?)),
+            Stored::Array(v) => Value::Array(decode_array(v)?),
+            Stored::Object(v) => Value::Object(Object(decode_fields(v)?)),
+            Stored::Table(v) => Value::Table(Table(v)),
+        })
+    }
+}
+
+fn decode_array(values: Vec<Stored>) -> Result<Array, Error> {
+    let values = values
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<Value>, _>>()?;
+    Ok(values.into())
+}
+
+impl TryFrom<&Record> for StoredRecord {
+    type Error = Error;
+
+    fn try_from(record: &Record) -> Result<Self, Self::Error> {
+        let id = match &record.id {
+            Id::Number(v) => StoredId::Number(*v),
+            Id::String(v) => StoredId::String(v.clone()),
+            Id::Uuid(v) => StoredId::Uuid(v.to_string()),
+            Id::Array(v) => {
+                StoredId::Array(v.iter().map(TryInto::try_into).collect::<Result<_, _>>()?)
+            }
+            Id::Object(v) => StoredId::Object(encode_fields(v)?),
+        };
+        Ok(StoredRecord(record.table.to_string(), id))
+    }
+}
+
+impl TryFrom<StoredRecord> for Record {
+    type Error = Error;
+
+    fn try_from(StoredRecord(table, id): StoredRecord) -> Result<Self, Self::Error> {
+        let id = match id {
+            StoredId::Number(v) => Id::Number(v),
+            StoredId::String(v) => Id::String(v),
+            StoredId::Uuid(v) => Id::Uuid(parse_uuid(&v)?),
+            StoredId::Array(v) => Id::Array(decode_array(v)?),
+            StoredId::Object(v) => Id::Object(Object(decode_fields(v)?)),
+        };
+        Ok(Record::new(table, id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ql::{
+        filter::{Filter, Language},
+        tokenizer::Tokenizer,
+    };
+
+    #[test]
+    fn codec_roundtrip_test() {
+        let log = Log::Relate {
+            id: Record::new("knows", Id::Uuid(uuid::Uuid::now_v7())),
+            origin: Record::new("person", 1),
+            destination: Record::new("person", "tobie"),
+            fields: BTreeMap::from([
+                ("since".into(), Value::Number(Number::Float(2.0))),
+                ("count".into(), 2.into()),
+                ("link".into(), Record::new("a", vec![Value::from(1)]).into()),
+                (
+                    "meta".into(),
+                    vec![Value::Null, Value::None, "x".into()].into(),
+                ),
+                ("uuid".into(), Value::Uuid(Uuid::new())),
+                ("at".into(), Value::Datetime(Datetime::now())),
+            ]),
+            at: 1_700_000_000_000_000_000,
+        };
+        let line = encode(&log).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(decode(&line).unwrap(), log);
+        assert!(decode("{").is_err());
+
+        let index = Log::Index {
+            table: String::from("person"),
+            name: String::from("idx"),
+            fields: vec![Idiom(vec![
+                Part::Field(Ident::new("meta")),
+                Part::Field(Ident::new("email")),
+            ])],
+            index: Index::Uniq,
+        };
+        assert_eq!(decode(&encode(&index).unwrap()).unwrap(), index);
+
+        let analyzer = Log::Analyzer(Analyzer::new(
+            String::from("english"),
+            vec![Tokenizer::Blank, Tokenizer::Class],
+            vec![
+                Filter::Ascii,
+                Filter::EdgeNgram(1, 3),
+                Filter::Snowball(Language::English),
+            ],
+        ));
+        assert_eq!(decode(&encode(&analyzer).unwrap()).unwrap(), analyzer);
+        let search = Log::Index {
+            table: String::from("post"),
+            name: String::from("ft"),
+            fields: vec![Idiom(vec![Part::Field(Ident::new("text"))])],
+            index: Index::search(String::from("english")),
+        };
+        assert_eq!(decode(&encode(&search).unwrap()).unwrap(), search);
+        let history = Log::History {
+            table: String::from("person"),
+            history: Duration::from_secs(30 * 86_400),
+        };
+        assert_eq!(decode(&encode(&history).unwrap()).unwrap(), history);
+
+        let source = String::from("DEFINE FIELD tags.* ON person TYPE string ASSERT $value != ''");
+        let Query::Define(Define::Field { definition, .. }) =
+            par::parse(&source).unwrap().0.remove(0)
+        else {
+            panic!("expected a field");
+        };
+        let field = Log::Field {
+            table: String::from("person"),
+            definition,
+            source,
+        };
+        assert_eq!(decode(&encode(&field).unwrap()).unwrap(), field);
+        let schema = Log::Schema {
+            table: String::from("person"),
+            schemafull: true,
+        };
+        assert_eq!(decode(&encode(&schema).unwrap()).unwrap(), schema);
+        let purge = Log::Purge {
+            id: Record::new("person", 1),
+            at: 1,
+        };
+        let commit = Log::Commit(vec![log, purge]);
+        let line = encode(&commit).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(decode(&line).unwrap(), commit);
+    }
+}
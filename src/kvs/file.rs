@@ -0,0 +1,131 @@
+use crate::{
+    err::Error,
+    kvs::{codec, log::Log, store::Store},
+};
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const WAL: &str = "wal.log";
+const SNAPSHOT: &str = "snapshot.log";
+const SNAPSHOT_TMP: &str = "snapshot.tmp";
+
+/// Stores logs in a data directory as a snapshot and a write-ahead log, both
+/// holding one encoded log per line
+#[derive(Debug)]
+pub struct File {
+    dir: PathBuf,
+    wal: fs::File,
+}
+
+impl File {
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let wal = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(dir.join(WAL))?;
+        Ok(File { dir, wal })
+    }
+}
+
+impl Store for File {
+    fn append(&mut self, log: &Log) -> Result<(), Error> {
+        let mut line = codec::encode(log)?;
+        line.push('\n');
+        self.wal.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<(), Error> {
+        self.wal.sync_data()?;
+        Ok(())
+    }
+
+    fn snapshot(&mut self, logs: &[Log]) -> Result<(), Error> {
+        let tmp = self.dir.join(SNAPSHOT_TMP);
+        let mut writer = BufWriter::new(fs::File::create(&tmp)?);
+        for log in logs {
+            writeln!(writer, "{}", codec::encode(log)?)?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        fs::rename(tmp, self.dir.join(SNAPSHOT))?;
+        self.wal.set_len(0)?;
+        self.wal.sync_all()?;
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<Vec<Log>, Error> {
+        let mut logs = Vec::new();
+        match fs::read_to_string(self.dir.join(SNAPSHOT)) {
+            Ok(snapshot) => {
+                for line in snapshot.lines() {
+                    logs.push(codec::decode(line)?);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut wal = String::new();
+        fs::File::open(self.dir.join(WAL))?.read_to_string(&mut wal)?;
+        let mut valid = 0;
+        for line in wal.split_inclusive('\n') {
+            // A line without a newline is a write that never finished
+            let Some(line) = line.strip_suffix('\n') else {
+                break;
+            };
+            logs.push(codec::decode(line)?);
+            valid += line.len() + 1;
+        }
+        if valid < wal.len() {
+            self.wal.set_len(valid as u64)?;
+        }
+        Ok(logs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ql::record::Record;
+    use std::collections::BTreeMap;
+
+    fn put(id: i64) -> Log {
+        Log::Put {
+            id: Record::new("person", id),
+            fields: BTreeMap::from([("id".into(), Record::new("person", id).into())]),
+            at: id as u64,
+        }
+    }
+
+    #[test]
+    fn file_store_test() {
+        let dir = std::env::temp_dir().join(format!("catalystdb-{}", uuid::Uuid::new_v4()));
+        let mut store = File::open(&dir).unwrap();
+        store.append(&put(1)).unwrap();
+        store.snapshot(&[put(1)]).unwrap();
+        store.append(&put(2)).unwrap();
+        store.sync().unwrap();
+        drop(store);
+
+        // A commit cut short is dropped whole
+        let commit = codec::encode(&Log::Commit(vec![put(3), put(4)])).unwrap();
+        let mut wal = OpenOptions::new().append(true).open(dir.join(WAL)).unwrap();
+        wal.write_all(&commit.as_bytes()[..commit.len() / 2]).unwrap();
+        drop(wal);
+
+        let mut store = File::open(&dir).unwrap();
+        assert_eq!(store.load().unwrap(), vec![put(1), put(2)]);
+        store.append(&put(3)).unwrap();
+        assert_eq!(store.load().unwrap(), vec![put(1), put(2), put(3)]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
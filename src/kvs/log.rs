@@ -0,0 +1,366 @@
+use crate::{
+    idx::analyzer::Analyzer,
+    ql::{definition::Definition, idiom::Idiom, index::Index, record::Record, value::Value},
+};
+use actix::Message;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::Duration,
+};
+
+/// A single change to the graph, every entry carries the full state of the
+/// record it touches and the time it was committed at, so replaying an
+/// entry twice is harmless
+#[non_exhaustive]
+#[derive(Message, Debug, Clone, PartialEq)]
+#[rtype(result = "()")]
+pub enum Log {
+    Define(String),
+    Analyzer(Analyzer),
+    History {
+        table: String,
+        history: Duration,
+    },
+    Schema {
+        table: String,
+        schemafull: bool,
+    },
+    Field {
+        table: String,
+        definition: Definition,
+        source: String,
+    },
+    Index {
+        table: String,
+        name: String,
+        fields: Vec<Idiom>,
+        index: Index,
+    },
+    Put {
+        id: Record,
+        fields: BTreeMap<Arc<str>, Value>,
+        at: u64,
+    },
+    Relate {
+        id: Record,
+        origin: Record,
+        destination: Record,
+        fields: BTreeMap<Arc<str>, Value>,
+        at: u64,
+    },
+    Purge {
+        id: Record,
+        at: u64,
+    },
+    /// The changes of a transaction, stored as a single entry so they are
+    /// replayed together or not at all
+    Commit(Vec<Log>),
+}
+
+impl Log {
+    /// Sets the time a change to a record was committed at
+    pub fn stamp(&mut self, time: u64) {
+        match self {
+            Log::Put { at, .. } | Log::Relate { at, .. } | Log::Purge { at, .. } => *at = time,
+            Log::Commit(logs) => logs.iter_mut().for_each(|log| log.stamp(time)),
+            _ => {}
+        }
+    }
+
+    /// The changes this log is made of, a commit is split into its changes
+    pub fn changes(self) -> Vec<Log> {
+        match self {
+            Log::Commit(logs) => logs.into_iter().flat_map(Log::changes).collect(),
+            log => vec![log],
+        }
+    }
+}
+
+/// A version of a record with the time it was committed at, a deleted
+/// record ends with a version without fields
+type Stamped = (u64, Option<BTreeMap<Arc<str>, Value>>);
+
+#[derive(Debug, Clone, PartialEq)]
+struct Stored {
+    /// The versions of the record oldest first
+    versions: Vec<Stamped>,
+    edge: Option<(Record, Record)>,
+}
+
+/// The compacted result of applying a sequence of logs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct State {
+    tables: BTreeSet<String>,
+    analyzers: BTreeMap<String, Analyzer>,
+    histories: BTreeMap<String, Duration>,
+    schemas: BTreeMap<String, bool>,
+    fields: BTreeMap<(String, Idiom), (Definition, String)>,
+    indexes: BTreeMap<(String, String), (Vec<Idiom>, Index)>,
+    records: BTreeMap<Record, Stored>,
+}
+
+impl State {
+    pub fn apply(&mut self, log: Log) {
+        match log {
+            Log::Define(table) => {
+                self.tables.insert(table);
+            }
+            Log::Analyzer(analyzer) => {
+                self.analyzers.insert(analyzer.name.clone(), analyzer);
+            }
+            Log::History { table, history } => {
+                self.tables.insert(table.clone());
+                self.histories.insert(table, history);
+            }
+            Log::Schema { table, schemafull } => {
+                self.tables.insert(table.clone());
+                self.schemas.insert(table, schemafull);
+            }
+            Log::Field {
+                table,
+                definition,
+                source,
+            } => {
+                self.tables.insert(table.clone());
+                let key = (table, definition.field.clone());
+                self.fields.insert(key, (definition, source));
+            }
+            Log::Index {
+                table,
+                name,
+                fields,
+                index,
+            } => {
+                self.tables.insert(table.clone());
+                self.indexes.insert((table, name), (fields, index));
+            }
+            Log::Put { id, fields, at } => {
+                self.tables.insert(id.table.to_string());
+                self.version(id, Some(fields), at, None);
+            }
+            Log::Relate {
+                id,
+                origin,
+                destination,
+                fields,
+                at,
+            } => {
+                self.tables.insert(id.table.to_string());
+                self.version(id, Some(fields), at, Some((origin, destination)));
+            }
+            Log::Purge { id, at } => {
+                if self.records.contains_key(&id) {
+                    self.version(id, None, at, None);
+                }
+            }
+            Log::Commit(logs) => {
+                for log in logs {
+                    self.apply(log);
+                }
+            }
+        }
+    }
+
+    /// Adds a version to a record, then drops the versions older than the
+    /// retention of its table. Only the latest version is kept when the
+    /// table has none, and a record deleted before the retention is dropped
+    fn version(
+        &mut self,
+        id: Record,
+        fields: Option<BTreeMap<Arc<str>, Value>>,
+        at: u64,
+        edge: Option<(Record, Record)>,
+    ) {
+        let history = self.histories.get(id.table.as_ref());
+        let cutoff = history.map_or(at, |history| at.saturating_sub(history.as_nanos() as u64));
+        let stored = self.records.entry(id.clone()).or_insert(Stored {
+            versions: Vec::new(),
+            edge,
+        });
+        // Transactions may store their changes in another order than they
+        // committed in
+        let position = stored.versions.partition_point(|(time, _)| *time <= at);
+        stored.versions.insert(position, (at, fields));
+        if let Some(current) = stored.versions.iter().rposition(|(at, _)| *at <= cutoff) {
+            stored.versions.drain(..current);
+        }
+        // Reads before the first version find nothing either way
+        while stored
+            .versions
+            .first()
+            .is_some_and(|(at, fields)| fields.is_none() && *at <= cutoff)
+        {
+            stored.versions.remove(0);
+        }
+        if stored.versions.is_empty() {
+            self.records.remove(&id);
+        }
+    }
+
+    /// The logs needed to rebuild this state, analyzers come before the
+    /// indexes using them, indexes before the records they cover and nodes
+    /// before the edges that connect them
+    pub fn logs(&self) -> Vec<Log> {
+        let tables = self.tables.iter().cloned().map(Log::Define);
+        let histories = self.histories.iter().map(|(table, history)| Log::History {
+            table: table.clone(),
+            history: *history,
+        });
+        let schemas = self.schemas.iter().map(|(table, schemafull)| Log::Schema {
+            table: table.clone(),
+            schemafull: *schemafull,
+        });
+        let fields = self
+            .fields
+            .iter()
+            .map(|((table, _), (definition, source))| Log::Field {
+                table: table.clone(),
+                definition: definition.clone(),
+                source: source.clone(),
+            });
+        let analyzers = self.analyzers.values().cloned().map(Log::Analyzer);
+        let indexes = self
+            .indexes
+            .iter()
+            .map(|((table, name), (fields, index))| Log::Index {
+                table: table.clone(),
+                name: name.clone(),
+                fields: fields.clone(),
+                index: index.clone(),
+            });
+        let versions = |id: &Record, stored: &Stored| {
+            let versions = stored.versions.iter().map(|(at, fields)| {
+                let (id, at) = (id.clone(), *at);
+                match (fields.clone(), stored.edge.clone()) {
+                    (None, _) => Log::Purge { id, at },
+                    (Some(fields), None) => Log::Put { id, fields, at },
+                    (Some(fields), Some((origin, destination))) => Log::Relate {
+                        id,
+                        origin,
+                        destination,
+                        fields,
+                        at,
+                    },
+                }
+            });
+            versions.collect::<Vec<_>>()
+        };
+        let nodes = self
+            .records
+            .iter()
+            .filter(|(_, stored)| stored.edge.is_none())
+            .flat_map(|(id, stored)| versions(id, stored));
+        let edges = self
+            .records
+            .iter()
+            .filter(|(_, stored)| {
+                stored.edge.as_ref().is_some_and(|(origin, destination)| {
+                    self.records.contains_key(origin) && self.records.contains_key(destination)
+                })
+            })
+            .flat_map(|(id, stored)| versions(id, stored));
+        tables
+            .chain(histories)
+            .chain(schemas)
+            .chain(fields)
+            .chain(analyzers)
+            .chain(indexes)
+            .chain(nodes)
+            .chain(edges)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn put(id: i64, age: i64, at: u64) -> Log {
+        Log::Put {
+            id: Record::new("person", id),
+            fields: BTreeMap::from([("age".into(), age.into())]),
+            at,
+        }
+    }
+
+    fn relate(origin: i64, at: u64) -> Log {
+        Log::Relate {
+            id: Record::new("knows", origin),
+            origin: Record::new("person", origin),
+            destination: Record::new("person", 2),
+            fields: BTreeMap::new(),
+            at,
+        }
+    }
+
+    fn purge(id: i64, at: u64) -> Log {
+        Log::Purge {
+            id: Record::new("person", id),
+            at,
+        }
+    }
+
+    #[test]
+    fn state_compaction_test() {
+        let mut state = State::default();
+        for log in [
+            relate(1, 1),
+            put(1, 10, 2),
+            put(2, 20, 3),
+            put(3, 30, 4),
+            put(1, 11, 5),
+            relate(3, 6),
+            purge(3, 7),
+        ] {
+            state.apply(log);
+        }
+        let logs = state.logs();
+        assert_eq!(
+            logs,
+            vec![
+                Log::Define(String::from("knows")),
+                Log::Define(String::from("person")),
+                put(1, 11, 5),
+                put(2, 20, 3),
+                relate(1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn state_history_test() {
+        let mut state = State::default();
+        let history = Log::History {
+            table: String::from("person"),
+            history: Duration::from_nanos(10),
+        };
+        for log in [
+            history.clone(),
+            put(1, 10, 100),
+            put(1, 11, 105),
+            put(1, 12, 120),
+            put(2, 20, 100),
+            purge(2, 125),
+            put(3, 30, 100),
+            purge(3, 101),
+            put(3, 31, 115),
+        ] {
+            state.apply(log);
+        }
+        // The versions within the retention are kept along with the one
+        // current when it starts, deletions included
+        assert_eq!(
+            state.logs(),
+            vec![
+                Log::Define(String::from("person")),
+                history,
+                put(1, 11, 105),
+                put(1, 12, 120),
+                put(2, 20, 100),
+                purge(2, 125),
+                put(3, 31, 115),
+            ]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{
+    err::Error,
+    kvs::{log::Log, store::Store},
+};
+use std::sync::{Arc, Mutex};
+
+/// Keeps the logs in memory, clones share the same logs so a store can be
+/// reopened after the actor holding it has gone
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    snapshot: Vec<Log>,
+    wal: Vec<Log>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Memory::default()
+    }
+
+    #[cfg(test)]
+    pub fn wal_len(&self) -> usize {
+        self.inner.lock().unwrap().wal.len()
+    }
+}
+
+impl Store for Memory {
+    fn append(&mut self, log: &Log) -> Result<(), Error> {
+        self.inner.lock().unwrap().wal.push(log.clone());
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn snapshot(&mut self, logs: &[Log]) -> Result<(), Error> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.snapshot = logs.to_vec();
+        inner.wal.clear();
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<Vec<Log>, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner
+            .snapshot
+            .iter()
+            .chain(inner.wal.iter())
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,6 @@
+pub(crate) mod codec;
+pub(crate) mod file;
+pub(crate) mod log;
+pub(crate) mod mem;
+pub(crate) mod storage;
+pub(crate) mod store;
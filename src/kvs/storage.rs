@@ -0,0 +1,533 @@
+use crate::{
+    dbs::{
+        graph::Graph,
+        ops::{
+            create::Create, define::Define, relate::Connect, restore::Restore, retrieve::Retrieve,
+        },
+        transaction::Txn,
+    },
+    err::Error,
+    kvs::{
+        log::{Log, State},
+        store::Store,
+    },
+    resp::Response,
+};
+use actix::{Actor, Addr, Context, Handler, Message, Supervised, SystemService};
+
+const SNAPSHOT_INTERVAL: usize = 1024;
+
+/// Writes every change to the graph to a store, the default storage has no
+/// store and discards what it is sent
+pub struct Storage {
+    store: Option<Box<dyn Store>>,
+    state: State,
+    pending: usize,
+    interval: usize,
+    replaying: bool,
+    /// The first write the store failed, no log is written after it
+    failure: Option<String>,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Storage {
+            store: None,
+            state: State::default(),
+            pending: 0,
+            interval: SNAPSHOT_INTERVAL,
+            replaying: false,
+            failure: None,
+        }
+    }
+}
+
+impl Actor for Storage {
+    type Context = Context<Self>;
+}
+
+impl Supervised for Storage {}
+
+impl SystemService for Storage {}
+
+impl Storage {
+    /// Loads the store and compacts it into a fresh snapshot
+    pub fn open<S: Store>(mut store: S) -> Result<Self, Error> {
+        let mut state = State::default();
+        for log in store.load()? {
+            state.apply(log);
+        }
+        store.snapshot(&state.logs())?;
+        Ok(Storage {
+            store: Some(Box::new(store)),
+            state,
+            ..Storage::default()
+        })
+    }
+
+    /// Sets how many logs are appended before the store is snapshotted
+    pub fn with_interval(mut self, interval: usize) -> Self {
+        self.interval = interval.max(1);
+        self
+    }
+
+    /// The logs that rebuild the stored graph
+    pub fn logs(&self) -> Vec<Log> {
+        self.state.logs()
+    }
+}
+
+impl Handler<Log> for Storage {
+    type Result = ();
+
+    fn handle(&mut self, log: Log, _ctx: &mut Self::Context) -> Self::Result {
+        let Some(store) = self.store.as_mut().filter(|_| !self.replaying) else {
+            return;
+        };
+        if self.failure.is_some() {
+            return;
+        }
+        if let Err(e) = store.append(&log) {
+            self.failure = Some(format!("appending to the write-ahead log failed: {e}"));
+            return;
+        }
+        self.state.apply(log);
+        self.pending += 1;
+        if self.pending >= self.interval {
+            self.pending = 0;
+            if let Err(e) = store.snapshot(&self.state.logs()) {
+                self.failure = Some(format!("snapshotting the store failed: {e}"));
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Result<(), Error>")]
+pub struct Flush;
+
+impl Handler<Flush> for Storage {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, _msg: Flush, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(failure) = &self.failure {
+            return Err(Error::Storage(failure.clone()));
+        }
+        match self.store.as_mut() {
+            Some(store) => store.sync(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Fails once the store failed to write a log
+#[derive(Message)]
+#[rtype(result = "Result<(), Error>")]
+pub struct Check;
+
+impl Handler<Check> for Storage {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, _msg: Check, _ctx: &mut Self::Context) -> Self::Result {
+        match &self.failure {
+            Some(failure) => Err(Error::Storage(failure.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// While replaying, the changes made by rebuilding the graph are already
+/// stored and are discarded
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Replay(pub bool);
+
+impl Handler<Replay> for Storage {
+    type Result = ();
+
+    fn handle(&mut self, Replay(replaying): Replay, _ctx: &mut Self::Context) -> Self::Result {
+        self.replaying = replaying;
+    }
+}
+
+/// Sends a change to the storage registered with the running system
+pub fn persist(log: Log) {
+    Storage::from_registry().do_send(log);
+}
+
+/// Waits until every change persisted so far is durable, failing once the
+/// store failed to write any of them
+pub async fn flush() -> Result<(), Error> {
+    Storage::from_registry().send(Flush).await.unwrap()
+}
+
+/// Fails once the store failed to write any change, so no change is made
+/// that could not be stored
+pub async fn check() -> Result<(), Error> {
+    Storage::from_registry().send(Check).await.unwrap()
+}
+
+/// Rebuilds the tables, entities and edges described by the logs
+pub async fn restore(graph: &Addr<Graph>, logs: Vec<Log>) -> Result<(), Error> {
+    let storage = Storage::from_registry();
+    storage.send(Replay(true)).await.unwrap();
+    let result = replay(graph, logs).await;
+    storage.send(Replay(false)).await.unwrap();
+    result
+}
+
+/// Applies the logs to the graph, the changes they make are persisted again
+pub async fn replay(graph: &Addr<Graph>, logs: Vec<Log>) -> Result<(), Error> {
+    for log in logs.into_iter().flat_map(Log::changes) {
+        match log {
+            Log::Define(table) => {
+                graph.send(Define::Table(table)).await.unwrap()?;
+            }
+            Log::Analyzer(analyzer) => {
+                graph.send(Define::Analyzer(analyzer)).await.unwrap()?;
+            }
+            Log::History { table, history } => {
+                graph
+                    .send(Define::History { table, history })
+                    .await
+                    .unwrap()?;
+            }
+            Log::Schema { table, schemafull } => {
+                graph
+                    .send(Define::Schema { table, schemafull })
+                    .await
+                    .unwrap()?;
+            }
+            Log::Field {
+                table,
+                definition,
+                source,
+            } => {
+                let define = Define::Field {
+                    table,
+                    definition,
+                    source,
+                };
+                graph.send(define).await.unwrap()?;
+            }
+            Log::Index {
+                table,
+                name,
+                fields,
+                index,
+            } => {
+                let define = Define::Index {
+                    name,
+                    table,
+                    fields,
+                    index,
+                };
+                graph.send(define).await.unwrap()?;
+            }
+            Log::Put { id, fields, at } => {
+                let record = graph.send(Retrieve::Record(id.clone())).await.unwrap();
+                if !matches!(record, Response::Node(_)) {
+                    let create = Create::new(id.clone(), fields.clone().into_iter().collect());
+                    graph.send(create).await.unwrap()?;
+                }
+                let fields = Some(fields);
+                graph.send(Restore { id, fields, at }).await.unwrap();
+            }
+            Log::Relate {
+                id,
+                origin,
+                destination,
+                fields,
+                at,
+            } => {
+                let edge = graph.send(Retrieve::Record(id.clone())).await.unwrap();
+                if matches!(edge, Response::Node(_)) {
+                    let fields = Some(fields);
+                    graph.send(Restore { id, fields, at }).await.unwrap();
+                    continue;
+                }
+                let org = graph.send(Retrieve::Record(origin.clone())).await.unwrap();
+                let dest = graph
+                    .send(Retrieve::Record(destination.clone()))
+                    .await
+                    .unwrap();
+                let (Response::Node(org), Response::Node(dest)) = (org, dest) else {
+                    continue;
+                };
+                let connect = Connect {
+                    id: id.clone(),
+                    fields: fields.clone().into_iter().collect(),
+                    org_id: origin,
+                    origin: org,
+                    dest_id: destination,
+                    destination: dest,
+                    txn: Txn::default(),
+                };
+                graph.send(connect).await.unwrap()?;
+                let fields = Some(fields);
+                graph.send(Restore { id, fields, at }).await.unwrap();
+            }
+            Log::Purge { id, at } => {
+                let fields = None;
+                graph.send(Restore { id, fields, at }).await.unwrap();
+            }
+            Log::Commit(_) => unreachable!(),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        dbs::{
+            executor::Executor,
+            ops::{get::Visible, history::History},
+            version::Version,
+        },
+        kvs::mem::Memory,
+        par,
+        ql::record::Record,
+    };
+    use actix::{System, SystemRegistry};
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    /// The people, the edges between them, an empty table and the names a
+    /// walk reaches
+    async fn select(graph: Addr<Graph>) -> [Result<serde_json::Value, Error>; 4] {
+        let executor = Executor::new(graph);
+        [
+            executor.run("SELECT * FROM person ORDER BY id").await,
+            executor.run("SELECT * FROM knows").await,
+            executor.run("SELECT * FROM empty").await,
+            executor
+                .run("SELECT person:1->knows->person.name AS names FROM person:1")
+                .await,
+        ]
+    }
+
+    #[actix_rt::test]
+    async fn restore_test() {
+        let memory = Memory::new();
+        let storage = Storage::open(memory.clone()).unwrap().with_interval(4);
+        SystemRegistry::set(storage.start());
+
+        let graph = Graph::new().start();
+        graph
+            .send(Define::Table("empty".into()))
+            .await
+            .unwrap()
+            .unwrap();
+        Executor::new(graph.clone())
+            .setup(
+                "CREATE person:1 SET name = 'a', tags = ['x'];
+                CREATE person:2 SET name = 'b';
+                CREATE person:3 SET name = 'c';
+                UPDATE person:1 SET age = 1.5;
+                RELATE person:1->knows:1->person:2 SET since = 2020;
+                RELATE person:3->knows:2->person:2;
+                DELETE person:3",
+            )
+            .await;
+        let before = select(graph).await;
+        assert!(memory.wal_len() < 4);
+
+        // Restart in a new system with its own registry
+        let after = std::thread::spawn(move || {
+            System::new().block_on(async move {
+                let storage = Storage::open(memory.clone()).unwrap();
+                let logs = storage.logs();
+                SystemRegistry::set(storage.start());
+                let graph = Graph::new().start();
+                restore(&graph, logs).await.unwrap();
+                let wal = memory.wal_len();
+                (select(graph).await, wal)
+            })
+        })
+        .join()
+        .unwrap();
+        let (after, wal) = after;
+        assert_eq!(wal, 0);
+        assert_eq!(before, after);
+        let [_, knows, empty, names] = after;
+        assert_eq!(knows.unwrap()[0]["in"], "person:1");
+        assert_eq!(empty, Ok(serde_json::json!([])));
+        assert_eq!(names.unwrap()[0]["names"], serde_json::json!(["b"]));
+    }
+
+    async fn history(graph: &Addr<Graph>, id: i64) -> Vec<Version> {
+        let id = Record::new("person", id);
+        match graph.send(Retrieve::Record(id)).await.unwrap() {
+            Response::Node(node) => node.send(History).await.unwrap(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn history_test() {
+        let memory = Memory::new();
+        SystemRegistry::set(Storage::open(memory.clone()).unwrap().start());
+
+        let graph = Graph::new().start();
+        Executor::new(graph.clone())
+            .setup(
+                "DEFINE TABLE person HISTORY 1h;
+                CREATE person:1 SET age = 1; UPDATE person:1 SET age = 2; DELETE person:1;
+                CREATE person:2 SET age = 1; UPDATE person:2 SET age = 2",
+            )
+            .await;
+        let before = (history(&graph, 1).await, history(&graph, 2).await);
+        assert_eq!(before.0.len(), 3);
+        assert_eq!(before.0[2].fields, None);
+        assert_eq!(before.1.len(), 2);
+
+        let after = std::thread::spawn(move || {
+            System::new().block_on(async move {
+                let storage = Storage::open(memory).unwrap();
+                let logs = storage.logs();
+                SystemRegistry::set(storage.start());
+                let graph = Graph::new().start();
+                restore(&graph, logs).await.unwrap();
+                (history(&graph, 1).await, history(&graph, 2).await)
+            })
+        })
+        .join()
+        .unwrap();
+        // Versions come back at the times they were written, deletes included
+        assert_eq!(before, after);
+    }
+
+    /// Fails every write once `fail` is set
+    struct Failing {
+        memory: Memory,
+        fail: Arc<AtomicBool>,
+    }
+
+    impl Store for Failing {
+        fn append(&mut self, log: &Log) -> Result<(), Error> {
+            match self.fail.load(Ordering::SeqCst) {
+                true => Err(Error::Storage(String::from("disk full"))),
+                false => self.memory.append(log),
+            }
+        }
+
+        fn sync(&mut self) -> Result<(), Error> {
+            self.memory.sync()
+        }
+
+        fn snapshot(&mut self, logs: &[Log]) -> Result<(), Error> {
+            match self.fail.load(Ordering::SeqCst) {
+                true => Err(Error::Storage(String::from("disk full"))),
+                false => self.memory.snapshot(logs),
+            }
+        }
+
+        fn load(&mut self) -> Result<Vec<Log>, Error> {
+            self.memory.load()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn failure_test() {
+        let memory = Memory::new();
+        let fail = Arc::new(AtomicBool::new(false));
+        let store = Failing {
+            memory: memory.clone(),
+            fail: fail.clone(),
+        };
+        SystemRegistry::set(Storage::open(store).unwrap().start());
+
+        let graph = Graph::new().start();
+        let run = |query: &str| {
+            let queries = par::parse(query).unwrap();
+            let graph = graph.clone();
+            async move {
+                let responses = Executor::new(graph).execute(queries).await;
+                serde_json::to_value(&responses).unwrap()[0].clone()
+            }
+        };
+        assert_eq!(run("CREATE person:1").await["status"], "OK");
+        fail.store(true, Ordering::SeqCst);
+        let failed = run("CREATE person:2").await;
+        assert_eq!(failed["status"], "ERR");
+        assert_eq!(
+            failed["result"],
+            "Storage error: appending to the write-ahead log failed: Storage error: disk full"
+        );
+        // Writes are refused once the store failed, even when it recovers,
+        // before they change anything
+        fail.store(false, Ordering::SeqCst);
+        assert_eq!(run("CREATE person:3").await["status"], "ERR");
+        let id = Record::new("person", 3);
+        let record = graph.send(Retrieve::Record(id)).await.unwrap();
+        assert_eq!(record, Response::None);
+        assert_eq!(
+            flush().await,
+            Err(Error::Storage(String::from(
+                "appending to the write-ahead log failed: Storage error: disk full"
+            )))
+        );
+        let ids = Storage::open(memory)
+            .unwrap()
+            .logs()
+            .into_iter()
+            .filter_map(|log| match log {
+                Log::Put { id, .. } => Some(id.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["person:1"]);
+    }
+
+    #[actix_rt::test]
+    async fn commit_failure_test() {
+        let fail = Arc::new(AtomicBool::new(false));
+        let store = Failing {
+            memory: Memory::new(),
+            fail: fail.clone(),
+        };
+        SystemRegistry::set(Storage::open(store).unwrap().start());
+        fail.store(true, Ordering::SeqCst);
+
+        let graph = Graph::new().start();
+        let queries = par::parse("BEGIN; CREATE person:1; COMMIT").unwrap();
+        let responses = Executor::new(graph.clone()).execute(queries).await;
+        let responses = serde_json::to_value(&responses).unwrap();
+        assert_eq!(responses[1]["status"], "ERR");
+        // A commit that is not durable is never seen by others
+        let id = Record::new("person", 1);
+        let Response::Node(node) = graph.send(Retrieve::Record(id)).await.unwrap() else {
+            panic!("the record was dropped before its transaction ended");
+        };
+        assert_eq!(node.send(Visible(Txn::now())).await.unwrap(), None);
+    }
+
+    #[actix_rt::test]
+    async fn transaction_test() {
+        let memory = Memory::new();
+        SystemRegistry::set(Storage::open(memory.clone()).unwrap().start());
+
+        let graph = Graph::new().start();
+        let queries = par::parse(
+            "BEGIN; CREATE person:1; UPDATE person:1 SET name = 'a'; CANCEL;
+            BEGIN; CREATE person:2; UPDATE person:2 SET name = 'b'; COMMIT;
+            CREATE person:3",
+        )
+        .unwrap();
+        Executor::new(graph).execute(queries).await;
+        let ids = Storage::open(memory)
+            .unwrap()
+            .logs()
+            .into_iter()
+            .filter_map(|log| match log {
+                Log::Put { id, .. } => Some(id.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["person:2", "person:3"]);
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{err::Error, kvs::log::Log};
+
+/// A backend that the storage actor writes its logs to
+pub trait Store: Send + 'static {
+    /// Appends a log to the write-ahead log
+    fn append(&mut self, log: &Log) -> Result<(), Error>;
+
+    /// Makes every appended log durable
+    fn sync(&mut self) -> Result<(), Error>;
+
+    /// Replaces the snapshot with the given logs and clears the write-ahead log
+    fn snapshot(&mut self, logs: &[Log]) -> Result<(), Error>;
+
+    /// Reads the snapshot followed by the write-ahead log
+    fn load(&mut self) -> Result<Vec<Log>, Error>;
+}
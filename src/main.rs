@@ -1,10 +1,13 @@
-use actix::{Actor, Addr};
+use actix::{Actor, Addr, SystemRegistry};
 use actix_web::{get, web::Data, App, HttpResponse, HttpServer, Responder};
 use dbs::graph::Graph;
+use kvs::{file::File, mem::Memory, storage::Storage};
 pub mod ctx;
 pub mod dbs;
 pub mod doc;
 pub mod err;
+pub mod idx;
+pub mod kvs;
 pub mod par;
 pub mod ql;
 pub mod resp;
@@ -16,16 +19,27 @@ struct AppState {
 async fn main() -> std::io::Result<()> {
     let address = "0.0.0.0";
     let port = 8080;
-    HttpServer::new(move || {
-        App::new()
-            .app_data(Data::new(AppState {
-                graph: Graph::new().start(),
-            }))
-            .service(server::query)
-    })
-    .bind((address, port))?
-    .run()
-    .await
+    let path = std::env::var("CATALYST_DATA").unwrap_or_else(|_| String::from("data"));
+    let storage = match path.as_str() {
+        "memory" => Storage::open(Memory::new()),
+        path => File::open(path).and_then(Storage::open),
+    }
+    .map_err(std::io::Error::other)?;
+    let storage = match std::env::var("CATALYST_SNAPSHOT_INTERVAL") {
+        Ok(interval) => storage.with_interval(interval.parse().map_err(std::io::Error::other)?),
+        Err(_) => storage,
+    };
+    let logs = storage.logs();
+    SystemRegistry::set(storage.start());
+    let graph = Graph::new().start();
+    kvs::storage::restore(&graph, logs)
+        .await
+        .map_err(std::io::Error::other)?;
+    let state = Data::new(AppState { graph });
+    HttpServer::new(move || App::new().app_data(state.clone()).service(server::query))
+        .bind((address, port))?
+        .run()
+        .await
 }
 
 #[get("/")]
@@ -0,0 +1,376 @@
+use crate::{
+    err::Error,
+    par::token::{Span, Token, TokenKind},
+    ql::distance::Distance,
+};
+use std::{iter::Peekable, str::CharIndices};
+
+pub struct Lexer<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Lexer {
+            input,
+            chars: input.char_indices().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub fn tokenize(mut self) -> Result<Vec<Token>, Error> {
+        let mut tokens = Vec::new();
+        loop {
+            let token = self.next_token()?;
+            let eof = token.kind == TokenKind::Eof;
+            tokens.push(token);
+            if eof {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        let mut chars = self.chars.clone();
+        chars.next();
+        chars.next().map(|(_, c)| c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.bump();
+            return true;
+        }
+        false
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.input.len(), |(i, _)| *i)
+    }
+
+    fn error(&self, line: usize, column: usize, message: String) -> Error {
+        Error::ParseError {
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), Error> {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') => self.skip_line(),
+                Some('-') if self.peek_second() == Some('-') => self.skip_line(),
+                Some('/') if self.peek_second() == Some('/') => self.skip_line(),
+                Some('/') if self.peek_second() == Some('*') => {
+                    let (line, column) = (self.line, self.column);
+                    self.bump();
+                    self.bump();
+                    loop {
+                        match self.bump() {
+                            Some('*') if self.eat('/') => break,
+                            Some(_) => (),
+                            None => {
+                                return Err(self.error(
+                                    line,
+                                    column,
+                                    String::from("Unterminated block comment"),
+                                ))
+                            }
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Token, Error> {
+        self.skip_whitespace()?;
+        let offset = self.offset();
+        let (line, column) = (self.line, self.column);
+
+        let Some(c) = self.bump() else {
+            return Ok(Token {
+                kind: TokenKind::Eof,
+                span: Span {
+                    offset,
+                    len: 0,
+                    line,
+                    column,
+                },
+            });
+        };
+
+        let kind = match c {
+            '(' => TokenKind::OpenParen,
+            ')' => TokenKind::CloseParen,
+            '[' => TokenKind::OpenBracket,
+            ']' => TokenKind::CloseBracket,
+            '{' => TokenKind::OpenBrace,
+            '}' => TokenKind::CloseBrace,
+            ',' => TokenKind::Comma,
+            ';' => TokenKind::SemiColon,
+            '.' => TokenKind::Dot,
+            ':' => TokenKind::Colon,
+            '$' => TokenKind::Dollar,
+            '?' => TokenKind::Question,
+            '*' => TokenKind::Star,
+            '+' if self.eat('=') => TokenKind::PlusEq,
+            '+' => TokenKind::Plus,
+            '/' => TokenKind::Slash,
+            '-' if self.eat('>') => TokenKind::ArrowIn,
+            '-' if self.eat('=') => TokenKind::MinusEq,
+            '-' => TokenKind::Minus,
+            '=' if self.eat('=') => TokenKind::EqEq,
+            '=' => TokenKind::Eq,
+            '!' if self.eat('=') => TokenKind::NotEq,
+            '!' => TokenKind::Bang,
+            '>' if self.eat('=') => TokenKind::GtEq,
+            '>' => TokenKind::Gt,
+            '<' if self.eat('=') => TokenKind::LtEq,
+            '<' if self.eat('|') => self.lex_knn(line, column)?,
+            '<' if self.peek() == Some('-') => match self.peek_second() {
+                Some('>') => {
+                    self.bump();
+                    self.bump();
+                    TokenKind::ArrowBoth
+                }
+                Some(c) if c.is_ascii_digit() || c.is_whitespace() => TokenKind::Lt,
+                _ => {
+                    self.bump();
+                    TokenKind::ArrowOut
+                }
+            },
+            '<' => TokenKind::Lt,
+            '&' if self.eat('&') => TokenKind::And,
+            '|' if self.eat('|') => TokenKind::Or,
+            '@' => self.lex_matches(line, column)?,
+            '\'' | '"' => TokenKind::Strand(self.lex_strand(c, line, column)?),
+            '`' => TokenKind::Ident(self.lex_quoted_ident(line, column)?),
+            c if c.is_ascii_digit() => self.lex_number(offset, line, column)?,
+            c if c.is_alphabetic() || c == '_' => {
+                while matches!(self.peek(), Some(c) if c.is_alphanumeric() || c == '_') {
+                    self.bump();
+                }
+                TokenKind::Ident(self.input[offset..self.offset()].to_string())
+            }
+            c => return Err(self.error(line, column, format!("Unexpected character '{c}'"))),
+        };
+
+        Ok(Token {
+            kind,
+            span: Span {
+                offset,
+                len: self.offset() - offset,
+                line,
+                column,
+            },
+        })
+    }
+
+    fn lex_matches(&mut self, line: usize, column: usize) -> Result<TokenKind, Error> {
+        if self.eat('@') {
+            return Ok(TokenKind::Matches(None));
+        }
+        let offset = self.offset();
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.bump();
+        }
+        let reference = self.input[offset..self.offset()].parse().ok();
+        match reference {
+            Some(r) if self.eat('@') => Ok(TokenKind::Matches(Some(r))),
+            _ => Err(self.error(line, column, String::from("Invalid matches operator"))),
+        }
+    }
+
+    fn lex_knn(&mut self, line: usize, column: usize) -> Result<TokenKind, Error> {
+        let offset = self.offset();
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.bump();
+        }
+        let k = self.input[offset..self.offset()].parse().ok();
+        let dist = match self.eat(',') {
+            true => {
+                let offset = self.offset();
+                while matches!(self.peek(), Some(c) if c.is_alphabetic()) {
+                    self.bump();
+                }
+                Distance::from_name(&self.input[offset..self.offset()]).map(Some)
+            }
+            false => Some(None),
+        };
+        match (k, dist) {
+            (Some(k), Some(dist)) if self.eat('|') && self.eat('>') => Ok(TokenKind::Knn(k, dist)),
+            _ => Err(self.error(line, column, String::from("Invalid KNN operator"))),
+        }
+    }
+
+    fn lex_strand(&mut self, quote: char, line: usize, column: usize) -> Result<String, Error> {
+        let mut strand = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => return Ok(strand),
+                Some('\\') => match self.bump() {
+                    Some('n') => strand.push('\n'),
+                    Some('t') => strand.push('\t'),
+                    Some('r') => strand.push('\r'),
+                    Some('0') => strand.push('\0'),
+                    Some(c @ ('\\' | '\'' | '"' | '`')) => strand.push(c),
+                    Some(c) => {
+                        return Err(self.error(
+                            self.line,
+                            self.column - 1,
+                            format!("Invalid escape character '{c}'"),
+                        ))
+                    }
+                    None => break,
+                },
+                Some(c) => strand.push(c),
+                None => break,
+            }
+        }
+        Err(self.error(line, column, String::from("Unterminated string")))
+    }
+
+    fn lex_quoted_ident(&mut self, line: usize, column: usize) -> Result<String, Error> {
+        let mut ident = String::new();
+        loop {
+            match self.bump() {
+                Some('`') => return Ok(ident),
+                Some(c) => ident.push(c),
+                None => {
+                    return Err(self.error(line, column, String::from("Unterminated identifier")))
+                }
+            }
+        }
+    }
+
+    fn lex_number(
+        &mut self,
+        offset: usize,
+        line: usize,
+        column: usize,
+    ) -> Result<TokenKind, Error> {
+        let mut float = false;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            self.bump();
+        }
+        if self.peek() == Some('.') && matches!(self.peek_second(), Some(c) if c.is_ascii_digit()) {
+            float = true;
+            self.bump();
+            while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+                self.bump();
+            }
+        }
+        if matches!(self.peek(), Some('e' | 'E'))
+            && matches!(self.peek_second(), Some(c) if c.is_ascii_digit() || c == '-' || c == '+')
+        {
+            float = true;
+            self.bump();
+            if matches!(self.peek(), Some('-' | '+')) {
+                self.bump();
+            }
+            while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+                self.bump();
+            }
+        }
+
+        let text = &self.input[offset..self.offset()];
+        if float {
+            return text
+                .parse()
+                .map(TokenKind::Float)
+                .map_err(|_| self.error(line, column, format!("Invalid number '{text}'")));
+        }
+        text.parse()
+            .map(TokenKind::Int)
+            .map_err(|_| self.error(line, column, format!("Invalid number '{text}'")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<TokenKind> {
+        Lexer::new(input)
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    #[test]
+    fn lex_arrows_test() {
+        assert_eq!(
+            kinds("a->b<-c<->d < -1"),
+            vec![
+                TokenKind::Ident("a".into()),
+                TokenKind::ArrowIn,
+                TokenKind::Ident("b".into()),
+                TokenKind::ArrowOut,
+                TokenKind::Ident("c".into()),
+                TokenKind::ArrowBoth,
+                TokenKind::Ident("d".into()),
+                TokenKind::Lt,
+                TokenKind::Minus,
+                TokenKind::Int(1),
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_position_test() {
+        let tokens = Lexer::new("SELECT *\n  FROM a").tokenize().unwrap();
+        let from = &tokens[2];
+        assert!(from.is_keyword("from"));
+        assert_eq!((from.span.line, from.span.column), (2, 3));
+    }
+
+    #[test]
+    fn lex_error_test() {
+        let err = Lexer::new("SELECT 'abc").tokenize().unwrap_err();
+        assert_eq!(
+            err,
+            Error::ParseError {
+                line: 1,
+                column: 8,
+                message: String::from("Unterminated string"),
+            }
+        );
+    }
+}
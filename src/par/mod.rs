@@ -1 +1,675 @@
+use crate::{err::Error, ql::query::Queries};
+use parser::Parser;
 
+pub(crate) mod lexer;
+pub(crate) mod parser;
+pub(crate) mod stmt;
+pub(crate) mod token;
+pub(crate) mod value;
+
+pub fn parse(input: &str) -> Result<Queries, Error> {
+    Parser::new(input)?.parse_query()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ql::{
+        condition::Condition,
+        data::Data,
+        datetime::Datetime,
+        direction::Direction,
+        distance::Distance,
+        edge::Edge,
+        expression::Expression,
+        fields::{Field, Fields},
+        filter::{Filter, Language},
+        function::Function,
+        group::Groups,
+        ident::Ident,
+        idiom::Idiom,
+        index::Index,
+        kind::Kind,
+        number::Number,
+        operator::Operator,
+        order::{Order, Orders},
+        output::Output,
+        part::Part,
+        path::Path,
+        query::Query,
+        record::Record,
+        recurse::{Collect, Recurse},
+        statements::{
+            create::Create, define::Define, delete::Delete, relate::Relate, select::Select,
+            update::Update,
+        },
+        table::Table,
+        tokenizer::Tokenizer,
+        value::{Value, Values},
+    };
+    use std::{collections::BTreeMap, time::Duration};
+
+    fn value(input: &str) -> Value {
+        let query = parse(&format!("SELECT {input} FROM a")).unwrap();
+        let Some(Query::Select(Select { fields, .. })) = query.0.into_iter().next() else {
+            panic!("select was not parsed");
+        };
+        let Some(Field::Single { expr, .. }) = fields.0.into_iter().next() else {
+            panic!("field was not parsed");
+        };
+        expr
+    }
+
+    #[test]
+    fn parse_select_test() {
+        let query =
+            parse("SELECT name, age AS years FROM person WHERE age > 30 LIMIT 5 START 1").unwrap();
+        let select = Select {
+            fields: Fields(vec![
+                Field::new(Ident::new("name").into()),
+                Field::new_alias(Ident::new("age").into(), String::from("years")),
+            ]),
+            what: Values(vec![Table(String::from("person")).into()]),
+            conditions: Some(Condition(
+                Expression::Binary {
+                    left: Ident::new("age").into(),
+                    op: Operator::Gt,
+                    right: 30.into(),
+                }
+                .into(),
+            )),
+            group: None,
+            order: None,
+            limit: Some(5),
+            start: Some(1),
+            version: None,
+        };
+        assert_eq!(query.0, vec![Query::Select(select)]);
+    }
+
+    #[test]
+    fn parse_multiple_statements_test() {
+        let query = parse("SELECT * FROM a; SELECT * FROM person:1, b;").unwrap();
+        assert_eq!(query.len(), 2);
+        let Query::Select(select) = &query[1] else {
+            panic!("select was not parsed");
+        };
+        assert_eq!(
+            select.what,
+            Values(vec![
+                Record::new("person", 1).into(),
+                Table(String::from("b")).into()
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_precedence_test() {
+        let expected: Value = Expression::Binary {
+            left: Expression::Binary {
+                left: 1.into(),
+                op: Operator::Add,
+                right: Expression::Binary {
+                    left: 2.into(),
+                    op: Operator::Mult,
+                    right: 3.into(),
+                }
+                .into(),
+            }
+            .into(),
+            op: Operator::Eq,
+            right: (-7).into(),
+        }
+        .into();
+        assert_eq!(value("1 + 2 * 3 = -7"), expected);
+    }
+
+    #[test]
+    fn parse_idiom_test() {
+        let expected = Value::Idiom(Idiom(vec![
+            Part::Field(Ident::new("a")),
+            Part::Field(Ident::new("b")),
+            Part::Index(0.into()),
+            Part::All,
+            Part::Last,
+            Part::Where(
+                Expression::Binary {
+                    left: Ident::new("c").into(),
+                    op: Operator::Eq,
+                    right: true.into(),
+                }
+                .into(),
+            ),
+        ]));
+        assert_eq!(value("a.b[0][*][$][WHERE c = true]"), expected);
+
+        let expected = Value::Idiom(Idiom(vec![
+            Part::Field(Ident::new("a")),
+            Part::Flatten,
+            Part::Index(0.into()),
+        ]));
+        assert_eq!(value("a...[0]"), expected);
+    }
+
+    #[test]
+    fn parse_edge_test() {
+        let expected = Value::Edge(Box::new(Edge {
+            dir: Direction::In,
+            from: Record::new("person", 1),
+            to: Table(String::from("knows")),
+        }));
+        assert_eq!(value("person:1->knows"), expected);
+
+        let expected = Value::Idiom(Idiom(vec![
+            Part::Start(Record::new("person", "tobie").into()),
+            Part::Path(Path::new(
+                Direction::Out,
+                Table(String::from("knows")),
+                Some(Condition(
+                    Expression::Binary {
+                        left: Ident::new("since").into(),
+                        op: Operator::GtEq,
+                        right: 2020.into(),
+                    }
+                    .into(),
+                )),
+            )),
+            Part::Path(Path::new(
+                Direction::Both,
+                Table(String::from("person")),
+                None,
+            )),
+            Part::Field(Ident::new("name")),
+        ]));
+        assert_eq!(
+            value("person:tobie<-(knows WHERE since >= 2020)<->person.name"),
+            expected
+        );
+        assert_eq!(
+            value("person:tobie<-knows(WHERE since >= 2020)<->person.name"),
+            expected
+        );
+    }
+
+    #[test]
+    fn parse_recurse_test() {
+        let knows = |recurse| {
+            Value::Idiom(Idiom(vec![
+                Part::Start(Record::new("person", 1).into()),
+                Part::Recurse(recurse),
+                Part::Path(Path::new(Direction::In, Table(String::from("knows")), None)),
+                Part::Path(Path::new(
+                    Direction::In,
+                    Table(String::from("person")),
+                    None,
+                )),
+            ]))
+        };
+        let recurse = |min, max, collect| Recurse { min, max, collect };
+        assert_eq!(
+            value("person:1{1..4}->knows->person"),
+            knows(recurse(1, Some(4), Collect::Nodes))
+        );
+        assert_eq!(
+            value("person:1{..}->knows->person"),
+            knows(recurse(1, None, Collect::Nodes))
+        );
+        assert_eq!(
+            value("person:1{2}->knows->person"),
+            knows(recurse(2, Some(2), Collect::Nodes))
+        );
+        assert_eq!(
+            value("person:1{0..+path}->knows->person"),
+            knows(recurse(0, None, Collect::Paths))
+        );
+        assert_eq!(
+            value("person:1{..3+collect}->knows->person").to_string(),
+            "person:1{1..3}->knows->person"
+        );
+
+        assert!(parse("SELECT person:1{3..2}->knows->person FROM a").is_err());
+        assert!(parse("SELECT person:1{0}->knows->person FROM a").is_err());
+        assert!(parse("SELECT person:1{1..2}.name FROM a").is_err());
+        assert!(parse("SELECT person:1{1..2+all}->knows->person FROM a").is_err());
+    }
+
+    #[test]
+    fn parse_literals_test() {
+        let Value::Object(object) = value("{ a: [1, 2.5, 'x'], \"b\": NULL, c: NONE, d: false }")
+        else {
+            panic!("object was not parsed");
+        };
+        assert_eq!(
+            object.get(&"a".into()),
+            &Value::from(vec![1.into(), Value::Number(2.5.into()), "x".into()])
+        );
+        assert_eq!(object.get(&"b".into()), &Value::Null);
+        assert_eq!(object.get(&"d".into()), &Value::Bool(false));
+    }
+
+    #[test]
+    fn parse_create_test() {
+        let query =
+            parse("CREATE person:1 SET name = 'x', meta.age = 3; CREATE person CONTENT { a: 1 }")
+                .unwrap();
+        let set = Data::Set(vec![
+            (
+                Idiom(vec![Part::Field(Ident::new("name"))]),
+                Operator::Eq,
+                "x".into(),
+            ),
+            (
+                Idiom(vec![
+                    Part::Field(Ident::new("meta")),
+                    Part::Field(Ident::new("age")),
+                ]),
+                Operator::Eq,
+                3.into(),
+            ),
+        ]);
+        let content = Data::Content(Value::from(BTreeMap::from([("a".to_string(), 1.into())])));
+        assert_eq!(
+            query.0,
+            vec![
+                Query::Create(Create {
+                    what: Values(vec![Record::new("person", 1).into()]),
+                    data: Some(set),
+                }),
+                Query::Create(Create {
+                    what: Values(vec![Table(String::from("person")).into()]),
+                    data: Some(content),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_update_test() {
+        let query =
+            parse("UPDATE person SET age += 1, tags -= 'a' WHERE age > 30 RETURN DIFF").unwrap();
+        let update = Update {
+            what: Values(vec![Table(String::from("person")).into()]),
+            data: Some(Data::Set(vec![
+                (
+                    Idiom(vec![Part::Field(Ident::new("age"))]),
+                    Operator::Add,
+                    1.into(),
+                ),
+                (
+                    Idiom(vec![Part::Field(Ident::new("tags"))]),
+                    Operator::Sub,
+                    "a".into(),
+                ),
+            ])),
+            conditions: Some(Condition(
+                Expression::Binary {
+                    left: Ident::new("age").into(),
+                    op: Operator::Gt,
+                    right: 30.into(),
+                }
+                .into(),
+            )),
+            output: Some(Output::Diff),
+        };
+        assert_eq!(query.0, vec![Query::Update(update)]);
+        let query = parse("UPDATE person:1 PATCH [{ op: 'remove', path: '/a' }]").unwrap();
+        let Query::Update(Update { data, .. }) = &query[0] else {
+            panic!("update was not parsed");
+        };
+        assert!(matches!(data, Some(Data::Patch(Value::Array(_)))));
+    }
+
+    #[test]
+    fn parse_delete_test() {
+        let query = parse("DELETE FROM person:1, person WHERE age > 30 RETURN BEFORE").unwrap();
+        let delete = Delete {
+            what: Values(vec![
+                Record::new("person", 1).into(),
+                Table(String::from("person")).into(),
+            ]),
+            conditions: Some(Condition(
+                Expression::Binary {
+                    left: Ident::new("age").into(),
+                    op: Operator::Gt,
+                    right: 30.into(),
+                }
+                .into(),
+            )),
+            output: Some(Output::Before),
+        };
+        assert_eq!(query.0, vec![Query::Delete(delete)]);
+    }
+
+    #[test]
+    fn parse_relate_test() {
+        let query =
+            parse("RELATE person:1->knows->[person:2, person:3] SET since = 2020; RELATE person:2<-knows:1<-person:1")
+                .unwrap();
+        let relate = Relate {
+            from: Record::new("person", 1).into(),
+            kind: Table(String::from("knows")).into(),
+            with: Value::from(vec![
+                Value::from(Record::new("person", 2)),
+                Record::new("person", 3).into(),
+            ]),
+            data: Some(Data::Set(vec![(
+                Idiom(vec![Part::Field(Ident::new("since"))]),
+                Operator::Eq,
+                2020.into(),
+            )])),
+            output: None,
+        };
+        let reverse = Relate {
+            from: Record::new("person", 1).into(),
+            kind: Record::new("knows", 1).into(),
+            with: Record::new("person", 2).into(),
+            data: None,
+            output: None,
+        };
+        assert_eq!(query.0, vec![Query::Relate(relate), Query::Relate(reverse)]);
+    }
+
+    #[test]
+    fn parse_function_test() {
+        let correct = Function::new("math::sum", vec![Ident::new("age").into()]);
+        assert_eq!(value("math::sum(age)"), correct.into());
+        assert_eq!(value("count()"), Function::new("count", vec![]).into());
+    }
+
+    #[test]
+    fn parse_group_order_test() {
+        let query = parse(
+            "SELECT city, count() FROM person GROUP BY city ORDER BY city COLLATE DESC, age LIMIT 2",
+        )
+        .unwrap();
+        let Some(Query::Select(select)) = query.0.into_iter().next() else {
+            panic!("select was not parsed");
+        };
+        let city = Idiom(vec![Part::Field(Ident::new("city"))]);
+        let mut first = Order::new(city.clone());
+        first.collate = true;
+        first.ascending = false;
+        let second = Order::new(Idiom(vec![Part::Field(Ident::new("age"))]));
+        assert_eq!(select.group, Some(Groups(vec![city])));
+        assert_eq!(select.order, Some(Orders(vec![first, second])));
+        assert_eq!(select.limit, Some(2));
+
+        let query = parse("SELECT count() FROM person GROUP ALL").unwrap();
+        let Some(Query::Select(select)) = query.0.into_iter().next() else {
+            panic!("select was not parsed");
+        };
+        assert_eq!(select.group, Some(Groups::default()));
+    }
+
+    #[test]
+    fn parse_define_test() {
+        let query = parse(
+            "DEFINE TABLE person; DEFINE INDEX idx ON TABLE person FIELDS email UNIQUE;
+            DEFINE INDEX pair ON person COLUMNS name, meta.age",
+        )
+        .unwrap();
+        let index = |name: &str, fields, unique| {
+            Query::Define(Define::Index {
+                name: String::from(name),
+                table: String::from("person"),
+                fields,
+                index: match unique {
+                    true => Index::Uniq,
+                    false => Index::Idx,
+                },
+            })
+        };
+        assert_eq!(
+            query.0,
+            vec![
+                Query::Define(Define::Table(String::from("person"))),
+                index(
+                    "idx",
+                    vec![Idiom(vec![Part::Field(Ident::new("email"))])],
+                    true
+                ),
+                index(
+                    "pair",
+                    vec![
+                        Idiom(vec![Part::Field(Ident::new("name"))]),
+                        Idiom(vec![
+                            Part::Field(Ident::new("meta")),
+                            Part::Field(Ident::new("age")),
+                        ]),
+                    ],
+                    false
+                ),
+            ]
+        );
+        assert!(parse("DEFINE INDEX idx ON person").is_err());
+    }
+
+    #[test]
+    fn parse_define_search_test() {
+        let query = parse(
+            "DEFINE ANALYZER simple TOKENIZERS blank, class FILTERS lowercase, ascii,
+                edgengram(2, 5), snowball(english);
+            DEFINE INDEX text ON post FIELDS text SEARCH ANALYZER simple BM25(1.5, 0.5);
+            DEFINE INDEX body ON post FIELDS body SEARCH ANALYZER simple BM25;
+            SELECT * FROM post WHERE text @1@ 'hello' AND body @@ 'world'",
+        )
+        .unwrap();
+        assert_eq!(
+            query.0[0],
+            Query::Define(Define::Analyzer {
+                name: String::from("simple"),
+                tokenizers: vec![Tokenizer::Blank, Tokenizer::Class],
+                filters: vec![
+                    Filter::Lowercase,
+                    Filter::Ascii,
+                    Filter::EdgeNgram(2, 5),
+                    Filter::Snowball(Language::English),
+                ],
+            })
+        );
+        let search = |name: &str, index| {
+            Query::Define(Define::Index {
+                name: String::from(name),
+                table: String::from("post"),
+                fields: vec![Idiom(vec![Part::Field(Ident::new(name))])],
+                index,
+            })
+        };
+        let tuned = Index::Search {
+            analyzer: String::from("simple"),
+            k1: Number::Float(1.5),
+            b: Number::Float(0.5),
+        };
+        assert_eq!(query.0[1], search("text", tuned));
+        let default = Index::search(String::from("simple"));
+        assert_eq!(query.0[2], search("body", default));
+        let Query::Select(Select {
+            conditions: Some(cond),
+            ..
+        }) = &query.0[3]
+        else {
+            panic!("select was not parsed");
+        };
+        assert_eq!(cond.0.to_string(), "text @1@ hello AND body @@ world");
+        assert!(parse("DEFINE ANALYZER a FILTERS snowball(klingon)").is_err());
+        assert!(parse("DEFINE ANALYZER a TOKENIZERS words").is_err());
+        assert!(parse("SELECT * FROM a WHERE b @x@ 'c'").is_err());
+    }
+
+    #[test]
+    fn parse_define_hnsw_test() {
+        let query = parse(
+            "DEFINE INDEX pt ON point FIELDS pt HNSW DIMENSION 3 DIST COSINE EFC 40;
+            DEFINE INDEX pt ON point FIELDS pt HNSW DIMENSION 2;
+            SELECT * FROM point WHERE pt <|10|> [1, 2] OR pt <|2,manhattan|> [3, 4]",
+        )
+        .unwrap();
+        let hnsw = |dimension, distance, efc| {
+            Query::Define(Define::Index {
+                name: String::from("pt"),
+                table: String::from("point"),
+                fields: vec![Idiom(vec![Part::Field(Ident::new("pt"))])],
+                index: Index::Hnsw {
+                    dimension,
+                    distance,
+                    m: 12,
+                    efc,
+                },
+            })
+        };
+        assert_eq!(query.0[0], hnsw(3, Distance::Cosine, 40));
+        assert_eq!(query.0[1], hnsw(2, Distance::Euclidean, 150));
+        let Query::Select(Select {
+            conditions: Some(cond),
+            ..
+        }) = &query.0[2]
+        else {
+            panic!("select was not parsed");
+        };
+        assert_eq!(
+            cond.0.to_string(),
+            "pt <|10|> [1,2] OR pt <|2,MANHATTAN|> [3,4]"
+        );
+        assert!(parse("DEFINE INDEX pt ON point FIELDS pt HNSW DIMENSION 0").is_err());
+        assert!(parse("DEFINE INDEX pt ON point FIELDS pt HNSW DIMENSION 2 M 1").is_err());
+        assert!(parse("SELECT * FROM a WHERE b <|x|> [1]").is_err());
+        assert!(parse("SELECT * FROM a WHERE b <|1,hamming|> [1]").is_err());
+    }
+
+    #[test]
+    fn parse_transaction_test() {
+        let query = parse("BEGIN TRANSACTION; CREATE person:1; COMMIT; BEGIN; CANCEL").unwrap();
+        assert_eq!(query[0], Query::Begin);
+        assert_eq!(query[2], Query::Commit);
+        assert_eq!(query[3], Query::Begin);
+        assert_eq!(query[4], Query::Cancel);
+    }
+
+    #[test]
+    fn parse_version_test() {
+        let query =
+            parse("SELECT * FROM person:1 VERSION d'2026-01-01T00:00:00Z'; SELECT d FROM a")
+                .unwrap();
+        let Query::Select(Select {
+            version: Some(version),
+            ..
+        }) = &query[0]
+        else {
+            panic!("expected a version");
+        };
+        let at = Datetime::parse("2026-01-01T00:00:00Z").unwrap();
+        assert_eq!(version, &Value::Datetime(at));
+        assert_eq!(version.to_string(), "d'2026-01-01T00:00:00Z'");
+        let Query::Select(Select { fields, .. }) = &query[1] else {
+            panic!("expected a select");
+        };
+        assert_eq!(fields.0, vec![Field::new(Ident::new("d").into())]);
+        assert!(parse("SELECT * FROM a VERSION d'2026-13-01'").is_err());
+
+        let query = parse("DEFINE TABLE person HISTORY 30d; DEFINE TABLE log HISTORY 2h").unwrap();
+        let history = |table: &str, secs: u64| {
+            Query::Define(Define::Options {
+                table: String::from(table),
+                schemafull: None,
+                history: Some(Duration::from_secs(secs)),
+            })
+        };
+        assert_eq!(
+            query.0,
+            vec![history("person", 30 * 86_400), history("log", 7_200)]
+        );
+        assert!(parse("DEFINE TABLE person HISTORY 30").is_err());
+        assert!(parse("DEFINE TABLE person HISTORY 30 d").is_err());
+        assert!(parse("DEFINE TABLE person HISTORY 30y").is_err());
+    }
+
+    #[test]
+    fn parse_define_field_test() {
+        let query = parse("DEFINE TABLE person SCHEMAFULL HISTORY 1d; DEFINE TABLE log SCHEMALESS")
+            .unwrap();
+        assert_eq!(
+            query[0],
+            Query::Define(Define::Options {
+                table: String::from("person"),
+                schemafull: Some(true),
+                history: Some(Duration::from_secs(86_400)),
+            })
+        );
+        let Query::Define(Define::Options { schemafull, .. }) = &query[1] else {
+            panic!("expected table options");
+        };
+        assert_eq!(*schemafull, Some(false));
+
+        let source = "DEFINE FIELD age ON TABLE person TYPE int DEFAULT 0 ASSERT $value >= 0";
+        let query = parse(&format!(
+            "{source}; DEFINE FIELD tags.* ON person TYPE string"
+        ))
+        .unwrap();
+        let Query::Define(Define::Field {
+            table,
+            definition,
+            source: text,
+        }) = &query[0]
+        else {
+            panic!("expected a field");
+        };
+        assert_eq!(table, "person");
+        assert_eq!(text, source);
+        assert_eq!(definition.field.to_string(), "age");
+        assert_eq!(definition.kind, Some(Kind::Int));
+        assert_eq!(definition.default, Some(Value::from(0)));
+        let check = Expression::Binary {
+            left: Value::Param("value".into()),
+            op: Operator::GtEq,
+            right: Value::from(0),
+        };
+        assert_eq!(definition.assert, Some(Value::Expression(Box::new(check))));
+        let Query::Define(Define::Field { definition, .. }) = &query[1] else {
+            panic!("expected a field");
+        };
+        assert_eq!(definition.field.to_string(), "tags.*");
+        assert_eq!(definition.kind, Some(Kind::String));
+
+        let query =
+            parse("DEFINE FIELD friends ON person TYPE option<array<record<person>>>").unwrap();
+        let Query::Define(Define::Field { definition, .. }) = &query[0] else {
+            panic!("expected a field");
+        };
+        let kind = Kind::Option(Box::new(Kind::Array(Some(Box::new(Kind::Record(Some(
+            String::from("person"),
+        )))))));
+        assert_eq!(definition.kind, Some(kind));
+        let query = parse("DEFINE FIELD created ON person VALUE time::now() READONLY").unwrap();
+        let Query::Define(Define::Field { definition, .. }) = &query[0] else {
+            panic!("expected a field");
+        };
+        let now = Function::new("time::now", vec![]);
+        assert_eq!(definition.value, Some(Value::Function(Box::new(now))));
+        assert!(definition.readonly);
+        assert!(parse("DEFINE FIELD age ON person TYPE integer").is_err());
+        assert!(parse("DEFINE FIELD age[0] ON person").is_err());
+        assert!(parse("DEFINE FIELD age").is_err());
+    }
+
+    #[test]
+    fn parse_error_position_test() {
+        let err = parse("SELECT *\nFROM person\nWHERE age > ;").unwrap_err();
+        assert_eq!(
+            err,
+            Error::ParseError {
+                line: 3,
+                column: 13,
+                message: String::from("Unexpected ';', expected a value"),
+            }
+        );
+        let err = parse("SELECT * FROM person:-a").unwrap_err();
+        assert_eq!(
+            err,
+            Error::ParseError {
+                line: 1,
+                column: 22,
+                message: String::from("Unexpected 'a', expected a record id"),
+            }
+        );
+    }
+}
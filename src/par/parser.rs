@@ -0,0 +1,136 @@
+use crate::{
+    err::Error,
+    par::{
+        lexer::Lexer,
+        token::{Token, TokenKind},
+    },
+};
+
+pub struct Parser {
+    input: String,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    pub fn new(input: &str) -> Result<Self, Error> {
+        Ok(Parser {
+            input: input.to_string(),
+            tokens: Lexer::new(input).tokenize()?,
+            pos: 0,
+        })
+    }
+
+    pub fn peek(&self) -> &Token {
+        self.peek_nth(0)
+    }
+
+    pub fn peek_nth(&self, n: usize) -> &Token {
+        let last = self.tokens.len() - 1;
+        &self.tokens[(self.pos + n).min(last)]
+    }
+
+    pub fn peek_kind(&self) -> &TokenKind {
+        &self.peek().kind
+    }
+
+    pub fn next(&mut self) -> Token {
+        let token = self.peek().clone();
+        if token.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.peek().kind == TokenKind::Eof
+    }
+
+    pub fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek_kind() == kind {
+            self.next();
+            return true;
+        }
+        false
+    }
+
+    pub fn expect(&mut self, kind: TokenKind) -> Result<Token, Error> {
+        if *self.peek_kind() == kind {
+            return Ok(self.next());
+        }
+        Err(self.unexpected(&kind.to_string()))
+    }
+
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.peek().is_keyword(keyword)
+    }
+
+    pub fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.is_keyword(keyword) {
+            self.next();
+            return true;
+        }
+        false
+    }
+
+    pub fn expect_keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if self.eat_keyword(keyword) {
+            return Ok(());
+        }
+        Err(self.unexpected(keyword))
+    }
+
+    /// The input from an offset up to the end of the last token parsed
+    pub fn source(&self, offset: usize) -> String {
+        let end = match self.pos.checked_sub(1).and_then(|i| self.tokens.get(i)) {
+            Some(prev) => prev.span.end(),
+            None => offset,
+        };
+        self.input[offset..end].to_string()
+    }
+
+    /// Returns true when the next token directly follows the previous one
+    /// without any whitespace in between, e.g. the `:` in `person:1`.
+    pub fn is_adjacent(&self) -> bool {
+        match self.pos.checked_sub(1).and_then(|i| self.tokens.get(i)) {
+            Some(prev) => prev.span.end() == self.peek().span.offset,
+            None => false,
+        }
+    }
+
+    pub fn parse_ident(&mut self) -> Result<String, Error> {
+        match self.peek_kind() {
+            TokenKind::Ident(_) => match self.next().kind {
+                TokenKind::Ident(ident) => Ok(ident),
+                _ => unreachable!(),
+            },
+            _ => Err(self.unexpected("an identifier")),
+        }
+    }
+
+    pub fn parse_usize(&mut self) -> Result<usize, Error> {
+        match *self.peek_kind() {
+            TokenKind::Int(v) if v >= 0 => {
+                self.next();
+                Ok(v as usize)
+            }
+            _ => Err(self.unexpected("a positive integer")),
+        }
+    }
+
+    pub fn error(&self, token: &Token, message: String) -> Error {
+        Error::ParseError {
+            line: token.span.line,
+            column: token.span.column,
+            message,
+        }
+    }
+
+    pub fn unexpected(&self, expected: &str) -> Error {
+        let token = self.peek();
+        self.error(
+            token,
+            format!("Unexpected {}, expected {expected}", token.kind),
+        )
+    }
+}
@@ -0,0 +1,582 @@
+use crate::{
+    err::Error,
+    par::{parser::Parser, token::TokenKind},
+    ql::{
+        condition::Condition,
+        data::Data,
+        definition::Definition,
+        distance::Distance,
+        fields::{Field, Fields},
+        filter::{Filter, Language},
+        group::Groups,
+        idiom::Idiom,
+        index::Index,
+        kind::Kind,
+        number::Number,
+        operator::Operator,
+        order::{Order, Orders},
+        output::Output,
+        part::Part,
+        query::{Queries, Query},
+        statements::{
+            create::Create, define::Define, delete::Delete, relate::Relate, select::Select,
+            update::Update,
+        },
+        table::Table,
+        tokenizer::Tokenizer,
+        value::{Value, Values},
+    },
+};
+use std::time::Duration;
+
+impl Parser {
+    pub fn parse_query(&mut self) -> Result<Queries, Error> {
+        let mut queries = Vec::new();
+        loop {
+            while self.eat(&TokenKind::SemiColon) {}
+            if self.is_eof() {
+                return Ok(Queries(queries));
+            }
+            queries.push(self.parse_statement()?);
+            if !self.eat(&TokenKind::SemiColon) && !self.is_eof() {
+                return Err(self.unexpected("';'"));
+            }
+        }
+    }
+
+    fn parse_statement(&mut self) -> Result<Query, Error> {
+        if self.eat_keyword("SELECT") {
+            return Ok(Query::Select(self.parse_select()?));
+        }
+        if self.eat_keyword("CREATE") {
+            return Ok(Query::Create(self.parse_create()?));
+        }
+        if self.eat_keyword("UPDATE") {
+            return Ok(Query::Update(self.parse_update()?));
+        }
+        if self.eat_keyword("DELETE") {
+            return Ok(Query::Delete(self.parse_delete()?));
+        }
+        if self.eat_keyword("RELATE") {
+            return Ok(Query::Relate(self.parse_relate()?));
+        }
+        if self.is_keyword("DEFINE") {
+            return Ok(Query::Define(self.parse_define()?));
+        }
+        for (keyword, query) in [
+            ("BEGIN", Query::Begin),
+            ("COMMIT", Query::Commit),
+            ("CANCEL", Query::Cancel),
+        ] {
+            if self.eat_keyword(keyword) {
+                self.eat_keyword("TRANSACTION");
+                return Ok(query);
+            }
+        }
+        Err(self.unexpected("a statement"))
+    }
+
+    fn parse_select(&mut self) -> Result<Select, Error> {
+        let fields = self.parse_fields()?;
+        self.expect_keyword("FROM")?;
+        let what = self.parse_what()?;
+        let conditions = self.parse_condition()?;
+        let group = self.parse_group()?;
+        let order = self.parse_order()?;
+
+        let mut limit = None;
+        if self.eat_keyword("LIMIT") {
+            self.eat_keyword("BY");
+            limit = Some(self.parse_usize()?);
+        }
+        let mut start = None;
+        if self.eat_keyword("START") {
+            self.eat_keyword("AT");
+            start = Some(self.parse_usize()?);
+        }
+        let mut version = None;
+        if self.eat_keyword("VERSION") {
+            version = Some(self.parse_value()?);
+        }
+
+        Ok(Select {
+            fields,
+            what,
+            conditions,
+            group,
+            order,
+            limit,
+            start,
+            version,
+        })
+    }
+
+    fn parse_group(&mut self) -> Result<Option<Groups>, Error> {
+        if !self.eat_keyword("GROUP") {
+            return Ok(None);
+        }
+        if self.eat_keyword("ALL") {
+            return Ok(Some(Groups::default()));
+        }
+        self.eat_keyword("BY");
+        let mut groups = Vec::new();
+        loop {
+            groups.push(self.parse_idiom()?);
+            if !self.eat(&TokenKind::Comma) {
+                return Ok(Some(Groups(groups)));
+            }
+        }
+    }
+
+    fn parse_order(&mut self) -> Result<Option<Orders>, Error> {
+        if !self.eat_keyword("ORDER") {
+            return Ok(None);
+        }
+        self.eat_keyword("BY");
+        let mut orders = Vec::new();
+        loop {
+            let mut order = Order::new(self.parse_idiom()?);
+            order.collate = self.eat_keyword("COLLATE");
+            order.numeric = self.eat_keyword("NUMERIC");
+            if self.eat_keyword("DESC") {
+                order.ascending = false;
+            } else {
+                self.eat_keyword("ASC");
+            }
+            orders.push(order);
+            if !self.eat(&TokenKind::Comma) {
+                return Ok(Some(Orders(orders)));
+            }
+        }
+    }
+
+    fn parse_create(&mut self) -> Result<Create, Error> {
+        let what = self.parse_what()?;
+        let data = self.parse_data()?;
+        Ok(Create { what, data })
+    }
+
+    fn parse_update(&mut self) -> Result<Update, Error> {
+        let what = self.parse_what()?;
+        let data = self.parse_data()?;
+        let conditions = self.parse_condition()?;
+        let output = self.parse_output()?;
+        Ok(Update {
+            what,
+            data,
+            conditions,
+            output,
+        })
+    }
+
+    fn parse_delete(&mut self) -> Result<Delete, Error> {
+        self.eat_keyword("FROM");
+        let what = self.parse_what()?;
+        let conditions = self.parse_condition()?;
+        let output = self.parse_output()?;
+        Ok(Delete {
+            what,
+            conditions,
+            output,
+        })
+    }
+
+    fn parse_relate(&mut self) -> Result<Relate, Error> {
+        let from = self.parse_primary()?;
+        let out = match self.peek_kind() {
+            TokenKind::ArrowIn => false,
+            TokenKind::ArrowOut => true,
+            _ => return Err(self.unexpected("'->' or '<-'")),
+        };
+        let arrow = self.next().kind;
+        let kind = match self.is_record_start() {
+            true => Value::Record(Box::new(self.parse_record()?)),
+            false => Value::Table(Table(self.parse_ident()?)),
+        };
+        self.expect(arrow)?;
+        let with = self.parse_primary()?;
+        let (from, with) = match out {
+            true => (with, from),
+            false => (from, with),
+        };
+        let data = self.parse_data()?;
+        let output = self.parse_output()?;
+        Ok(Relate {
+            from,
+            kind,
+            with,
+            data,
+            output,
+        })
+    }
+
+    fn parse_define(&mut self) -> Result<Define, Error> {
+        let offset = self.next().span.offset;
+        if self.eat_keyword("TABLE") {
+            return self.parse_define_table();
+        }
+        if self.eat_keyword("FIELD") {
+            let (table, definition) = self.parse_definition()?;
+            return Ok(Define::Field {
+                table,
+                definition,
+                source: self.source(offset),
+            });
+        }
+        if self.eat_keyword("ANALYZER") {
+            return self.parse_analyzer();
+        }
+        if !self.eat_keyword("INDEX") {
+            return Err(self.unexpected("TABLE, FIELD, ANALYZER or INDEX"));
+        }
+        let name = self.parse_ident()?;
+        self.expect_keyword("ON")?;
+        self.eat_keyword("TABLE");
+        let table = self.parse_ident()?;
+        if !self.eat_keyword("FIELDS") && !self.eat_keyword("COLUMNS") {
+            return Err(self.unexpected("FIELDS or COLUMNS"));
+        }
+        let mut fields = vec![self.parse_idiom()?];
+        while self.eat(&TokenKind::Comma) {
+            fields.push(self.parse_idiom()?);
+        }
+        let index = if self.eat_keyword("UNIQUE") {
+            Index::Uniq
+        } else if self.eat_keyword("SEARCH") {
+            self.parse_search()?
+        } else if self.eat_keyword("HNSW") {
+            self.parse_hnsw()?
+        } else {
+            Index::Idx
+        };
+        Ok(Define::Index {
+            name,
+            table,
+            fields,
+            index,
+        })
+    }
+
+    fn parse_define_table(&mut self) -> Result<Define, Error> {
+        let table = self.parse_ident()?;
+        let (mut schemafull, mut history) = (None, None);
+        loop {
+            if self.eat_keyword("SCHEMAFULL") {
+                schemafull = Some(true);
+            } else if self.eat_keyword("SCHEMALESS") {
+                schemafull = Some(false);
+            } else if self.eat_keyword("HISTORY") {
+                history = Some(self.parse_duration()?);
+            } else {
+                break;
+            }
+        }
+        if schemafull.is_none() && history.is_none() {
+            return Ok(Define::Table(table));
+        }
+        Ok(Define::Options {
+            table,
+            schemafull,
+            history,
+        })
+    }
+
+    /// The field and table of `DEFINE FIELD`, followed by its clauses
+    fn parse_definition(&mut self) -> Result<(String, Definition), Error> {
+        let token = self.peek().clone();
+        let field = self.parse_idiom()?;
+        let is_path = |part: &Part| matches!(part, Part::Field(_) | Part::All);
+        if !field.iter().all(is_path) || field.first() == Some(&Part::All) {
+            return Err(self.error(&token, format!("Invalid field '{field}'")));
+        }
+        self.expect_keyword("ON")?;
+        self.eat_keyword("TABLE");
+        let table = self.parse_ident()?;
+        let mut definition = Definition::new(field);
+        loop {
+            if self.eat_keyword("TYPE") {
+                definition.kind = Some(self.parse_kind()?);
+            } else if self.eat_keyword("DEFAULT") {
+                definition.default = Some(self.parse_value()?);
+            } else if self.eat_keyword("ASSERT") {
+                definition.assert = Some(self.parse_value()?);
+            } else if self.eat_keyword("VALUE") {
+                definition.value = Some(self.parse_value()?);
+            } else if self.eat_keyword("READONLY") {
+                definition.readonly = true;
+            } else {
+                return Ok((table, definition));
+            }
+        }
+    }
+
+    fn parse_kind(&mut self) -> Result<Kind, Error> {
+        let token = self.peek().clone();
+        let name = self.parse_ident()?.to_lowercase();
+        let has_inner = matches!(name.as_str(), "array" | "record" | "option");
+        if !has_inner || !self.eat(&TokenKind::Lt) {
+            return Ok(match name.as_str() {
+                "any" => Kind::Any,
+                "bool" => Kind::Bool,
+                "int" => Kind::Int,
+                "float" => Kind::Float,
+                "number" => Kind::Number,
+                "string" => Kind::String,
+                "datetime" => Kind::Datetime,
+                "uuid" => Kind::Uuid,
+                "object" => Kind::Object,
+                "array" => Kind::Array(None),
+                "record" => Kind::Record(None),
+                _ => return Err(self.error(&token, format!("Unknown type '{name}'"))),
+            });
+        }
+        let kind = match name.as_str() {
+            "array" => Kind::Array(Some(Box::new(self.parse_kind()?))),
+            "record" => Kind::Record(Some(self.parse_ident()?)),
+            _ => Kind::Option(Box::new(self.parse_kind()?)),
+        };
+        self.expect(TokenKind::Gt)?;
+        Ok(kind)
+    }
+
+    fn parse_analyzer(&mut self) -> Result<Define, Error> {
+        let name = self.parse_ident()?;
+        let mut tokenizers = Vec::new();
+        let mut filters = Vec::new();
+        if self.eat_keyword("TOKENIZERS") {
+            loop {
+                let token = self.peek().clone();
+                let name = self.parse_ident()?;
+                match Tokenizer::from_name(&name) {
+                    Some(tokenizer) => tokenizers.push(tokenizer),
+                    None => return Err(self.error(&token, format!("Unknown tokenizer {name}"))),
+                }
+                if !self.eat(&TokenKind::Comma) {
+                    break;
+                }
+            }
+        }
+        if self.eat_keyword("FILTERS") {
+            loop {
+                filters.push(self.parse_filter()?);
+                if !self.eat(&TokenKind::Comma) {
+                    break;
+                }
+            }
+        }
+        Ok(Define::Analyzer {
+            name,
+            tokenizers,
+            filters,
+        })
+    }
+
+    fn parse_filter(&mut self) -> Result<Filter, Error> {
+        let token = self.peek().clone();
+        let name = self.parse_ident()?;
+        let filter = match name.to_lowercase().as_str() {
+            "ascii" => Filter::Ascii,
+            "lowercase" => Filter::Lowercase,
+            "uppercase" => Filter::Uppercase,
+            "edgengram" => {
+                self.expect(TokenKind::OpenParen)?;
+                let min = self.parse_usize()?;
+                self.expect(TokenKind::Comma)?;
+                let max = self.parse_usize()?;
+                self.expect(TokenKind::CloseParen)?;
+                match (u16::try_from(min), u16::try_from(max)) {
+                    (Ok(min), Ok(max)) if min <= max => Filter::EdgeNgram(min, max),
+                    _ => return Err(self.error(&token, String::from("Invalid edgengram size"))),
+                }
+            }
+            "snowball" => {
+                self.expect(TokenKind::OpenParen)?;
+                let token = self.peek().clone();
+                let lang = self.parse_ident()?;
+                self.expect(TokenKind::CloseParen)?;
+                match Language::from_name(&lang) {
+                    Some(lang) => Filter::Snowball(lang),
+                    None => return Err(self.error(&token, format!("Unknown language {lang}"))),
+                }
+            }
+            _ => return Err(self.error(&token, format!("Unknown filter {name}"))),
+        };
+        Ok(filter)
+    }
+
+    fn parse_search(&mut self) -> Result<Index, Error> {
+        self.expect_keyword("ANALYZER")?;
+        let analyzer = self.parse_ident()?;
+        if !self.eat_keyword("BM25") || !self.eat(&TokenKind::OpenParen) {
+            return Ok(Index::search(analyzer));
+        }
+        let k1 = self.parse_number()?;
+        self.expect(TokenKind::Comma)?;
+        let b = self.parse_number()?;
+        self.expect(TokenKind::CloseParen)?;
+        Ok(Index::Search { analyzer, k1, b })
+    }
+
+    fn parse_hnsw(&mut self) -> Result<Index, Error> {
+        self.expect_keyword("DIMENSION")?;
+        let dimension = self.parse_size(1)?;
+        let (mut distance, mut m, mut efc) = (Distance::default(), 12, 150);
+        loop {
+            if self.eat_keyword("DIST") {
+                let token = self.peek().clone();
+                let name = self.parse_ident()?;
+                distance = match Distance::from_name(&name) {
+                    Some(distance) => distance,
+                    None => return Err(self.error(&token, format!("Unknown distance {name}"))),
+                };
+            } else if self.eat_keyword("M") {
+                m = self.parse_size(2)?;
+            } else if self.eat_keyword("EFC") {
+                efc = self.parse_size(1)?;
+            } else {
+                break;
+            }
+        }
+        Ok(Index::Hnsw {
+            dimension,
+            distance,
+            m,
+            efc,
+        })
+    }
+
+    fn parse_size<T: TryFrom<usize>>(&mut self, min: usize) -> Result<T, Error> {
+        let token = self.peek().clone();
+        let size = self.parse_usize()?;
+        match T::try_from(size) {
+            Ok(value) if size >= min => Ok(value),
+            _ => Err(self.error(&token, format!("Invalid size {size}"))),
+        }
+    }
+
+    /// A duration such as `30d`, an amount directly followed by its unit
+    fn parse_duration(&mut self) -> Result<Duration, Error> {
+        let amount = self.parse_usize()? as u64;
+        let seconds = match self.peek_kind() {
+            TokenKind::Ident(unit) if self.is_adjacent() => match unit.as_str() {
+                "s" => 1,
+                "m" => 60,
+                "h" => 3_600,
+                "d" => 86_400,
+                "w" => 604_800,
+                _ => return Err(self.unexpected("a duration unit")),
+            },
+            _ => return Err(self.unexpected("a duration unit")),
+        };
+        self.next();
+        Ok(Duration::from_secs(amount.saturating_mul(seconds)))
+    }
+
+    fn parse_number(&mut self) -> Result<Number, Error> {
+        match *self.peek_kind() {
+            TokenKind::Int(v) => {
+                self.next();
+                Ok(Number::Int(v))
+            }
+            TokenKind::Float(v) => {
+                self.next();
+                Ok(Number::Float(v))
+            }
+            _ => Err(self.unexpected("a number")),
+        }
+    }
+
+    fn parse_fields(&mut self) -> Result<Fields, Error> {
+        let mut fields = Vec::new();
+        loop {
+            if self.eat(&TokenKind::Star) {
+                fields.push(Field::WildCard);
+            } else {
+                let expr = self.parse_value()?;
+                match self.eat_keyword("AS") {
+                    true => fields.push(Field::new_alias(expr, self.parse_ident()?)),
+                    false => fields.push(Field::new(expr)),
+                }
+            }
+            if !self.eat(&TokenKind::Comma) {
+                return Ok(Fields(fields));
+            }
+        }
+    }
+
+    pub fn parse_what(&mut self) -> Result<Values, Error> {
+        let mut what = Vec::new();
+        loop {
+            what.push(match self.parse_value()? {
+                Value::Idiom(Idiom(parts)) if matches!(parts.as_slice(), [Part::Field(_)]) => {
+                    let Some(Part::Field(ident)) = parts.into_iter().next() else {
+                        unreachable!()
+                    };
+                    Value::Table(Table(ident.to_string()))
+                }
+                value => value,
+            });
+            if !self.eat(&TokenKind::Comma) {
+                return Ok(Values(what));
+            }
+        }
+    }
+
+    pub fn parse_data(&mut self) -> Result<Option<Data>, Error> {
+        if self.eat_keyword("SET") {
+            let mut set = Vec::new();
+            loop {
+                let idiom = self.parse_idiom()?;
+                let op = match self.peek_kind() {
+                    TokenKind::Eq => Operator::Eq,
+                    TokenKind::PlusEq => Operator::Add,
+                    TokenKind::MinusEq => Operator::Sub,
+                    _ => return Err(self.unexpected("'=', '+=' or '-='")),
+                };
+                self.next();
+                set.push((idiom, op, self.parse_value()?));
+                if !self.eat(&TokenKind::Comma) {
+                    return Ok(Some(Data::Set(set)));
+                }
+            }
+        }
+        if self.eat_keyword("CONTENT") {
+            return Ok(Some(Data::Content(self.parse_value()?)));
+        }
+        if self.eat_keyword("MERGE") {
+            return Ok(Some(Data::Merge(self.parse_value()?)));
+        }
+        if self.eat_keyword("REPLACE") {
+            return Ok(Some(Data::Replace(self.parse_value()?)));
+        }
+        if self.eat_keyword("PATCH") {
+            return Ok(Some(Data::Patch(self.parse_value()?)));
+        }
+        Ok(None)
+    }
+
+    pub fn parse_output(&mut self) -> Result<Option<Output>, Error> {
+        if !self.eat_keyword("RETURN") {
+            return Ok(None);
+        }
+        let outputs = [
+            ("NONE", Output::None),
+            ("BEFORE", Output::Before),
+            ("AFTER", Output::After),
+            ("DIFF", Output::Diff),
+        ];
+        for (keyword, output) in outputs {
+            if self.eat_keyword(keyword) {
+                return Ok(Some(output));
+            }
+        }
+        Err(self.unexpected("NONE, BEFORE, AFTER or DIFF"))
+    }
+
+    pub fn parse_condition(&mut self) -> Result<Option<Condition>, Error> {
+        if self.eat_keyword("WHERE") {
+            return Ok(Some(Condition(self.parse_value()?)));
+        }
+        Ok(None)
+    }
+}
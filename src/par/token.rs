@@ -0,0 +1,121 @@
+use crate::ql::distance::Distance;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub offset: usize,
+    pub len: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn end(&self) -> usize {
+        self.offset + self.len
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Ident(String),
+    Int(i64),
+    Float(f64),
+    Strand(String),
+
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    OpenBrace,
+    CloseBrace,
+    Comma,
+    SemiColon,
+    Dot,
+    Colon,
+    Dollar,
+    Question,
+
+    Star,
+    Plus,
+    Minus,
+    Slash,
+    PlusEq,
+    MinusEq,
+    Eq,
+    EqEq,
+    NotEq,
+    Lt,
+    Gt,
+    LtEq,
+    GtEq,
+    Bang,
+    And,
+    Or,
+    Matches(Option<u8>),
+    Knn(u32, Option<Distance>),
+
+    ArrowIn,
+    ArrowOut,
+    ArrowBoth,
+
+    Eof,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Ident(v) => write!(f, "'{v}'"),
+            TokenKind::Int(v) => write!(f, "'{v}'"),
+            TokenKind::Float(v) => write!(f, "'{v}'"),
+            TokenKind::Strand(v) => write!(f, "string '{v}'"),
+            TokenKind::OpenParen => write!(f, "'('"),
+            TokenKind::CloseParen => write!(f, "')'"),
+            TokenKind::OpenBracket => write!(f, "'['"),
+            TokenKind::CloseBracket => write!(f, "']'"),
+            TokenKind::OpenBrace => write!(f, "'{{'"),
+            TokenKind::CloseBrace => write!(f, "'}}'"),
+            TokenKind::Comma => write!(f, "','"),
+            TokenKind::SemiColon => write!(f, "';'"),
+            TokenKind::Dot => write!(f, "'.'"),
+            TokenKind::Colon => write!(f, "':'"),
+            TokenKind::Dollar => write!(f, "'$'"),
+            TokenKind::Question => write!(f, "'?'"),
+            TokenKind::Star => write!(f, "'*'"),
+            TokenKind::Plus => write!(f, "'+'"),
+            TokenKind::Minus => write!(f, "'-'"),
+            TokenKind::Slash => write!(f, "'/'"),
+            TokenKind::PlusEq => write!(f, "'+='"),
+            TokenKind::MinusEq => write!(f, "'-='"),
+            TokenKind::Eq => write!(f, "'='"),
+            TokenKind::EqEq => write!(f, "'=='"),
+            TokenKind::NotEq => write!(f, "'!='"),
+            TokenKind::Lt => write!(f, "'<'"),
+            TokenKind::Gt => write!(f, "'>'"),
+            TokenKind::LtEq => write!(f, "'<='"),
+            TokenKind::GtEq => write!(f, "'>='"),
+            TokenKind::Bang => write!(f, "'!'"),
+            TokenKind::And => write!(f, "'&&'"),
+            TokenKind::Or => write!(f, "'||'"),
+            TokenKind::Matches(None) => write!(f, "'@@'"),
+            TokenKind::Matches(Some(r)) => write!(f, "'@{r}@'"),
+            TokenKind::Knn(k, None) => write!(f, "'<|{k}|>'"),
+            TokenKind::Knn(k, Some(dist)) => write!(f, "'<|{k},{dist}|>'"),
+            TokenKind::ArrowIn => write!(f, "'->'"),
+            TokenKind::ArrowOut => write!(f, "'<-'"),
+            TokenKind::ArrowBoth => write!(f, "'<->'"),
+            TokenKind::Eof => write!(f, "end of query"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl Token {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.kind, TokenKind::Ident(v) if v.eq_ignore_ascii_case(keyword))
+    }
+}
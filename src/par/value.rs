@@ -0,0 +1,500 @@
+use crate::{
+    err::Error,
+    par::{parser::Parser, token::TokenKind},
+    ql::{
+        array::Array,
+        condition::Condition,
+        datetime::Datetime,
+        direction::Direction,
+        edge::Edge,
+        expression::Expression,
+        function::Function,
+        id::Id,
+        ident::Ident,
+        idiom::Idiom,
+        object::Object,
+        operator::Operator,
+        part::Part,
+        path::Path,
+        record::Record,
+        recurse::{Collect, Recurse},
+        table::Table,
+        value::Value,
+    },
+};
+use std::collections::BTreeMap;
+
+impl Parser {
+    pub fn parse_value(&mut self) -> Result<Value, Error> {
+        self.parse_or()
+    }
+
+    fn binary(left: Value, op: Operator, right: Value) -> Value {
+        Expression::Binary { left, op, right }.into()
+    }
+
+    fn parse_or(&mut self) -> Result<Value, Error> {
+        let mut left = self.parse_and()?;
+        while self.eat(&TokenKind::Or) || self.eat_keyword("OR") {
+            let right = self.parse_and()?;
+            left = Self::binary(left, Operator::Or, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Value, Error> {
+        let mut left = self.parse_relation()?;
+        while self.eat(&TokenKind::And) || self.eat_keyword("AND") {
+            let right = self.parse_relation()?;
+            left = Self::binary(left, Operator::And, right);
+        }
+        Ok(left)
+    }
+
+    fn relation_operator(&mut self) -> Option<Operator> {
+        let op = match self.peek_kind() {
+            TokenKind::Eq | TokenKind::EqEq => Operator::Eq,
+            TokenKind::NotEq => Operator::NtEq,
+            TokenKind::Lt => Operator::Lt,
+            TokenKind::Gt => Operator::Gt,
+            TokenKind::LtEq => Operator::LtEq,
+            TokenKind::GtEq => Operator::GtEq,
+            TokenKind::Matches(r) => Operator::Matches(*r),
+            TokenKind::Knn(k, dist) => Operator::Knn(*k, *dist),
+            _ if self.is_keyword("IS") => {
+                self.next();
+                if self.eat_keyword("NOT") {
+                    return Some(Operator::NtEq);
+                }
+                return Some(Operator::Eq);
+            }
+            _ => return None,
+        };
+        self.next();
+        Some(op)
+    }
+
+    fn parse_relation(&mut self) -> Result<Value, Error> {
+        let mut left = self.parse_additive()?;
+        while let Some(op) = self.relation_operator() {
+            let right = self.parse_additive()?;
+            left = Self::binary(left, op, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Value, Error> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek_kind() {
+                TokenKind::Plus => Operator::Add,
+                TokenKind::Minus => Operator::Sub,
+                _ => return Ok(left),
+            };
+            self.next();
+            let right = self.parse_multiplicative()?;
+            left = Self::binary(left, op, right);
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Value, Error> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = match self.peek_kind() {
+                TokenKind::Star => Operator::Mult,
+                TokenKind::Slash => Operator::Div,
+                _ => return Ok(left),
+            };
+            self.next();
+            let right = self.parse_unary()?;
+            left = Self::binary(left, op, right);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Value, Error> {
+        if self.eat(&TokenKind::Minus) {
+            return Ok(match self.parse_unary()? {
+                Value::Number(v) => Value::Number(-v),
+                expr => Expression::Unary {
+                    op: Operator::Neg,
+                    expr,
+                }
+                .into(),
+            });
+        }
+        if self.eat(&TokenKind::Bang) || self.eat_keyword("NOT") {
+            let expr = self.parse_unary()?;
+            return Ok(Expression::Unary {
+                op: Operator::Not,
+                expr,
+            }
+            .into());
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> Result<Value, Error> {
+        let (start, parts) = match self.peek_kind() {
+            TokenKind::ArrowIn | TokenKind::ArrowOut | TokenKind::ArrowBoth => {
+                (None, self.parse_parts()?)
+            }
+            TokenKind::Ident(_)
+                if !self.is_literal_keyword()
+                    && !self.is_record_start()
+                    && !self.is_function_start() =>
+            {
+                (None, self.parse_idiom()?.0)
+            }
+            _ => {
+                let start = self.parse_primary()?;
+                (Some(start), self.parse_parts()?)
+            }
+        };
+
+        Ok(match (start, parts) {
+            (Some(start), parts) if parts.is_empty() => start,
+            (Some(Value::Record(from)), parts) => match parts.as_slice() {
+                [Part::Path(Path {
+                    dir,
+                    to,
+                    filter: None,
+                })] => Value::Edge(Box::new(Edge {
+                    dir: dir.clone(),
+                    from: *from,
+                    to: to.clone(),
+                })),
+                _ => {
+                    let mut idiom = vec![Part::Start(Value::Record(from))];
+                    idiom.extend(parts);
+                    Value::Idiom(Idiom(idiom))
+                }
+            },
+            (Some(start), parts) => {
+                let mut idiom = vec![Part::Start(start)];
+                idiom.extend(parts);
+                Value::Idiom(Idiom(idiom))
+            }
+            (None, parts) => Value::Idiom(Idiom(parts)),
+        })
+    }
+
+    pub fn parse_idiom(&mut self) -> Result<Idiom, Error> {
+        let mut parts = vec![Part::Field(Ident::new(self.parse_ident()?))];
+        parts.append(&mut self.parse_parts()?);
+        Ok(Idiom(parts))
+    }
+
+    fn is_literal_keyword(&self) -> bool {
+        ["TRUE", "FALSE", "NONE", "NULL"]
+            .iter()
+            .any(|keyword| self.is_keyword(keyword))
+            || (["u", "d"].iter().any(|prefix| self.is_keyword(prefix))
+                && matches!(self.peek_nth(1).kind, TokenKind::Strand(_)))
+    }
+
+    pub fn is_record_start(&self) -> bool {
+        let (ident, colon) = (self.peek(), self.peek_nth(1));
+        colon.kind == TokenKind::Colon && ident.span.end() == colon.span.offset
+    }
+
+    pub fn is_function_start(&self) -> bool {
+        let (ident, next) = (self.peek(), self.peek_nth(1));
+        match next.kind {
+            TokenKind::OpenParen => ident.span.end() == next.span.offset,
+            TokenKind::Colon => self.peek_nth(2).kind == TokenKind::Colon,
+            _ => false,
+        }
+    }
+
+    pub fn parse_function(&mut self) -> Result<Function, Error> {
+        let mut name = self.parse_ident()?;
+        while self.eat(&TokenKind::Colon) {
+            self.expect(TokenKind::Colon)?;
+            name.push_str("::");
+            name.push_str(&self.parse_ident()?);
+        }
+        self.expect(TokenKind::OpenParen)?;
+        let mut args = Vec::new();
+        while !self.eat(&TokenKind::CloseParen) {
+            args.push(self.parse_value()?);
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(TokenKind::CloseParen)?;
+                break;
+            }
+        }
+        Ok(Function::new(name, args))
+    }
+
+    pub fn parse_parts(&mut self) -> Result<Vec<Part>, Error> {
+        let mut parts = Vec::new();
+        loop {
+            match self.peek_kind() {
+                TokenKind::Dot => {
+                    self.next();
+                    if self.eat(&TokenKind::Dot) {
+                        self.expect(TokenKind::Dot)?;
+                        parts.push(Part::Flatten);
+                    } else if self.eat(&TokenKind::Star) {
+                        parts.push(Part::All);
+                    } else {
+                        parts.push(Part::Field(Ident::new(self.parse_ident()?)));
+                    }
+                }
+                TokenKind::OpenBracket => {
+                    self.next();
+                    parts.push(self.parse_bracket_part()?);
+                    self.expect(TokenKind::CloseBracket)?;
+                }
+                TokenKind::ArrowIn | TokenKind::ArrowOut | TokenKind::ArrowBoth => {
+                    parts.push(Part::Path(self.parse_path()?));
+                }
+                // Only a brace right against the idiom repeats a walk, a
+                // spaced one starts an object
+                TokenKind::OpenBrace if self.is_adjacent() => {
+                    self.next();
+                    parts.push(Part::Recurse(self.parse_recurse()?));
+                    if !matches!(
+                        self.peek_kind(),
+                        TokenKind::ArrowIn | TokenKind::ArrowOut | TokenKind::ArrowBoth
+                    ) {
+                        return Err(self.unexpected("a path to repeat"));
+                    }
+                }
+                _ => return Ok(parts),
+            }
+        }
+    }
+
+    fn parse_bracket_part(&mut self) -> Result<Part, Error> {
+        Ok(match self.peek_kind().clone() {
+            TokenKind::Star => {
+                self.next();
+                Part::All
+            }
+            TokenKind::Dollar if self.peek_nth(1).kind == TokenKind::CloseBracket => {
+                self.next();
+                Part::Last
+            }
+            TokenKind::Int(i) if self.peek_nth(1).kind == TokenKind::CloseBracket => {
+                self.next();
+                Part::Index(i.into())
+            }
+            TokenKind::Question => {
+                self.next();
+                Part::Where(self.parse_value()?)
+            }
+            _ if self.eat_keyword("WHERE") => Part::Where(self.parse_value()?),
+            _ => Part::Value(self.parse_value()?),
+        })
+    }
+
+    fn parse_recurse(&mut self) -> Result<Recurse, Error> {
+        let token = self.peek().clone();
+        let bound = |parser: &mut Self| match parser.peek_kind() {
+            TokenKind::Int(_) => parser.parse_usize().map(Some),
+            _ => Ok(None),
+        };
+        let min = bound(self)?;
+        let max = match self.eat(&TokenKind::Dot) {
+            true => {
+                self.expect(TokenKind::Dot)?;
+                bound(self)?
+            }
+            false if min.is_some() => min,
+            false => return Err(self.unexpected("a depth or '..'")),
+        };
+        let collect = match self.eat(&TokenKind::Plus) {
+            true if self.eat_keyword("PATH") => Collect::Paths,
+            true if self.eat_keyword("COLLECT") => Collect::Nodes,
+            true => return Err(self.unexpected("PATH or COLLECT")),
+            false => Collect::Nodes,
+        };
+        self.expect(TokenKind::CloseBrace)?;
+        let min = u32::try_from(min.unwrap_or(1));
+        match (min, max.map(u32::try_from).transpose()) {
+            (Ok(min), Ok(max)) if max.is_none_or(|max| max >= min.max(1)) => {
+                Ok(Recurse { min, max, collect })
+            }
+            _ => Err(self.error(&token, String::from("Invalid recursion depth"))),
+        }
+    }
+
+    fn parse_path(&mut self) -> Result<Path, Error> {
+        let dir = match self.next().kind {
+            TokenKind::ArrowIn => Direction::In,
+            TokenKind::ArrowOut => Direction::Out,
+            TokenKind::ArrowBoth => Direction::Both,
+            _ => unreachable!(),
+        };
+        if self.eat(&TokenKind::OpenParen) {
+            let to = Table(self.parse_ident()?);
+            let filter = match self.eat_keyword("WHERE") {
+                true => Some(Condition(self.parse_value()?)),
+                false => None,
+            };
+            self.expect(TokenKind::CloseParen)?;
+            return Ok(Path::new(dir, to, filter));
+        }
+        let to = Table(self.parse_ident()?);
+        // The filter may also follow the table, as in `->knows(WHERE since > 2020)`
+        let filtered = self.peek_kind() == &TokenKind::OpenParen
+            && self.is_adjacent()
+            && self.peek_nth(1).is_keyword("WHERE");
+        if !filtered {
+            return Ok(Path::new(dir, to, None));
+        }
+        self.next();
+        self.next();
+        let filter = Condition(self.parse_value()?);
+        self.expect(TokenKind::CloseParen)?;
+        Ok(Path::new(dir, to, Some(filter)))
+    }
+
+    pub fn parse_primary(&mut self) -> Result<Value, Error> {
+        let token = self.peek().clone();
+        Ok(match token.kind {
+            TokenKind::Int(v) => {
+                self.next();
+                Value::Number(v.into())
+            }
+            TokenKind::Float(v) => {
+                self.next();
+                Value::Number(v.into())
+            }
+            TokenKind::Strand(v) => {
+                self.next();
+                v.into()
+            }
+            TokenKind::OpenParen => {
+                self.next();
+                let value = self.parse_value()?;
+                self.expect(TokenKind::CloseParen)?;
+                value
+            }
+            TokenKind::Dollar => {
+                self.next();
+                if !self.is_adjacent() {
+                    return Err(self.unexpected("a param name"));
+                }
+                Value::Param(self.parse_ident()?.into())
+            }
+            TokenKind::OpenBracket => Value::Array(self.parse_array()?),
+            TokenKind::OpenBrace => Value::Object(self.parse_object()?),
+            TokenKind::Ident(_) if self.eat_keyword("TRUE") => Value::Bool(true),
+            TokenKind::Ident(_) if self.eat_keyword("FALSE") => Value::Bool(false),
+            TokenKind::Ident(_) if self.eat_keyword("NONE") => Value::None,
+            TokenKind::Ident(_) if self.eat_keyword("NULL") => Value::Null,
+            TokenKind::Ident(_) if self.is_keyword("u") => Value::Uuid(self.parse_uuid()?.into()),
+            TokenKind::Ident(_)
+                if self.is_keyword("d")
+                    && matches!(self.peek_nth(1).kind, TokenKind::Strand(_)) =>
+            {
+                Value::Datetime(self.parse_datetime()?)
+            }
+            TokenKind::Ident(_) if self.is_function_start() => self.parse_function()?.into(),
+            TokenKind::Ident(_) if self.is_record_start() => {
+                Value::Record(Box::new(self.parse_record()?))
+            }
+            _ => return Err(self.unexpected("a value")),
+        })
+    }
+
+    pub fn parse_array(&mut self) -> Result<Array, Error> {
+        self.expect(TokenKind::OpenBracket)?;
+        let mut array = Array::new();
+        while !self.eat(&TokenKind::CloseBracket) {
+            array.push(self.parse_value()?);
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(TokenKind::CloseBracket)?;
+                break;
+            }
+        }
+        Ok(array)
+    }
+
+    pub fn parse_object(&mut self) -> Result<Object, Error> {
+        self.expect(TokenKind::OpenBrace)?;
+        let mut object = BTreeMap::new();
+        while !self.eat(&TokenKind::CloseBrace) {
+            let key = match self.peek_kind() {
+                TokenKind::Ident(_) => self.parse_ident()?,
+                TokenKind::Strand(_) | TokenKind::Int(_) => match self.next().kind {
+                    TokenKind::Strand(v) => v,
+                    TokenKind::Int(v) => v.to_string(),
+                    _ => unreachable!(),
+                },
+                _ => return Err(self.unexpected("an object key")),
+            };
+            self.expect(TokenKind::Colon)?;
+            object.insert(key.into(), self.parse_value()?);
+            if !self.eat(&TokenKind::Comma) {
+                self.expect(TokenKind::CloseBrace)?;
+                break;
+            }
+        }
+        Ok(Object(object))
+    }
+
+    fn parse_uuid(&mut self) -> Result<uuid::Uuid, Error> {
+        self.next();
+        let token = self.next();
+        let TokenKind::Strand(ref v) = token.kind else {
+            return Err(self.error(
+                &token,
+                format!("Unexpected {}, expected a uuid", token.kind),
+            ));
+        };
+        uuid::Uuid::try_parse(v).map_err(|_| self.error(&token, format!("Invalid uuid '{v}'")))
+    }
+
+    fn parse_datetime(&mut self) -> Result<Datetime, Error> {
+        self.next();
+        let token = self.next();
+        let TokenKind::Strand(ref v) = token.kind else {
+            unreachable!()
+        };
+        Datetime::parse(v).ok_or_else(|| self.error(&token, format!("Invalid datetime '{v}'")))
+    }
+
+    pub fn parse_record(&mut self) -> Result<Record, Error> {
+        let table = self.parse_ident()?;
+        self.expect(TokenKind::Colon)?;
+        if !self.is_adjacent() {
+            return Err(self.unexpected("a record id"));
+        }
+        Ok(Record::new(table, self.parse_id()?))
+    }
+
+    fn parse_id(&mut self) -> Result<Id, Error> {
+        Ok(match self.peek_kind().clone() {
+            TokenKind::Int(v) => {
+                self.next();
+                Id::Number(v)
+            }
+            TokenKind::Minus => {
+                let minus = self.next();
+                match self.next().kind {
+                    TokenKind::Int(v) => Id::Number(-v),
+                    kind => {
+                        let message = format!("Unexpected {kind}, expected a record id");
+                        return Err(self.error(&minus, message));
+                    }
+                }
+            }
+            TokenKind::Ident(_)
+                if self.is_keyword("u")
+                    && matches!(self.peek_nth(1).kind, TokenKind::Strand(_)) =>
+            {
+                Id::Uuid(self.parse_uuid()?)
+            }
+            TokenKind::Ident(_) => Id::String(self.parse_ident()?),
+            TokenKind::Strand(_) => match self.next().kind {
+                TokenKind::Strand(v) => Id::String(v),
+                _ => unreachable!(),
+            },
+            TokenKind::OpenBracket => Id::Array(self.parse_array()?),
+            TokenKind::OpenBrace => Id::Object(self.parse_object()?),
+            _ => return Err(self.unexpected("a record id")),
+        })
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    dbs::graph::Graph,
+    doc::document::Cursor,
+    err::Error,
+    ql::{idiom::Idiom, operator::Operator, value::Value},
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Data {
+    Set(Vec<(Idiom, Operator, Value)>),
+    Content(Value),
+    Merge(Value),
+    Replace(Value),
+    Patch(Value),
+}
+
+impl Data {
+    pub async fn compute(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        cur: Option<&Cursor>,
+        doc: &mut Value,
+    ) -> Result<(), Error> {
+        match self {
+            Data::Set(set) => {
+                // Every assignment sees the document the ones before it left
+                let mut cursor = cur.cloned().unwrap_or_else(|| Cursor::from(Value::None));
+                for (idiom, op, val) in set {
+                    cursor.value = doc.clone();
+                    let cur = Some(&cursor);
+                    let val = stk.run(|stk| val.evaluate(stk, graph, cur)).await?;
+                    let val = match op {
+                        Operator::Eq => val,
+                        Operator::Add | Operator::Sub => {
+                            let prev = stk.run(|stk| doc.get(stk, graph, cur, idiom)).await?;
+                            match op {
+                                Operator::Add => prev.try_inc(val)?,
+                                _ => prev.try_dec(val)?,
+                            }
+                        }
+                        op => return Err(Error::InvalidOperator(op.clone())),
+                    };
+                    doc.set(idiom, val)?;
+                }
+            }
+            Data::Merge(val) => {
+                let val = stk.run(|stk| val.evaluate(stk, graph, cur)).await?;
+                doc.merge(val)?;
+            }
+            Data::Patch(val) => {
+                let val = stk.run(|stk| val.evaluate(stk, graph, cur)).await?;
+                doc.patch(val)?;
+            }
+            Data::Content(val) | Data::Replace(val) => {
+                match stk.run(|stk| val.evaluate(stk, graph, cur)).await? {
+                    val @ Value::Object(_) => *doc = val,
+                    val => {
+                        return Err(Error::IncorrectValueType {
+                            expected: String::from("Object"),
+                            result: val,
+                        })
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        dbs::executor::Executor,
+        ql::{ident::Ident, object::Object, part::Part},
+    };
+    use actix::Actor;
+    use reblessive::TreeStack;
+    use std::collections::BTreeMap;
+
+    #[actix_rt::test]
+    async fn set_in_order_test() {
+        let graph = Graph::new().start();
+        let field = |name: &str| Idiom(vec![Part::Field(Ident::new(name))]);
+        let data = Data::Set(vec![
+            (field("age"), Operator::Add, 1.into()),
+            (field("y"), Operator::Eq, Value::Idiom(field("age"))),
+        ]);
+        let mut doc = Value::Object(Object(BTreeMap::from([("age".into(), 20.into())])));
+        let mut stack = TreeStack::new();
+        stack
+            .enter(|stk| data.compute(stk, &graph, None, &mut doc))
+            .finish()
+            .await
+            .unwrap();
+        let correct = BTreeMap::from([("age".into(), 21.into()), ("y".into(), 21.into())]);
+        assert_eq!(doc, Value::Object(Object(correct)));
+    }
+
+    #[actix_rt::test]
+    async fn update_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET age = 20, tags = ['a', 'b'], meta.x = 1;
+                CREATE person:2 SET age = 40",
+            )
+            .await;
+        let set = executor
+            .run("UPDATE person SET age += 1, tags -= 'a', meta.y = age WHERE age < 30")
+            .await;
+        let person = serde_json::json!({"id": "person:1", "age": 21, "tags": ["b"], "meta": {"x": 1, "y": 21}});
+        assert_eq!(set, Ok(serde_json::json!([person])));
+        let merged = executor.run("UPDATE person:2 MERGE { meta: { z: 1 } } RETURN BEFORE");
+        let before = serde_json::json!([{"id": "person:2", "age": 40}]);
+        assert_eq!(merged.await, Ok(before));
+        let patched = executor
+            .run("UPDATE person:2 PATCH [{ op: 'replace', path: '/age', value: 50 }] RETURN DIFF")
+            .await;
+        let diff = serde_json::json!([[{"op": "replace", "path": "/age", "value": 50}]]);
+        assert_eq!(patched, Ok(diff));
+        let replaced = executor.run("UPDATE person:1 REPLACE { name: 'a' } RETURN NONE");
+        assert_eq!(replaced.await, Ok(serde_json::json!([])));
+        let people = executor.run("SELECT * FROM person ORDER BY id").await;
+        let people_after = serde_json::json!([
+            {"id": "person:1", "name": "a"},
+            {"id": "person:2", "age": 50, "meta": {"z": 1}},
+        ]);
+        assert_eq!(people, Ok(people_after));
+    }
+}
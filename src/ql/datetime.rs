@@ -0,0 +1,181 @@
+use crate::dbs::version;
+use std::fmt;
+
+const NANOS: i64 = 1_000_000_000;
+
+/// A point in time, in nanoseconds since the unix epoch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Hash, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct Datetime(pub i64);
+
+impl Datetime {
+    pub fn now() -> Self {
+        Datetime(version::now() as i64)
+    }
+
+    /// Parses an RFC 3339 datetime such as `2026-01-01T00:00:00Z`, the time
+    /// and offset are optional. Datetimes beyond the range nanoseconds since
+    /// the epoch can hold, roughly 1677 to 2262, do not parse.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (date, rest) = text.split_at_checked(10)?;
+        let [year, month, day] = fields(date, '-')?;
+        if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+            return None;
+        }
+        let mut nanos = seconds(days_from_civil(year, month, day), 86_400)?;
+        let Some(rest) = rest.strip_prefix(['T', 't', ' ']) else {
+            return rest.is_empty().then_some(Datetime(nanos));
+        };
+
+        let (time, mut rest) = rest.split_at_checked(8)?;
+        let [hour, minute, second] = fields(time, ':')?;
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        nanos = nanos.checked_add((hour * 3600 + minute * 60 + second) * NANOS)?;
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let digits = fraction.len()
+                - fraction
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .len();
+            if digits == 0 || digits > 9 {
+                return None;
+            }
+            let fraction = fraction[..digits].parse::<i64>().ok()? * 10_i64.pow(9 - digits as u32);
+            nanos = nanos.checked_add(fraction)?;
+            rest = &rest[1 + digits..];
+        }
+        let offset = match rest {
+            "Z" | "z" => 0,
+            _ => {
+                let sign = match rest.get(..1)? {
+                    "+" => 1,
+                    "-" => -1,
+                    _ => return None,
+                };
+                let [hours, minutes] = fields(rest.get(1..)?, ':')?;
+                let offset = hours
+                    .checked_mul(3600)?
+                    .checked_add(minutes.checked_mul(60)?)?;
+                sign * offset
+            }
+        };
+        Some(Datetime(nanos.checked_sub(seconds(offset, 1)?)?))
+    }
+
+    pub fn to_rfc3339(self) -> String {
+        let seconds = self.0.div_euclid(NANOS);
+        let nanos = self.0.rem_euclid(NANOS);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let time = seconds.rem_euclid(86_400);
+        let mut text = format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+            time / 3600,
+            time % 3600 / 60,
+            time % 60
+        );
+        if nanos > 0 {
+            let fraction = format!("{nanos:09}");
+            text.push('.');
+            text.push_str(fraction.trim_end_matches('0'));
+        }
+        text.push('Z');
+        text
+    }
+}
+
+impl fmt::Display for Datetime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "d'{}'", self.to_rfc3339())
+    }
+}
+
+/// Nanoseconds in `count` spans of `unit` seconds, unset on overflow
+fn seconds(count: i64, unit: i64) -> Option<i64> {
+    count.checked_mul(unit)?.checked_mul(NANOS)
+}
+
+/// Splits fixed width numbers such as `2026-01-01` or `00:00:00`
+fn fields<const N: usize>(text: &str, separator: char) -> Option<[i64; N]> {
+    let mut fields = [0; N];
+    let mut parts = text.split(separator);
+    for field in fields.iter_mut() {
+        let part = parts.next()?;
+        if part.len() < 2 || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        *field = part.parse().ok()?;
+    }
+    parts.next().is_none().then_some(fields)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since the epoch of a date in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_datetime_test() {
+        let epoch = Datetime::parse("1970-01-01T00:00:00Z").unwrap();
+        assert_eq!(epoch, Datetime(0));
+        let date = Datetime::parse("2026-01-01").unwrap();
+        assert_eq!(date.0, 1_767_225_600 * NANOS);
+        assert_eq!(date.to_rfc3339(), "2026-01-01T00:00:00Z");
+        let offset = Datetime::parse("2026-01-01T02:30:00.5+02:00").unwrap();
+        assert_eq!(offset.to_rfc3339(), "2026-01-01T00:30:00.5Z");
+        let before = Datetime::parse("1969-12-31T23:59:59.999Z").unwrap();
+        assert_eq!(before.to_rfc3339(), "1969-12-31T23:59:59.999Z");
+        assert_eq!(
+            Datetime::parse("2024-02-29T00:00:00Z").unwrap().to_string(),
+            "d'2024-02-29T00:00:00Z'"
+        );
+        assert!(Datetime::parse("2023-02-29T00:00:00Z").is_none());
+        assert!(Datetime::parse("2026-01-01T00:00:00").is_none());
+        assert!(Datetime::parse("2026-1-01").is_none());
+    }
+
+    #[test]
+    fn parse_datetime_range_test() {
+        let before = Datetime::parse("1900-01-01T00:00:00Z").unwrap();
+        assert_eq!(before.0, -2_208_988_800 * NANOS);
+        assert_eq!(before.to_rfc3339(), "1900-01-01T00:00:00Z");
+        let last = Datetime::parse("2262-04-11T23:47:16.854775807Z").unwrap();
+        assert_eq!(last, Datetime(i64::MAX));
+        assert!(Datetime::parse("2262-04-11T23:47:16.854775808Z").is_none());
+        assert!(Datetime::parse("3000-01-01").is_none());
+        assert!(Datetime::parse("3000-01-01T00:00:00Z").is_none());
+        assert!(Datetime::parse("1000-01-01T00:00:00Z").is_none());
+        assert!(Datetime::parse("2026-01-01T00:00:00+9999999999999999:00").is_none());
+    }
+}
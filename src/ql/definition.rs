@@ -0,0 +1,29 @@
+use crate::ql::{idiom::Idiom, kind::Kind, value::Value};
+
+/// How the values of a field are checked and filled in on every write to
+/// its table
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub field: Idiom,
+    pub kind: Option<Kind>,
+    pub default: Option<Value>,
+    pub assert: Option<Value>,
+    /// Computed again on every write, replacing what was written
+    pub value: Option<Value>,
+    /// Whether the field keeps the value it was created with
+    pub readonly: bool,
+}
+
+impl Definition {
+    pub fn new(field: Idiom) -> Self {
+        Definition {
+            field,
+            kind: None,
+            default: None,
+            assert: None,
+            value: None,
+            readonly: false,
+        }
+    }
+}
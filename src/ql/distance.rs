@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How far apart two vectors are, used by vector indexes and the KNN operator
+#[non_exhaustive]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Distance {
+    Cosine,
+    #[default]
+    Euclidean,
+    Manhattan,
+}
+
+impl Distance {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "cosine" => Distance::Cosine,
+            "euclidean" => Distance::Euclidean,
+            "manhattan" => Distance::Manhattan,
+            _ => return None,
+        })
+    }
+
+    pub fn compute(&self, a: &[f64], b: &[f64]) -> f64 {
+        match self {
+            Distance::Cosine => 1.0 - cosine(a, b),
+            Distance::Euclidean => euclidean(a, b),
+            Distance::Manhattan => manhattan(a, b),
+        }
+    }
+}
+
+impl fmt::Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Distance::Cosine => write!(f, "COSINE"),
+            Distance::Euclidean => write!(f, "EUCLIDEAN"),
+            Distance::Manhattan => write!(f, "MANHATTAN"),
+        }
+    }
+}
+
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+pub fn magnitude(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// The cosine of the angle between two vectors, zero when either is empty
+pub fn cosine(a: &[f64], b: &[f64]) -> f64 {
+    let norm = magnitude(a) * magnitude(b);
+    match norm == 0.0 {
+        true => 0.0,
+        false => dot(a, b) / norm,
+    }
+}
+
+pub fn euclidean(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+pub fn manhattan(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum()
+}
+
+pub fn chebyshev(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
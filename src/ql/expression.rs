@@ -2,6 +2,7 @@ use crate::{
     dbs::graph::Graph,
     doc::document::Cursor,
     err::Error,
+    idx::analyzer::Analyzer,
     ql::{operator::Operator, traits::Incoperate, value::Value},
 };
 use actix::Addr;
@@ -62,6 +63,10 @@ impl Expression {
                     Operator::Gt => Value::Bool(left.gt(&right)),
                     Operator::LtEq => Value::Bool(left.le(&right)),
                     Operator::GtEq => Value::Bool(left.ge(&right)),
+                    Operator::Matches(_) => Value::Bool(matches(&left, &right)),
+                    // The nearest neighbours are picked by the table, a KNN
+                    // operator left in a condition was not planned
+                    Operator::Knn(..) => return Err(Error::UnplannedKnn(op.clone())),
                     Operator::Add => left.try_add(right)?,
                     Operator::Sub => left.try_sub(right)?,
                     Operator::Mult => left.try_mul(right)?,
@@ -73,6 +78,17 @@ impl Expression {
     }
 }
 
+/// Matches text no search index answered for, using the default analyzer
+fn matches(text: &Value, query: &Value) -> bool {
+    match (text, query) {
+        (Value::String(text), Value::String(query)) => {
+            Analyzer::default().matches(&text.0, &query.0)
+        }
+        (Value::Array(texts), query) => texts.iter().any(|text| matches(text, query)),
+        _ => false,
+    }
+}
+
 impl Incoperate for Expression {
     fn incorperate(&self, fields: &BTreeMap<Arc<str>, Value>) -> Expression {
         match self.to_owned() {
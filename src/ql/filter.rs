@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Filter {
+    Ascii,
+    EdgeNgram(u16, u16),
+    Lowercase,
+    Snowball(Language),
+    Uppercase,
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::Ascii => write!(f, "ascii"),
+            Filter::EdgeNgram(min, max) => write!(f, "edgengram({min},{max})"),
+            Filter::Lowercase => write!(f, "lowercase"),
+            Filter::Snowball(lang) => write!(f, "snowball({lang})"),
+            Filter::Uppercase => write!(f, "uppercase"),
+        }
+    }
+}
+
+/// The languages snowball stemming is available in
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    Arabic,
+    Danish,
+    Dutch,
+    English,
+    French,
+    German,
+    Greek,
+    Hungarian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+}
+
+impl Language {
+    const ALL: [(&'static str, Language); 17] = [
+        ("arabic", Language::Arabic),
+        ("danish", Language::Danish),
+        ("dutch", Language::Dutch),
+        ("english", Language::English),
+        ("french", Language::French),
+        ("german", Language::German),
+        ("greek", Language::Greek),
+        ("hungarian", Language::Hungarian),
+        ("italian", Language::Italian),
+        ("norwegian", Language::Norwegian),
+        ("portuguese", Language::Portuguese),
+        ("romanian", Language::Romanian),
+        ("russian", Language::Russian),
+        ("spanish", Language::Spanish),
+        ("swedish", Language::Swedish),
+        ("tamil", Language::Tamil),
+        ("turkish", Language::Turkish),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Language::ALL
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, lang)| *lang)
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, _) = Language::ALL
+            .iter()
+            .find(|(_, lang)| lang == self)
+            .expect("every language is listed");
+        write!(f, "{name}")
+    }
+}
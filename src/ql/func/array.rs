@@ -0,0 +1,46 @@
+use crate::{
+    err::Error,
+    ql::{array::Array, value::Value},
+};
+
+fn array(args: Vec<Value>, name: &str) -> Result<Vec<Value>, Error> {
+    match args.into_iter().next() {
+        Some(Value::Array(Array(v))) => Ok(v),
+        _ => Err(Error::InvalidArguments(format!("{name} expects an array"))),
+    }
+}
+
+pub fn distinct(args: Vec<Value>) -> Result<Value, Error> {
+    let mut distinct: Vec<Value> = Vec::new();
+    for val in array(args, "array::distinct")? {
+        if !distinct.contains(&val) {
+            distinct.push(val);
+        }
+    }
+    Ok(distinct.into())
+}
+
+pub fn group(args: Vec<Value>) -> Result<Value, Error> {
+    let values = Value::from(array(args, "array::group")?).flatten();
+    distinct(vec![values])
+}
+
+pub fn len(args: Vec<Value>) -> Result<Value, Error> {
+    Ok((array(args, "array::len")?.len() as i64).into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn group_test() {
+        let args = vec![Value::from(vec![
+            Value::from(vec![Value::from(1), 2.into()]),
+            Value::from(vec![Value::from(2), 3.into()]),
+            1.into(),
+        ])];
+        let correct = Value::from(vec![Value::from(1), 2.into(), 3.into()]);
+        assert_eq!(group(args).unwrap(), correct);
+    }
+}
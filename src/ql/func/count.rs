@@ -0,0 +1,12 @@
+use crate::{
+    err::Error,
+    ql::{array::Array, value::Value},
+};
+
+pub fn count(args: Vec<Value>) -> Result<Value, Error> {
+    Ok(match args.into_iter().next() {
+        None => 1.into(),
+        Some(Value::Array(Array(v))) => (v.iter().filter(|v| v.is_truthy()).count() as i64).into(),
+        Some(v) => (v.is_truthy() as i64).into(),
+    })
+}
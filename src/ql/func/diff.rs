@@ -0,0 +1,89 @@
+use crate::ql::value::Value;
+use std::collections::BTreeMap;
+
+impl Value {
+    pub fn diff(&self, val: &Value) -> Value {
+        let mut ops = Vec::new();
+        diff(self, val, String::new(), &mut ops);
+        ops.into()
+    }
+}
+
+fn diff(before: &Value, after: &Value, path: String, ops: &mut Vec<Value>) {
+    match (before, after) {
+        (before, after) if before == after => (),
+        (Value::Object(before), Value::Object(after)) => {
+            for (key, val) in before.iter() {
+                let path = format!("{path}/{}", escape(key));
+                match after.0.get(key) {
+                    Some(after) => diff(val, after, path, ops),
+                    None => ops.push(op("remove", path, None)),
+                }
+            }
+            for (key, val) in after.iter() {
+                if !before.contains_key(key) {
+                    let path = format!("{path}/{}", escape(key));
+                    ops.push(op("add", path, Some(val.clone())));
+                }
+            }
+        }
+        (Value::Array(before), Value::Array(after)) => {
+            let len = before.len().min(after.len());
+            for i in 0..len {
+                diff(&before[i], &after[i], format!("{path}/{i}"), ops);
+            }
+            for (i, val) in after.iter().enumerate().skip(len) {
+                ops.push(op("add", format!("{path}/{i}"), Some(val.clone())));
+            }
+            for i in (len..before.len()).rev() {
+                ops.push(op("remove", format!("{path}/{i}"), None));
+            }
+        }
+        (_, after) => ops.push(op("replace", path, Some(after.clone()))),
+    }
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn op(op: &str, path: String, value: Option<Value>) -> Value {
+    let mut op = BTreeMap::from([
+        ("op".to_string(), op.into()),
+        ("path".to_string(), path.into()),
+    ]);
+    if let Some(value) = value {
+        op.insert("value".to_string(), value);
+    }
+    op.into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diff_patch_test() {
+        let before = Value::from(BTreeMap::from([
+            ("a".to_string(), 1.into()),
+            (
+                "b".to_string(),
+                vec![Value::from(1), 2.into(), 3.into()].into(),
+            ),
+            ("c".to_string(), "x".into()),
+        ]));
+        let after = Value::from(BTreeMap::from([
+            ("a".to_string(), 2.into()),
+            ("b".to_string(), vec![Value::from(1)].into()),
+            ("d/e".to_string(), true.into()),
+        ]));
+        let ops = before.diff(&after);
+        let Value::Array(array) = &ops else {
+            panic!("diff did not return an array");
+        };
+        assert_eq!(array.len(), 5);
+        let mut doc = before.clone();
+        doc.patch(ops).unwrap();
+        assert_eq!(doc, after);
+    }
+}
@@ -2,9 +2,13 @@ use crate::{
     dbs::{
         entity::Entity,
         graph::Graph,
-        ops::{get::Get, retrieve::Retrieve, walk::Walk},
+        ops::{
+            get::Get,
+            retrieve::Retrieve,
+            walk::{self, Walk},
+        },
     },
-    doc::document::Cursor,
+    doc::document::{self, Cursor},
     err::Error,
     ql::{
         array::Array,
@@ -12,12 +16,15 @@ use crate::{
         ident::Ident,
         idiom::Idiom,
         part::{Next, ParseWalk, Part, Skip},
+        path::Path,
+        recurse::Collect,
         value::Value,
     },
     resp::Response,
 };
 use actix::Addr;
 use reblessive::tree::Stk;
+use std::collections::BTreeMap;
 
 impl Value {
     pub async fn get(
@@ -29,21 +36,64 @@ impl Value {
     ) -> Result<Value, Error> {
         match path.first() {
             Some(p) => match self {
-                Value::Object(_v) => todo!(),
-                Value::Edge(_v) => todo!(),
+                Value::Object(v) => match p {
+                    Part::Field(Ident(field)) => {
+                        let v = v.get(field);
+                        stk.run(|stk| v.get(stk, graph, cur, path.next())).await
+                    }
+                    Part::All => {
+                        let path = path.next();
+                        let mut mapped = BTreeMap::new();
+                        for (k, v) in v.iter() {
+                            let val = stk.run(|stk| v.get(stk, graph, cur, path)).await?;
+                            mapped.insert(k.clone(), val);
+                        }
+                        Ok(mapped.into())
+                    }
+                    Part::Value(x) => match stk.run(|stk| x.evaluate(stk, graph, cur)).await? {
+                        Value::String(field) => {
+                            let v = v.get(&field.as_str().into());
+                            stk.run(|stk| v.get(stk, graph, cur, path.next())).await
+                        }
+                        _ => Ok(Value::None),
+                    },
+                    Part::Where(w) => {
+                        let doc = Cursor::from(self.clone()).with_txn(document::txn(cur));
+                        match stk.run(|stk| w.evaluate(stk, graph, Some(&doc))).await?.is_truthy() {
+                            true => stk.run(|stk| self.get(stk, graph, cur, path.next())).await,
+                            false => Ok(Value::None),
+                        }
+                    }
+                    Part::Flatten => stk.run(|stk| self.get(stk, graph, cur, path.next())).await,
+                    // Paths from a document start at the record it holds
+                    Part::Path(_) | Part::Recurse(_) => match v.get(&"id".into()) {
+                        id @ Value::Record(_) => stk.run(|stk| id.get(stk, graph, cur, path)).await,
+                        _ => Ok(Value::None),
+                    },
+                    _ => Ok(Value::None),
+                },
+                Value::Edge(v) => {
+                    let mut parts = vec![Part::Path(Path::new(v.dir.clone(), v.to.clone(), None))];
+                    parts.extend_from_slice(path);
+                    let from = Value::Record(Box::new(v.from.clone()));
+                    stk.run(|stk| from.get(stk, graph, cur, &parts)).await
+                }
                 Value::Record(v) => {
                     let val = v.clone();
                     if path.len() == 0 {
                         return Ok(Value::Record(val));
                     }
+                    let txn = document::txn(cur);
                     let response = graph.send(Retrieve::Record(*val.clone())).await.unwrap();
                     let Response::Node(node) = response else {
                         return Ok(Value::None);
                     };
                     match p {
                         Part::Path(_) => {
-                            let (get, walk, path) = path.parse_walk();
-                            let response = node.send(Walk::new(walk, *val)).await.unwrap()?;
+                            let (fields, walk, path) = path.parse_walk();
+                            let get = Get::new(fields, None, graph.clone()).with_txn(txn);
+                            let walk = Walk::new(walk, *val, graph.clone()).with_txn(txn);
+                            let response = node.send(walk).await.unwrap()?;
                             let nodes: Vec<Addr<Entity>> = response.try_into()?;
 
                             let mut array = Array::with_capacity(nodes.len());
@@ -58,30 +108,57 @@ impl Value {
                             }
                             return Ok(Value::Array(array));
                         }
-                        _ => {
-                            todo!();
-                        }
-                        /* _ => {
-                            let (get, walk, path) = path.parse_walk();
-                            let response = node.send(Walk::new(walk, *val)).await.unwrap()?;
-                            let nodes: Vec<Addr<Entity>> = response.try_into()?;
+                        Part::Recurse(recurse) => {
+                            let path = path.next();
+                            let (fields, hops, path) = path.parse_walk();
+                            if hops.is_empty() {
+                                return Ok(Value::None);
+                            }
+                            let get = Get::new(fields, None, graph.clone()).with_txn(txn);
+                            let paths =
+                                walk::recurse(graph, &txn, node, *val, hops, recurse).await?;
 
-                            let mut array = Array::with_capacity(nodes.len());
-                            for node in nodes {
-                                let response = node.send(get.clone()).await.unwrap()?;
-                                match response {
-                                    Response::Value(v) => array
-                                        .push(stk.run(|stk| v.get(stk, graph, cur, path)).await?),
-                                    Response::None => array.push(Value::None),
-                                    _ => unreachable!(),
+                            let mut array = Array::with_capacity(paths.len());
+                            for nodes in paths {
+                                let nodes = match recurse.collect {
+                                    Collect::Nodes => &nodes[nodes.len() - 1..],
+                                    Collect::Paths => &nodes[..],
+                                };
+                                let mut values = Array::with_capacity(nodes.len());
+                                for node in nodes {
+                                    let response = node.send(get.clone()).await.unwrap()?;
+                                    match response {
+                                        Response::Value(v) => values.push(
+                                            stk.run(|stk| v.get(stk, graph, cur, path)).await?,
+                                        ),
+                                        Response::None => values.push(Value::None),
+                                        _ => unreachable!(),
+                                    }
+                                }
+                                match recurse.collect {
+                                    Collect::Nodes => array.append(&mut values),
+                                    Collect::Paths => array.push(Value::Array(values)),
                                 }
                             }
-                            return Ok(Value::Array(array));
-                        } */
+                            Ok(Value::Array(array))
+                        }
+                        // Any other part reads the fields of the linked record
+                        _ => {
+                            let get = Get::new(Field::WildCard.into(), None, graph.clone())
+                                .with_txn(txn);
+                            match node.send(get).await.unwrap()? {
+                                Response::Value(v) => stk.run(|stk| v.get(stk, graph, cur, path)).await,
+                                _ => Ok(Value::None),
+                            }
+                        }
                     }
                 }
                 Value::Array(v) => match p {
-                    Part::All | Part::Flatten => {
+                    Part::Flatten => {
+                        let v = self.clone().flatten();
+                        stk.run(|stk| v.get(stk, graph, cur, path.next())).await
+                    }
+                    Part::All => {
                         let path = path.next();
                         let mut mapped = Array::with_capacity(v.len());
                         for v in v.iter() {
@@ -116,8 +193,9 @@ impl Value {
                     },
                     Part::Where(w) => {
                         let mut array = Array::with_capacity(v.len());
+                        let txn = document::txn(cur);
                         for v in v.iter() {
-                            let cur = v.clone().into();
+                            let cur = Cursor::from(v.clone()).with_txn(txn);
                             if stk
                                 .run(|stk| w.evaluate(stk, graph, Some(&cur)))
                                 .await?
@@ -0,0 +1,672 @@
+use crate::{
+    dbs::{
+        entity::Entity,
+        graph::Graph,
+        ops::{get::Get, retrieve::Retrieve, walk::Walk},
+        transaction::Txn,
+    },
+    doc::document::{self, Cursor},
+    err::Error,
+    ql::{
+        condition::Condition,
+        direction::Direction,
+        fields::{Field, Fields},
+        function::Function,
+        number::Number,
+        object::Object,
+        path::Path,
+        record::Record,
+        table::Table,
+        value::Value,
+    },
+    resp::Response,
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, VecDeque},
+    sync::Arc,
+};
+
+/// How a path search moves between records
+struct Route {
+    tables: Vec<String>,
+    dir: Direction,
+    weight: Option<Arc<str>>,
+    depth: Option<usize>,
+    filter: Option<Condition>,
+    txn: Txn,
+}
+
+/// A record waiting to be settled, ordered so the cheapest pops first
+struct Step {
+    cost: f64,
+    hops: usize,
+    id: Record,
+}
+
+impl PartialEq for Step {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Step {}
+
+impl PartialOrd for Step {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Step {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.hops.cmp(&self.hops))
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+/// `graph::shortest_path(from, to, tables, direction [, options])`
+pub async fn shortest_path(
+    stk: &mut Stk,
+    graph: &Addr<Graph>,
+    cur: Option<&Cursor>,
+    func: &Function,
+) -> Result<Value, Error> {
+    path(stk, graph, cur, func, false).await
+}
+
+/// `graph::weighted_path(from, to, tables, direction, field [, options])`
+pub async fn weighted_path(
+    stk: &mut Stk,
+    graph: &Addr<Graph>,
+    cur: Option<&Cursor>,
+    func: &Function,
+) -> Result<Value, Error> {
+    path(stk, graph, cur, func, true).await
+}
+
+async fn path(
+    stk: &mut Stk,
+    graph: &Addr<Graph>,
+    cur: Option<&Cursor>,
+    func: &Function,
+    weighted: bool,
+) -> Result<Value, Error> {
+    let name = func.name.as_str();
+    let invalid = |expected: &str| Error::InvalidArguments(format!("{name} expects {expected}"));
+    let required = if weighted { 5 } else { 4 };
+    if func.args.len() < required || func.args.len() > required + 1 {
+        return Err(invalid(match weighted {
+            true => "a start, an end, edge tables, a direction, a weight field and options",
+            false => "a start, an end, edge tables, a direction and options",
+        }));
+    }
+
+    let mut args = Vec::with_capacity(required);
+    for arg in func.args[..required].iter() {
+        args.push(stk.run(|stk| arg.evaluate(stk, graph, cur)).await?);
+    }
+    let mut args = args.into_iter();
+    let (Some(Value::Record(from)), Some(Value::Record(to))) = (args.next(), args.next()) else {
+        return Err(invalid("records to find a path between"));
+    };
+    let tables = match args.next() {
+        Some(Value::Array(tables)) => tables.iter().map(table).collect(),
+        Some(value) => table(&value).map(|table| vec![table]),
+        None => None,
+    };
+    let Some(tables) = tables.filter(|tables| !tables.is_empty()) else {
+        return Err(invalid("edge tables as strings"));
+    };
+    let dir = match args.next() {
+        Some(Value::String(dir)) => match dir.as_str() {
+            "->" => Direction::In,
+            "<-" => Direction::Out,
+            "<->" => Direction::Both,
+            _ => return Err(invalid("a direction of '->', '<-' or '<->'")),
+        },
+        _ => return Err(invalid("a direction of '->', '<-' or '<->'")),
+    };
+    let weight = match args.next() {
+        Some(Value::String(field)) => Some(Arc::from(field.as_str())),
+        Some(_) => return Err(invalid("the name of a numeric edge field")),
+        None => None,
+    };
+
+    // The filter is checked against each edge, so it stays unevaluated
+    let (mut depth, mut filter) = (None, None);
+    match func.args.get(required) {
+        Some(Value::Object(options)) => {
+            for (key, value) in options.iter() {
+                match key.as_ref() {
+                    "depth" => match stk.run(|stk| value.evaluate(stk, graph, cur)).await? {
+                        Value::Number(n) if n.to_float() >= 1.0 => depth = Some(n.to_usize()),
+                        _ => return Err(invalid("a depth of at least 1")),
+                    },
+                    "where" => filter = Some(Condition(value.clone())),
+                    key => return Err(invalid(&format!("options of depth or where, found {key}"))),
+                }
+            }
+        }
+        Some(_) => return Err(invalid("an object of options")),
+        None => (),
+    }
+
+    let route = Route {
+        tables,
+        dir,
+        weight,
+        depth,
+        filter,
+        txn: document::txn(cur),
+    };
+    Ok(match search(graph, *from, *to, &route).await? {
+        Some(path) => path.into_iter().map(Value::from).collect::<Vec<_>>().into(),
+        None => Value::None,
+    })
+}
+
+fn table(value: &Value) -> Option<String> {
+    match value {
+        Value::String(table) => Some(table.as_string()),
+        Value::Table(table) => Some(table.0.clone()),
+        _ => None,
+    }
+}
+
+/// Dijkstra over the records reachable from `from`, a record is settled again
+/// when reached in fewer hops so a depth limit never hides a cheaper path
+async fn search(
+    graph: &Addr<Graph>,
+    from: Record,
+    to: Record,
+    route: &Route,
+) -> Result<Option<Vec<Record>>, Error> {
+    let mut costs = HashMap::from([((from.clone(), 0), 0.0)]);
+    let mut parents: HashMap<(Record, usize), (Record, Record)> = HashMap::new();
+    let mut settled: HashMap<Record, usize> = HashMap::new();
+    let mut queue = BinaryHeap::from([Step {
+        cost: 0.0,
+        hops: 0,
+        id: from,
+    }]);
+
+    while let Some(Step { cost, hops, id }) = queue.pop() {
+        if settled.get(&id).is_some_and(|&settled| settled <= hops) {
+            continue;
+        }
+        settled.insert(id.clone(), hops);
+        if id == to {
+            let mut path = vec![id.clone()];
+            let mut key = (id, hops);
+            while let Some((edge, prev)) = parents.remove(&key) {
+                path.push(edge);
+                path.push(prev.clone());
+                key = (prev, key.1 - 1);
+            }
+            path.reverse();
+            return Ok(Some(path));
+        }
+        if route.depth.is_some_and(|depth| hops >= depth) {
+            continue;
+        }
+
+        let Response::Node(node) = graph.send(Retrieve::Record(id.clone())).await.unwrap() else {
+            continue;
+        };
+        for (edge, next, weight) in neighbours(graph, &node, &id, route).await? {
+            if settled
+                .get(&next)
+                .is_some_and(|&settled| settled <= hops + 1)
+            {
+                continue;
+            }
+            let key = (next.clone(), hops + 1);
+            let cost = cost + weight;
+            if costs.get(&key).is_some_and(|&known| known <= cost) {
+                continue;
+            }
+            costs.insert(key.clone(), cost);
+            parents.insert(key, (edge, id.clone()));
+            queue.push(Step {
+                cost,
+                hops: hops + 1,
+                id: next,
+            });
+        }
+    }
+    Ok(None)
+}
+
+/// The edges leaving `id` along the route that pass its filter, with the
+/// record at their other end and the cost of crossing them
+async fn neighbours(
+    graph: &Addr<Graph>,
+    node: &Addr<Entity>,
+    id: &Record,
+    route: &Route,
+) -> Result<Vec<(Record, Record, f64)>, Error> {
+    let get = Get::new(
+        Fields(vec![Field::WildCard]),
+        route.filter.clone(),
+        graph.clone(),
+    )
+    .with_txn(route.txn);
+    let mut found = Vec::new();
+    for table in route.tables.iter() {
+        let path = Path::new(route.dir.clone(), Table(table.clone()), None);
+        let edges: Vec<Addr<Entity>> = node
+            .send(Walk::new(vec![path], id.clone(), graph.clone()).with_txn(route.txn))
+            .await
+            .unwrap()?
+            .try_into()?;
+        for edge in edges {
+            let Response::Value(Value::Object(fields)) = edge.send(get.clone()).await.unwrap()?
+            else {
+                continue;
+            };
+            let record = |key: &str| Record::try_from(fields.get(&key.into()).clone());
+            let (edge, origin, dest) = (record("id")?, record("in")?, record("out")?);
+            let next = match route.dir {
+                Direction::In => dest,
+                Direction::Out => origin,
+                _ if origin == *id => dest,
+                _ => origin,
+            };
+            let weight = match &route.weight {
+                None => 1.0,
+                Some(field) => match fields.get(field) {
+                    Value::Number(n) if n.to_float() >= 0.0 => n.to_float(),
+                    _ => {
+                        return Err(Error::InvalidArguments(format!(
+                            "Edge {edge} has no non-negative number in {field} to weigh it by"
+                        )))
+                    }
+                },
+            };
+            found.push((edge, next, weight));
+        }
+    }
+    Ok(found)
+}
+
+/// The records of a table and the edges between them, by position in `ids`
+struct Subgraph {
+    ids: Vec<Record>,
+    out: Vec<Vec<usize>>,
+    inc: Vec<Vec<usize>>,
+}
+
+impl Subgraph {
+    async fn load(
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        name: &str,
+        args: &[Value],
+    ) -> Result<Subgraph, Error> {
+        let (Some(table), Some(edges)) = (args.first().and_then(table), args.get(1)) else {
+            return Err(Error::InvalidArguments(format!(
+                "{name} expects a table and edge tables"
+            )));
+        };
+        let edges = match edges {
+            Value::Array(edges) => edges.iter().map(self::table).collect(),
+            edges => self::table(edges).map(|edge| vec![edge]),
+        };
+        let Some(edges) = edges.filter(|edges| !edges.is_empty()) else {
+            return Err(Error::InvalidArguments(format!(
+                "{name} expects edge tables as strings"
+            )));
+        };
+
+        let mut ids: Vec<Record> = Vec::new();
+        for fields in records(graph, txn, table).await? {
+            ids.push(Record::try_from(fields.get(&"id".into()).clone())?);
+        }
+        ids.sort();
+        let index: HashMap<_, _> = ids
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, id)| (id, i))
+            .collect();
+        let mut out = vec![Vec::new(); ids.len()];
+        let mut inc = vec![Vec::new(); ids.len()];
+        for edge in edges {
+            for fields in records(graph, txn, edge).await? {
+                let record = |key: &str| Record::try_from(fields.get(&key.into()).clone());
+                let (Ok(origin), Ok(dest)) = (record("in"), record("out")) else {
+                    continue;
+                };
+                if let (Some(&i), Some(&j)) = (index.get(&origin), index.get(&dest)) {
+                    out[i].push(j);
+                    inc[j].push(i);
+                }
+            }
+        }
+        Ok(Subgraph { ids, out, inc })
+    }
+
+    /// Neighbours without repeats or self loops, ignoring direction
+    fn undirected(&self) -> Vec<BTreeSet<usize>> {
+        (0..self.ids.len())
+            .map(|i| {
+                let near = self.out[i].iter().chain(self.inc[i].iter());
+                near.copied().filter(|&j| j != i).collect()
+            })
+            .collect()
+    }
+
+    /// One object per record holding its id and the given metrics
+    fn results<const N: usize>(
+        self,
+        metrics: impl Fn(usize) -> [(&'static str, Value); N],
+    ) -> Value {
+        let mut array = Vec::with_capacity(self.ids.len());
+        for (i, id) in self.ids.into_iter().enumerate() {
+            let mut object = BTreeMap::from([(Arc::from("id"), Value::from(id))]);
+            object.extend(metrics(i).map(|(key, value)| (Arc::from(key), value)));
+            array.push(Value::from(object));
+        }
+        array.into()
+    }
+}
+
+async fn records(graph: &Addr<Graph>, txn: &Txn, table: String) -> Result<Vec<Object>, Error> {
+    let nodes: Vec<Addr<Entity>> = graph
+        .send(Retrieve::Table(table))
+        .await
+        .unwrap()
+        .try_into()?;
+    let mut records = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let get = Get::new(Field::WildCard.into(), None, graph.clone()).with_txn(*txn);
+        if let Response::Value(Value::Object(fields)) = node.send(get).await.unwrap()? {
+            records.push(fields);
+        }
+    }
+    Ok(records)
+}
+
+fn float(v: f64) -> Value {
+    Value::Number(Number::Float(v))
+}
+
+fn int(v: usize) -> Value {
+    Value::Number(Number::Int(v as i64))
+}
+
+/// `graph::pagerank(table, edges [, { damping, iterations }])`
+pub async fn pagerank(graph: &Addr<Graph>, txn: &Txn, args: Vec<Value>) -> Result<Value, Error> {
+    let invalid =
+        |expected: &str| Error::InvalidArguments(format!("graph::pagerank expects {expected}"));
+    let (mut damping, mut iterations) = (0.85, 100);
+    match args.get(2) {
+        Some(Value::Object(options)) => {
+            for (key, value) in options.iter() {
+                match (key.as_ref(), value) {
+                    ("damping", Value::Number(n)) if (0.0..1.0).contains(&n.to_float()) => {
+                        damping = n.to_float()
+                    }
+                    ("iterations", Value::Number(n)) if n.to_float() >= 1.0 => {
+                        iterations = n.to_usize()
+                    }
+                    _ => return Err(invalid("a damping below 1 and at least 1 iteration")),
+                }
+            }
+        }
+        Some(_) => return Err(invalid("an object of options")),
+        None => (),
+    }
+
+    let subgraph = Subgraph::load(graph, txn, "graph::pagerank", &args).await?;
+    let n = subgraph.ids.len();
+    let mut ranks = vec![1.0 / n as f64; n];
+    for _ in 0..iterations {
+        // Records without edges out share their rank with every record
+        let dangling: f64 = (0..n)
+            .filter(|&i| subgraph.out[i].is_empty())
+            .map(|i| ranks[i])
+            .sum();
+        let base = (1.0 - damping) / n as f64 + damping * dangling / n as f64;
+        let mut next = vec![base; n];
+        for (i, out) in subgraph.out.iter().enumerate() {
+            for &j in out {
+                next[j] += damping * ranks[i] / out.len() as f64;
+            }
+        }
+        let delta: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if delta < 1e-9 {
+            break;
+        }
+    }
+    Ok(subgraph.results(|i| [("rank", float(ranks[i]))]))
+}
+
+/// `graph::components(table, edges)`, records reachable from each other in
+/// either direction share the smallest id among them as their component
+pub async fn components(graph: &Addr<Graph>, txn: &Txn, args: Vec<Value>) -> Result<Value, Error> {
+    let subgraph = Subgraph::load(graph, txn, "graph::components", &args).await?;
+    let mut parents: Vec<usize> = (0..subgraph.ids.len()).collect();
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+    for (i, out) in subgraph.out.iter().enumerate() {
+        for &j in out {
+            let (a, b) = (root(&mut parents, i), root(&mut parents, j));
+            parents[a.max(b)] = a.min(b);
+        }
+    }
+    let roots: Vec<_> = (0..parents.len()).map(|i| root(&mut parents, i)).collect();
+    let ids = subgraph.ids.clone();
+    Ok(subgraph.results(|i| [("component", ids[roots[i]].clone().into())]))
+}
+
+/// `graph::degree(table, edges)`
+pub async fn degree(graph: &Addr<Graph>, txn: &Txn, args: Vec<Value>) -> Result<Value, Error> {
+    let subgraph = Subgraph::load(graph, txn, "graph::degree", &args).await?;
+    let (out, inc): (Vec<_>, Vec<_>) = (0..subgraph.ids.len())
+        .map(|i| (subgraph.out[i].len(), subgraph.inc[i].len()))
+        .unzip();
+    Ok(subgraph.results(|i| {
+        [
+            ("in", int(inc[i])),
+            ("out", int(out[i])),
+            ("degree", int(inc[i] + out[i])),
+        ]
+    }))
+}
+
+/// `graph::betweenness(table, edges)`, counting the shortest directed paths
+/// between other records that pass through each record
+pub async fn betweenness(graph: &Addr<Graph>, txn: &Txn, args: Vec<Value>) -> Result<Value, Error> {
+    let subgraph = Subgraph::load(graph, txn, "graph::betweenness", &args).await?;
+    let n = subgraph.ids.len();
+    let out: Vec<BTreeSet<usize>> = subgraph
+        .out
+        .iter()
+        .enumerate()
+        .map(|(i, out)| out.iter().copied().filter(|&j| j != i).collect())
+        .collect();
+    let mut scores = vec![0.0; n];
+    // Brandes, a breadth first search from every record
+    for s in 0..n {
+        let mut order = Vec::with_capacity(n);
+        let mut preds = vec![Vec::new(); n];
+        let mut paths = vec![0.0; n];
+        let mut dist = vec![usize::MAX; n];
+        paths[s] = 1.0;
+        dist[s] = 0;
+        let mut queue = VecDeque::from([s]);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for &w in &out[v] {
+                if dist[w] == usize::MAX {
+                    dist[w] = dist[v] + 1;
+                    queue.push_back(w);
+                }
+                if dist[w] == dist[v] + 1 {
+                    paths[w] += paths[v];
+                    preds[w].push(v);
+                }
+            }
+        }
+        let mut deps = vec![0.0; n];
+        while let Some(w) = order.pop() {
+            for &v in &preds[w] {
+                deps[v] += paths[v] / paths[w] * (1.0 + deps[w]);
+            }
+            if w != s {
+                scores[w] += deps[w];
+            }
+        }
+    }
+    Ok(subgraph.results(|i| [("betweenness", float(scores[i]))]))
+}
+
+/// `graph::triangle_count(table, edges)`, ignoring the direction of edges
+pub async fn triangle_count(
+    graph: &Addr<Graph>,
+    txn: &Txn,
+    args: Vec<Value>,
+) -> Result<Value, Error> {
+    let subgraph = Subgraph::load(graph, txn, "graph::triangle_count", &args).await?;
+    let near = subgraph.undirected();
+    let mut counts = vec![0; near.len()];
+    for (u, near_u) in near.iter().enumerate() {
+        for &v in near_u.range(u + 1..) {
+            for &w in near[v].range(v + 1..) {
+                if near_u.contains(&w) {
+                    counts[u] += 1;
+                    counts[v] += 1;
+                    counts[w] += 1;
+                }
+            }
+        }
+    }
+    Ok(subgraph.results(|i| [("triangles", int(counts[i]))]))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dbs::{executor::Executor, graph::Graph},
+        err::Error,
+    };
+    use actix::{Actor, Addr};
+
+    async fn people(graph: Addr<Graph>) -> Executor {
+        let executor = Executor::new(graph);
+        executor
+            .setup("CREATE person:1; CREATE person:2; CREATE person:3; CREATE person:4")
+            .await;
+        executor
+    }
+
+    #[actix_rt::test]
+    async fn path_test() {
+        let executor = people(Graph::new().start()).await;
+        executor
+            .setup(
+                "RELATE person:1->knows:a->person:2 SET cost = 1;
+                RELATE person:2->knows:b->person:3 SET cost = 1.5;
+                RELATE person:1->knows:c->person:3 SET cost = 5;
+                RELATE person:3->likes:d->person:4",
+            )
+            .await;
+        let path = |path: &str| {
+            let executor = &executor;
+            let query = format!("SELECT graph::{path} AS p FROM person:1");
+            async move {
+                let result = executor.run(&query).await?;
+                Ok::<_, Error>(result[0]["p"].clone())
+            }
+        };
+        let direct = serde_json::json!(["person:1", "knows:c", "person:3"]);
+        let cheap = serde_json::json!(["person:1", "knows:a", "person:2", "knows:b", "person:3"]);
+        let shortest = path("shortest_path(person:1, person:3, 'knows', '->')");
+        assert_eq!(shortest.await, Ok(direct.clone()));
+        let weighted = path("weighted_path(person:1, person:3, 'knows', '->', 'cost')");
+        assert_eq!(weighted.await, Ok(cheap.clone()));
+        let shallow =
+            path("weighted_path(person:1, person:3, 'knows', '->', 'cost', { depth: 1 })");
+        assert_eq!(shallow.await, Ok(direct));
+        let filtered =
+            path("shortest_path(person:1, person:3, 'knows', '->', { where: cost < 5 })");
+        assert_eq!(filtered.await, Ok(cheap));
+        let against = path("shortest_path(person:3, person:1, 'knows', '->')");
+        assert_eq!(against.await, Ok(serde_json::Value::Null));
+        let back = path("shortest_path(person:3, person:1, 'knows', '<-')");
+        let reversed = serde_json::json!(["person:3", "knows:c", "person:1"]);
+        assert_eq!(back.await, Ok(reversed));
+        let both = path("shortest_path(person:2, person:4, ['knows', 'likes'], '<->')");
+        let mixed = serde_json::json!(["person:2", "knows:b", "person:3", "likes:d", "person:4"]);
+        assert_eq!(both.await, Ok(mixed));
+        // Weights must be numbers and a direction must be given
+        let unweighted = path("weighted_path(person:3, person:4, 'likes', '->', 'cost')");
+        assert!(unweighted.await.is_err());
+        let undirected = path("shortest_path(person:1, person:3, 'knows')");
+        assert!(undirected.await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn analytics_test() {
+        let executor = people(Graph::new().start()).await;
+        executor
+            .setup(
+                "CREATE person:5;
+                RELATE person:1->knows->person:2; RELATE person:2->knows->person:3;
+                RELATE person:3->knows->person:1; RELATE person:3->knows->person:4",
+            )
+            .await;
+        let column = |function: &str, key: &str| {
+            let executor = &executor;
+            let query = format!("SELECT graph::{function} AS v FROM person:1");
+            let key = key.to_string();
+            async move {
+                let result = executor.run(&query).await.unwrap();
+                let rows = result[0]["v"].as_array().unwrap();
+                let column: Vec<_> = rows.iter().map(|row| row[&key].clone()).collect();
+                serde_json::Value::from(column)
+            }
+        };
+        let ids = ["person:1", "person:2", "person:3", "person:4", "person:5"];
+        let degree = "degree('person', 'knows')";
+        assert_eq!(column(degree, "id").await, serde_json::json!(ids));
+        assert_eq!(
+            column(degree, "in").await,
+            serde_json::json!([1, 1, 1, 1, 0])
+        );
+        assert_eq!(
+            column(degree, "out").await,
+            serde_json::json!([1, 1, 2, 0, 0])
+        );
+        let degrees = serde_json::json!([2, 2, 3, 1, 0]);
+        assert_eq!(column(degree, "degree").await, degrees);
+        let components = column("components('person', ['knows'])", "component").await;
+        let roots = ["person:1", "person:1", "person:1", "person:1", "person:5"];
+        assert_eq!(components, serde_json::json!(roots));
+        let triangles = column("triangle_count('person', 'knows')", "triangles").await;
+        assert_eq!(triangles, serde_json::json!([1, 1, 1, 0, 0]));
+        let betweenness = column("betweenness('person', 'knows')", "betweenness").await;
+        assert_eq!(betweenness, serde_json::json!([1.0, 2.0, 3.0, 0.0, 0.0]));
+        let ranks = column("pagerank('person', 'knows', { damping: 0.85 })", "rank").await;
+        let ranks = ranks.as_array().unwrap();
+        let ranks: Vec<f64> = ranks.iter().map(|r| r.as_f64().unwrap()).collect();
+        assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(ranks[..4].iter().all(|&rank| rank > ranks[4]));
+        assert!((ranks[0] - ranks[3]).abs() < 1e-9);
+        let edgeless = executor.run("SELECT graph::pagerank('person') AS v FROM person:1");
+        assert!(edgeless.await.is_err());
+    }
+}
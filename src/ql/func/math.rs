@@ -0,0 +1,72 @@
+use crate::{
+    err::Error,
+    ql::{array::Array, number::Number, value::Value},
+};
+
+fn numbers(args: Vec<Value>, name: &str) -> Result<Vec<Number>, Error> {
+    match args.into_iter().next() {
+        Some(Value::Array(Array(v))) => Ok(v
+            .into_iter()
+            .filter_map(|v| match v {
+                Value::Number(v) => Some(v),
+                _ => None,
+            })
+            .collect()),
+        Some(Value::Number(v)) => Ok(vec![v]),
+        _ => Err(Error::InvalidArguments(format!(
+            "{name} expects an array of numbers"
+        ))),
+    }
+}
+
+pub fn sum(args: Vec<Value>) -> Result<Value, Error> {
+    let sum = numbers(args, "math::sum")?
+        .into_iter()
+        .fold(Number::Int(0), |acc, v| acc + v);
+    Ok(Value::Number(sum))
+}
+
+pub fn mean(args: Vec<Value>) -> Result<Value, Error> {
+    let values = numbers(args, "math::mean")?;
+    if values.is_empty() {
+        return Ok(Value::None);
+    }
+    let len = values.len() as f64;
+    let sum = values.into_iter().fold(Number::Int(0), |acc, v| acc + v);
+    Ok(Value::Number(sum / Number::Float(len)))
+}
+
+pub fn min(args: Vec<Value>) -> Result<Value, Error> {
+    let min = numbers(args, "math::min")?.into_iter().min();
+    Ok(min.map_or(Value::None, Value::Number))
+}
+
+pub fn max(args: Vec<Value>) -> Result<Value, Error> {
+    let max = numbers(args, "math::max")?.into_iter().max();
+    Ok(max.map_or(Value::None, Value::Number))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn math_test() {
+        let args = || {
+            vec![Value::from(vec![
+                Value::from(1),
+                4.into(),
+                Value::None,
+                2.into(),
+            ])]
+        };
+        assert_eq!(sum(args()).unwrap(), 7.into());
+        assert_eq!(
+            mean(args()).unwrap(),
+            Value::Number(Number::Float(7.0 / 3.0))
+        );
+        assert_eq!(min(args()).unwrap(), 1.into());
+        assert_eq!(max(args()).unwrap(), 4.into());
+        assert!(sum(vec!["a".into()]).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{
+    err::Error,
+    ql::{object::Object, value::Value},
+};
+
+impl Value {
+    pub fn merge(&mut self, val: Value) -> Result<(), Error> {
+        let Value::Object(Object(val)) = val else {
+            return Err(Error::IncorrectValueType {
+                expected: String::from("Object"),
+                result: val,
+            });
+        };
+        let Value::Object(Object(doc)) = self else {
+            *self = Value::Object(Object(val));
+            return Ok(());
+        };
+        for (key, val) in val {
+            match (doc.get_mut(&key), val) {
+                (Some(cur @ Value::Object(_)), val @ Value::Object(_)) => cur.merge(val)?,
+                (_, val) => {
+                    doc.insert(key, val);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn merge_nested_test() {
+        let mut doc = Value::from(BTreeMap::from([
+            ("a".to_string(), 1.into()),
+            (
+                "b".to_string(),
+                BTreeMap::from([("c".to_string(), 1.into()), ("d".to_string(), 1.into())]).into(),
+            ),
+        ]));
+        let val = Value::from(BTreeMap::from([
+            (
+                "b".to_string(),
+                BTreeMap::from([("d".to_string(), 2.into())]).into(),
+            ),
+            ("e".to_string(), 3.into()),
+        ]));
+        doc.merge(val).unwrap();
+        let correct = Value::from(BTreeMap::from([
+            ("a".to_string(), 1.into()),
+            (
+                "b".to_string(),
+                BTreeMap::from([("c".to_string(), 1.into()), ("d".to_string(), 2.into())]).into(),
+            ),
+            ("e".to_string(), 3.into()),
+        ]));
+        assert_eq!(doc, correct);
+    }
+}
@@ -1,3 +1,16 @@
+pub mod array;
+pub mod count;
+pub mod diff;
 pub mod flatten;
 pub mod get;
+pub mod graph;
+pub mod math;
+pub mod merge;
 pub mod operation;
+pub mod patch;
+pub mod record;
+pub mod search;
+pub mod set;
+pub mod string;
+pub mod time;
+pub mod vector;
@@ -0,0 +1,162 @@
+use crate::{
+    err::Error,
+    ql::{array::Array, object::Object, value::Value},
+};
+
+impl Value {
+    pub fn patch(&mut self, ops: Value) -> Result<(), Error> {
+        let Value::Array(Array(ops)) = ops else {
+            return Err(Error::IncorrectValueType {
+                expected: String::from("Array"),
+                result: ops,
+            });
+        };
+        let mut doc = self.clone();
+        for op in ops {
+            let Value::Object(op) = op else {
+                return Err(Error::InvalidPatch(format!("{op} is not an operation")));
+            };
+            let path = pointer(&op, "path")?;
+            let value = op.get(&"value".into()).clone();
+            match op.get(&"op".into()) {
+                Value::String(v) => match v.as_str() {
+                    "add" => add(&mut doc, &path, value)?,
+                    "remove" => {
+                        remove(&mut doc, &path)?;
+                    }
+                    "replace" => *lookup(&mut doc, &path)? = value,
+                    "move" => {
+                        let val = remove(&mut doc, &pointer(&op, "from")?)?;
+                        add(&mut doc, &path, val)?;
+                    }
+                    "copy" => {
+                        let val = lookup(&mut doc, &pointer(&op, "from")?)?.clone();
+                        add(&mut doc, &path, val)?;
+                    }
+                    "test" => {
+                        if *lookup(&mut doc, &path)? != value {
+                            return Err(Error::InvalidPatch(format!(
+                                "test failed for {}",
+                                op.get(&"path".into())
+                            )));
+                        }
+                    }
+                    v => return Err(Error::InvalidPatch(format!("unknown operation '{v}'"))),
+                },
+                v => return Err(Error::InvalidPatch(format!("unknown operation {v}"))),
+            }
+        }
+        *self = doc;
+        Ok(())
+    }
+}
+
+fn pointer(op: &Object, key: &str) -> Result<Vec<String>, Error> {
+    let Value::String(path) = op.get(&key.into()) else {
+        return Err(Error::InvalidPatch(format!("missing '{key}'")));
+    };
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(path) = path.strip_prefix('/') else {
+        return Err(Error::InvalidPatch(format!("invalid pointer '{path}'")));
+    };
+    Ok(path
+        .split('/')
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn lookup<'a>(doc: &'a mut Value, path: &[String]) -> Result<&'a mut Value, Error> {
+    let mut cur = doc;
+    for seg in path {
+        cur = match cur {
+            Value::Object(Object(v)) => v.get_mut(seg.as_str()),
+            Value::Array(Array(v)) => seg.parse::<usize>().ok().and_then(|i| v.get_mut(i)),
+            _ => None,
+        }
+        .ok_or_else(|| Error::InvalidPatch(format!("path /{} does not exist", path.join("/"))))?;
+    }
+    Ok(cur)
+}
+
+fn add(doc: &mut Value, path: &[String], val: Value) -> Result<(), Error> {
+    let Some((last, parent)) = path.split_last() else {
+        *doc = val;
+        return Ok(());
+    };
+    match lookup(doc, parent)? {
+        Value::Object(Object(v)) => {
+            v.insert(last.as_str().into(), val);
+        }
+        Value::Array(Array(v)) if last == "-" => v.push(val),
+        Value::Array(Array(v)) => match last.parse::<usize>() {
+            Ok(i) if i <= v.len() => v.insert(i, val),
+            _ => return Err(Error::InvalidPatch(format!("invalid index '{last}'"))),
+        },
+        v => return Err(Error::InvalidPatch(format!("can not add a field to {v}"))),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &[String]) -> Result<Value, Error> {
+    let Some((last, parent)) = path.split_last() else {
+        return Err(Error::InvalidPatch(String::from(
+            "can not remove the document",
+        )));
+    };
+    let removed = match lookup(doc, parent)? {
+        Value::Object(Object(v)) => v.remove(last.as_str()),
+        Value::Array(Array(v)) => match last.parse::<usize>() {
+            Ok(i) if i < v.len() => Some(v.remove(i)),
+            _ => None,
+        },
+        _ => None,
+    };
+    removed.ok_or_else(|| Error::InvalidPatch(format!("path /{} does not exist", path.join("/"))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn op(op: &str, path: &str, value: Value) -> Value {
+        BTreeMap::from([
+            ("op".to_string(), op.into()),
+            ("path".to_string(), path.into()),
+            ("value".to_string(), value),
+        ])
+        .into()
+    }
+
+    #[test]
+    fn patch_test() {
+        let mut doc = Value::from(BTreeMap::from([
+            ("a".to_string(), 1.into()),
+            ("b".to_string(), vec![Value::from(1), 2.into()].into()),
+        ]));
+        let ops = vec![
+            op("replace", "/a", 2.into()),
+            op("add", "/b/-", 3.into()),
+            op("remove", "/b/0", Value::None),
+            op("add", "/c~1d", true.into()),
+            op("test", "/a", 2.into()),
+        ];
+        doc.patch(ops.into()).unwrap();
+        let correct = Value::from(BTreeMap::from([
+            ("a".to_string(), 2.into()),
+            ("b".to_string(), vec![Value::from(2), 3.into()].into()),
+            ("c/d".to_string(), true.into()),
+        ]));
+        assert_eq!(doc, correct);
+    }
+
+    #[test]
+    fn patch_failed_test() {
+        let mut doc = Value::from(BTreeMap::from([("a".to_string(), 1.into())]));
+        let ops = vec![op("replace", "/a", 2.into()), op("test", "/a", 1.into())];
+        assert!(doc.patch(ops.into()).is_err());
+        assert_eq!(doc, BTreeMap::from([("a".to_string(), 1.into())]).into());
+    }
+}
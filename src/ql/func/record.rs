@@ -0,0 +1,37 @@
+use crate::{
+    dbs::{
+        graph::Graph,
+        ops::{history::History, retrieve::Retrieve},
+    },
+    err::Error,
+    ql::{array::Array, datetime::Datetime, value::Value},
+    resp::Response,
+};
+use actix::Addr;
+use std::{collections::BTreeMap, sync::Arc};
+
+/// `record::history(record)`, every version of the record the table still
+/// keeps along with the time it was committed at, a deletion is a version
+/// whose value is NONE
+pub async fn history(graph: &Addr<Graph>, args: Vec<Value>) -> Result<Value, Error> {
+    let Some(Value::Record(id)) = args.into_iter().next() else {
+        return Err(Error::InvalidArguments(String::from(
+            "record::history expects a record",
+        )));
+    };
+    let node = match graph.send(Retrieve::Record(*id)).await.unwrap() {
+        Response::Node(node) => node,
+        _ => return Ok(Value::Array(Array::new())),
+    };
+    let versions = node.send(History).await.unwrap();
+    let versions = versions.into_iter().map(|version| {
+        Value::from(BTreeMap::from([
+            (Arc::from("at"), Value::from(Datetime(version.at as i64))),
+            (
+                Arc::from("value"),
+                version.fields.map_or(Value::None, Value::from),
+            ),
+        ]))
+    });
+    Ok(versions.collect::<Vec<_>>().into())
+}
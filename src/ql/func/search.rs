@@ -0,0 +1,61 @@
+use crate::{
+    dbs::index,
+    doc::document::Cursor,
+    err::Error,
+    idx::search::Hit,
+    ql::{number::Number, object::Object, value::Value},
+};
+
+/// The hit of the matches operator with the given reference, or of the
+/// first matches operator without one
+fn hit<'a>(cur: Option<&'a Cursor>, reference: Option<&Value>) -> Result<Option<&'a Hit>, Error> {
+    let Some(cur) = cur else {
+        return Ok(None);
+    };
+    Ok(match reference {
+        None => cur.matches.values().next(),
+        Some(Value::Number(Number::Int(r))) => match u8::try_from(*r) {
+            Ok(r) => cur.matches.get(&Some(r)),
+            Err(_) => None,
+        },
+        Some(_) => {
+            return Err(Error::InvalidArguments(String::from(
+                "search functions expect a matches reference number",
+            )))
+        }
+    })
+}
+
+pub fn score(args: Vec<Value>, cur: Option<&Cursor>) -> Result<Value, Error> {
+    Ok(match hit(cur, args.first())? {
+        Some(hit) => Value::Number(Number::Float(hit.score)),
+        None => Value::None,
+    })
+}
+
+pub fn highlight(args: Vec<Value>, cur: Option<&Cursor>) -> Result<Value, Error> {
+    let mut args = args.into_iter();
+    let (Some(Value::String(pre)), Some(Value::String(post))) = (args.next(), args.next()) else {
+        return Err(Error::InvalidArguments(String::from(
+            "search::highlight expects a prefix and a suffix",
+        )));
+    };
+    let reference = args.next();
+    let Some(hit) = hit(cur, reference.as_ref())? else {
+        return Ok(Value::None);
+    };
+    let Some(Value::Object(Object(fields))) = cur.map(|cur| &cur.value) else {
+        return Ok(Value::None);
+    };
+    let mark = |text: &Value| match text {
+        Value::String(text) => hit
+            .analyzer
+            .highlight(&text.0, &hit.terms, &pre.0, &post.0)
+            .into(),
+        text => text.clone(),
+    };
+    Ok(match index::pick(fields, &hit.field) {
+        Value::Array(texts) => texts.iter().map(mark).collect::<Vec<_>>().into(),
+        text => mark(&text),
+    })
+}
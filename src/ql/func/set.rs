@@ -0,0 +1,80 @@
+use crate::{
+    err::Error,
+    ql::{ident::Ident, object::Object, part::Part, value::Value},
+};
+
+impl Value {
+    pub fn set(&mut self, path: &[Part], val: Value) -> Result<(), Error> {
+        let Some((part, next)) = path.split_first() else {
+            *self = val;
+            return Ok(());
+        };
+        match self {
+            Value::Object(Object(v)) => match part {
+                Part::Field(Ident(field)) => match v.get_mut(field) {
+                    Some(v) => v.set(next, val),
+                    None => {
+                        let mut new = Value::None;
+                        new.set(next, val)?;
+                        v.insert(field.clone(), new);
+                        Ok(())
+                    }
+                },
+                Part::All => v.values_mut().try_for_each(|v| v.set(next, val.clone())),
+                _ => Err(Error::InvalidIdiom),
+            },
+            Value::Array(v) => match part {
+                Part::All | Part::Flatten => {
+                    v.iter_mut().try_for_each(|v| v.set(next, val.clone()))
+                }
+                Part::First => match v.first_mut() {
+                    Some(v) => v.set(next, val),
+                    None => Ok(()),
+                },
+                Part::Last => match v.last_mut() {
+                    Some(v) => v.set(next, val),
+                    None => Ok(()),
+                },
+                Part::Index(i) => match v.get_mut(i.to_usize()) {
+                    Some(v) => v.set(next, val),
+                    None => Err(Error::OutOfBoundsIndex(i.to_usize())),
+                },
+                _ => Err(Error::InvalidIdiom),
+            },
+            Value::None | Value::Null => match part {
+                Part::Field(_) => {
+                    *self = Value::Object(Object::default());
+                    self.set(path, val)
+                }
+                _ => Err(Error::InvalidIdiom),
+            },
+            _ => Err(Error::InvalidIdiom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn set_nested_field_test() {
+        let mut doc = Value::Object(Object::default());
+        let path = vec![Part::Field("a".into()), Part::Field("b".into())];
+        doc.set(&path, 1.into()).unwrap();
+        doc.set(
+            &[Part::Field("c".into())],
+            vec![Value::from(1), 2.into()].into(),
+        )
+        .unwrap();
+        doc.set(&[Part::Field("c".into()), Part::Index(1.into())], 3.into())
+            .unwrap();
+        let a = Value::Object(Object(BTreeMap::from([("b".into(), 1.into())])));
+        let correct = Value::Object(Object(BTreeMap::from([
+            ("a".into(), a),
+            ("c".into(), vec![Value::from(1), 3.into()].into()),
+        ])));
+        assert_eq!(doc, correct);
+    }
+}
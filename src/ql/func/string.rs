@@ -0,0 +1,23 @@
+use crate::{err::Error, ql::value::Value};
+
+/// `string::concat(..)`, the arguments joined as text, where NONE and NULL
+/// add nothing
+pub fn concat(args: Vec<Value>) -> Result<Value, Error> {
+    let text: String = args
+        .into_iter()
+        .filter(|v| !v.is_none() && !v.is_null())
+        .map(|v| v.to_string())
+        .collect();
+    Ok(text.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn concat_test() {
+        let args = vec![Value::from("a"), " ".into(), 1.into(), Value::None];
+        assert_eq!(concat(args).unwrap(), Value::from("a 1"));
+    }
+}
@@ -0,0 +1,13 @@
+use crate::{
+    err::Error,
+    ql::{datetime::Datetime, value::Value},
+};
+
+pub fn now(args: Vec<Value>) -> Result<Value, Error> {
+    if !args.is_empty() {
+        return Err(Error::InvalidArguments(String::from(
+            "time::now expects no arguments",
+        )));
+    }
+    Ok(Datetime::now().into())
+}
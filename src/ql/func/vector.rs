@@ -0,0 +1,113 @@
+use crate::{
+    err::Error,
+    ql::{distance, number::Number, value::Value},
+};
+
+fn vector(args: Vec<Value>, name: &str) -> Result<Vec<f64>, Error> {
+    match args.first().and_then(Value::to_vector) {
+        Some(v) => Ok(v),
+        None => Err(Error::InvalidArguments(format!(
+            "{name} expects an array of numbers"
+        ))),
+    }
+}
+
+fn vectors(args: Vec<Value>, name: &str) -> Result<(Vec<f64>, Vec<f64>), Error> {
+    match (
+        args.first().and_then(Value::to_vector),
+        args.get(1).and_then(Value::to_vector),
+    ) {
+        (Some(a), Some(b)) if a.len() == b.len() => Ok((a, b)),
+        _ => Err(Error::InvalidArguments(format!(
+            "{name} expects two arrays of numbers of the same length"
+        ))),
+    }
+}
+
+fn float(v: f64) -> Value {
+    Value::Number(Number::Float(v))
+}
+
+fn floats(v: impl Iterator<Item = f64>) -> Value {
+    v.map(float).collect::<Vec<_>>().into()
+}
+
+pub fn add(args: Vec<Value>) -> Result<Value, Error> {
+    let (a, b) = vectors(args, "vector::add")?;
+    Ok(floats(a.iter().zip(&b).map(|(a, b)| a + b)))
+}
+
+pub fn subtract(args: Vec<Value>) -> Result<Value, Error> {
+    let (a, b) = vectors(args, "vector::subtract")?;
+    Ok(floats(a.iter().zip(&b).map(|(a, b)| a - b)))
+}
+
+pub fn dot(args: Vec<Value>) -> Result<Value, Error> {
+    let (a, b) = vectors(args, "vector::dot")?;
+    Ok(float(distance::dot(&a, &b)))
+}
+
+pub fn magnitude(args: Vec<Value>) -> Result<Value, Error> {
+    let a = vector(args, "vector::magnitude")?;
+    Ok(float(distance::magnitude(&a)))
+}
+
+pub fn normalize(args: Vec<Value>) -> Result<Value, Error> {
+    let a = vector(args, "vector::normalize")?;
+    let magnitude = distance::magnitude(&a);
+    match magnitude == 0.0 {
+        true => Ok(floats(a.into_iter())),
+        false => Ok(floats(a.into_iter().map(|v| v / magnitude))),
+    }
+}
+
+pub fn cosine(args: Vec<Value>) -> Result<Value, Error> {
+    let (a, b) = vectors(args, "vector::similarity::cosine")?;
+    Ok(float(distance::cosine(&a, &b)))
+}
+
+pub fn euclidean(args: Vec<Value>) -> Result<Value, Error> {
+    let (a, b) = vectors(args, "vector::distance::euclidean")?;
+    Ok(float(distance::euclidean(&a, &b)))
+}
+
+pub fn manhattan(args: Vec<Value>) -> Result<Value, Error> {
+    let (a, b) = vectors(args, "vector::distance::manhattan")?;
+    Ok(float(distance::manhattan(&a, &b)))
+}
+
+pub fn chebyshev(args: Vec<Value>) -> Result<Value, Error> {
+    let (a, b) = vectors(args, "vector::distance::chebyshev")?;
+    Ok(float(distance::chebyshev(&a, &b)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(a: &[i64], b: &[i64]) -> Vec<Value> {
+        let vector = |v: &[i64]| Value::from(v.iter().map(|v| Value::from(*v)).collect::<Vec<_>>());
+        vec![vector(a), vector(b)]
+    }
+
+    #[test]
+    fn vector_distance_test() {
+        assert_eq!(euclidean(args(&[0, 0], &[3, 4])), Ok(float(5.0)));
+        assert_eq!(manhattan(args(&[0, 0], &[3, -4])), Ok(float(7.0)));
+        assert_eq!(chebyshev(args(&[1, 2], &[4, 0])), Ok(float(3.0)));
+        assert_eq!(cosine(args(&[1, 0], &[0, 2])), Ok(float(0.0)));
+        assert_eq!(cosine(args(&[2, 0], &[5, 0])), Ok(float(1.0)));
+        assert_eq!(dot(args(&[1, 2], &[3, 4])), Ok(float(11.0)));
+        assert_eq!(magnitude(args(&[3, 4], &[])), Ok(float(5.0)));
+        assert_eq!(
+            normalize(args(&[3, 4], &[])),
+            Ok(Value::from(vec![float(0.6), float(0.8)]))
+        );
+        assert_eq!(
+            subtract(args(&[3, 4], &[1, 1])),
+            Ok(Value::from(vec![float(2.0), float(3.0)]))
+        );
+        assert!(euclidean(args(&[1, 2], &[1])).is_err());
+        assert!(add(vec![Value::from("a"), Value::from("b")]).is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    dbs::graph::Graph,
+    doc::document::{self, Cursor},
+    err::Error,
+    ql::{
+        func::{array, count, graph as algorithms, math, record, search, string, time, vector},
+        value::Value,
+    },
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+use std::fmt;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Function {
+    pub name: String,
+    pub args: Vec<Value>,
+}
+
+impl Function {
+    pub fn new<S: Into<String>>(name: S, args: Vec<Value>) -> Self {
+        Function {
+            name: name.into(),
+            args,
+        }
+    }
+
+    pub fn is_aggregate(&self) -> bool {
+        matches!(
+            self.name.as_str(),
+            "count"
+                | "math::sum"
+                | "math::mean"
+                | "math::min"
+                | "math::max"
+                | "array::group"
+                | "array::distinct"
+        )
+    }
+
+    pub async fn evaluate(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        cur: Option<&Cursor>,
+    ) -> Result<Value, Error> {
+        // Path functions evaluate their own arguments, their edge filter is
+        // checked against each edge instead of the cursor
+        match self.name.as_str() {
+            "graph::shortest_path" => {
+                return algorithms::shortest_path(stk, graph, cur, self).await
+            }
+            "graph::weighted_path" => {
+                return algorithms::weighted_path(stk, graph, cur, self).await
+            }
+            _ => (),
+        }
+        let mut args = Vec::with_capacity(self.args.len());
+        for arg in self.args.iter() {
+            args.push(stk.run(|stk| arg.evaluate(stk, graph, cur)).await?);
+        }
+        // Search functions read the index hits the cursor carries, graph
+        // analytics the records of the graph and record history the versions
+        // a record keeps
+        let txn = document::txn(cur);
+        match self.name.as_str() {
+            "search::score" => search::score(args, cur),
+            "search::highlight" => search::highlight(args, cur),
+            "graph::pagerank" => algorithms::pagerank(graph, &txn, args).await,
+            "graph::components" => algorithms::components(graph, &txn, args).await,
+            "graph::degree" => algorithms::degree(graph, &txn, args).await,
+            "graph::betweenness" => algorithms::betweenness(graph, &txn, args).await,
+            "graph::triangle_count" => algorithms::triangle_count(graph, &txn, args).await,
+            "record::history" => record::history(graph, args).await,
+            _ => self.run(args),
+        }
+    }
+
+    pub fn run(&self, args: Vec<Value>) -> Result<Value, Error> {
+        match self.name.as_str() {
+            "count" => count::count(args),
+            "math::sum" => math::sum(args),
+            "math::mean" => math::mean(args),
+            "math::min" => math::min(args),
+            "math::max" => math::max(args),
+            "array::group" => array::group(args),
+            "array::distinct" => array::distinct(args),
+            "array::len" => array::len(args),
+            "string::concat" => string::concat(args),
+            "time::now" => time::now(args),
+            "vector::add" => vector::add(args),
+            "vector::subtract" => vector::subtract(args),
+            "vector::dot" => vector::dot(args),
+            "vector::magnitude" => vector::magnitude(args),
+            "vector::normalize" => vector::normalize(args),
+            "vector::similarity::cosine" => vector::cosine(args),
+            "vector::distance::euclidean" => vector::euclidean(args),
+            "vector::distance::manhattan" => vector::manhattan(args),
+            "vector::distance::chebyshev" => vector::chebyshev(args),
+            name => Err(Error::InvalidFunction(name.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(ToString::to_string).collect();
+        write!(f, "{}({})", self.name, args.join(", "))
+    }
+}
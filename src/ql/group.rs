@@ -0,0 +1,27 @@
+use crate::ql::idiom::Idiom;
+use std::{fmt, ops::Deref};
+
+/// The idioms a SELECT is grouped by, `GROUP ALL` is an empty group
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Groups(pub Vec<Idiom>);
+
+impl Deref for Groups {
+    type Target = Vec<Idiom>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for Groups {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.is_empty() {
+            true => write!(f, "GROUP ALL"),
+            false => {
+                let idioms: Vec<String> = self.iter().map(ToString::to_string).collect();
+                write!(f, "GROUP BY {}", idioms.join(", "))
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use crate::{
     dbs::graph::Graph,
     doc::document::Cursor,
     err::Error,
-    ql::{array::Array, object::Object, strand::Strand, value::Value},
+    ql::{array::Array, number::Number, object::Object, strand::Strand, value::Value},
 };
 use actix::Addr;
 use reblessive::tree::Stk;
@@ -96,6 +96,26 @@ impl From<BTreeMap<String, Value>> for Id {
     }
 }
 
+impl TryFrom<Value> for Id {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Value::Number(Number::Int(v)) => Id::Number(v),
+            Value::String(v) => Id::from(v),
+            Value::Uuid(v) => Id::Uuid(v.0),
+            Value::Array(v) => Id::Array(v),
+            Value::Object(v) => Id::Object(v),
+            value => {
+                return Err(Error::FailedFromValue {
+                    from: value,
+                    into: String::from("Id"),
+                })
+            }
+        })
+    }
+}
+
 impl Display for Id {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -2,7 +2,7 @@ use crate::{
     dbs::graph::Graph,
     doc::document::Cursor,
     err::Error,
-    ql::{ident::Ident, part::Part, value::Value},
+    ql::{ident::Ident, part::Part, path::Path, value::Value},
 };
 use actix::Addr;
 use reblessive::tree::Stk;
@@ -37,6 +37,22 @@ impl IntoIterator for Idioms {
 pub struct Idiom(pub Vec<Part>);
 
 impl Idiom {
+    /// The start and hops of an idiom that only walks paths from a value,
+    /// like `person:1->knows->person`
+    pub fn walk(&self) -> Option<(&Value, Vec<Path>)> {
+        let (Part::Start(start), parts) = self.split_first()? else {
+            return None;
+        };
+        let path = parts
+            .iter()
+            .map(|part| match part {
+                Part::Path(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        (!path.is_empty()).then_some((start, path))
+    }
+
     pub async fn evaluate(
         &self,
         stk: &mut Stk,
@@ -44,10 +60,13 @@ impl Idiom {
         cur: Option<&Cursor>,
     ) -> Result<Value, Error> {
         match self.first() {
-            Some(Part::Start(v)) => stk.run(|stk| v.evaluate(stk, graph, cur)).await,
+            Some(Part::Start(v) | Part::Value(v)) => {
+                let v = stk.run(|stk| v.evaluate(stk, graph, cur)).await?;
+                stk.run(|stk| v.get(stk, graph, cur, &self[1..])).await
+            }
             _ => match cur {
-                Some(_) => todo!(),
-                None => todo!(),
+                Some(doc) => stk.run(|stk| doc.value.get(stk, graph, cur, self)).await,
+                None => Ok(Value::None),
             },
         }
     }
@@ -141,16 +160,14 @@ impl Deref for Idiom {
 
 impl fmt::Display for Idiom {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut idiom_str = String::new();
-        for part in self.0.iter() {
-            if let Part::Index(part) = part {
-                idiom_str.push_str(&format!("[{part}]"));
-            } else {
-                idiom_str.push_str(&format!("{part}."));
+        for (i, part) in self.0.iter().enumerate() {
+            match part {
+                Part::Field(v) if i == 0 => write!(f, "{v}")?,
+                Part::All if i > 0 => write!(f, ".*")?,
+                part => write!(f, "{part}")?,
             }
         }
-        let idiom_str = idiom_str.trim_end_matches('.');
-        write!(f, "{idiom_str}")
+        Ok(())
     }
 }
 
@@ -0,0 +1,54 @@
+use crate::ql::{distance::Distance, number::Number};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The kind of index a `DEFINE INDEX` statement builds
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Index {
+    #[default]
+    Idx,
+    Uniq,
+    Search {
+        analyzer: String,
+        k1: Number,
+        b: Number,
+    },
+    Hnsw {
+        dimension: u16,
+        distance: Distance,
+        m: u8,
+        efc: u16,
+    },
+}
+
+impl Index {
+    pub fn search(analyzer: String) -> Self {
+        Index::Search {
+            analyzer,
+            k1: Number::Float(1.2),
+            b: Number::Float(0.75),
+        }
+    }
+}
+
+impl fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Index::Idx => Ok(()),
+            Index::Uniq => write!(f, "UNIQUE"),
+            Index::Search { analyzer, k1, b } => {
+                write!(f, "SEARCH ANALYZER {analyzer} BM25({k1},{b})")
+            }
+            Index::Hnsw {
+                dimension,
+                distance,
+                m,
+                efc,
+            } => write!(
+                f,
+                "HNSW DIMENSION {dimension} DIST {distance} M {m} EFC {efc}"
+            ),
+        }
+    }
+}
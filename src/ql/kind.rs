@@ -0,0 +1,81 @@
+use crate::ql::{array::Array, datetime::Datetime, number::Number, uuid::Uuid, value::Value};
+use std::fmt;
+
+/// The type a field of a table holds
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Any,
+    Bool,
+    Int,
+    Float,
+    Number,
+    String,
+    Datetime,
+    Uuid,
+    Object,
+    Array(Option<Box<Kind>>),
+    Record(Option<String>),
+    Option(Box<Kind>),
+}
+
+impl Kind {
+    /// The value as this kind, converting between kinds only when nothing
+    /// is lost, or the value back when it does not fit
+    pub fn coerce(&self, value: Value) -> Result<Value, Value> {
+        Ok(match (self, value) {
+            (Kind::Any, value) => value,
+            (Kind::Option(_), value @ (Value::None | Value::Null)) => value,
+            (Kind::Option(kind), value) => kind.coerce(value)?,
+            (Kind::Bool, value @ Value::Bool(_)) => value,
+            (Kind::Int, value @ Value::Number(Number::Int(_))) => value,
+            (Kind::Int, Value::Number(Number::Float(v))) if v.fract() == 0.0 => {
+                Value::Number(Number::Int(v as i64))
+            }
+            (Kind::Float, Value::Number(v)) => Value::Number(Number::Float(v.to_float())),
+            (Kind::Number, value @ Value::Number(_)) => value,
+            (Kind::String, value @ Value::String(_)) => value,
+            (Kind::Datetime, value @ Value::Datetime(_)) => value,
+            (Kind::Datetime, Value::String(v)) => match Datetime::parse(v.as_str()) {
+                Some(v) => Value::Datetime(v),
+                None => return Err(Value::String(v)),
+            },
+            (Kind::Uuid, value @ Value::Uuid(_)) => value,
+            (Kind::Uuid, Value::String(v)) => match uuid::Uuid::try_parse(v.as_str()) {
+                Ok(v) => Value::Uuid(Uuid(v)),
+                Err(_) => return Err(Value::String(v)),
+            },
+            (Kind::Object, value @ Value::Object(_)) => value,
+            (Kind::Array(None), value @ Value::Array(_)) => value,
+            (Kind::Array(Some(kind)), Value::Array(Array(values))) => values
+                .into_iter()
+                .map(|value| kind.coerce(value))
+                .collect::<Result<Vec<_>, _>>()?
+                .into(),
+            (Kind::Record(None), value @ Value::Record(_)) => value,
+            (Kind::Record(Some(tb)), Value::Record(v)) if *v.table == *tb => Value::Record(v),
+            (_, value) => return Err(value),
+        })
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Any => write!(f, "any"),
+            Kind::Bool => write!(f, "bool"),
+            Kind::Int => write!(f, "int"),
+            Kind::Float => write!(f, "float"),
+            Kind::Number => write!(f, "number"),
+            Kind::String => write!(f, "string"),
+            Kind::Datetime => write!(f, "datetime"),
+            Kind::Uuid => write!(f, "uuid"),
+            Kind::Object => write!(f, "object"),
+            Kind::Array(None) => write!(f, "array"),
+            Kind::Array(Some(kind)) => write!(f, "array<{kind}>"),
+            Kind::Record(None) => write!(f, "record"),
+            Kind::Record(Some(table)) => write!(f, "record<{table}>"),
+            Kind::Option(kind) => write!(f, "option<{kind}>"),
+        }
+    }
+}
@@ -1,23 +1,37 @@
 pub(crate) mod array;
 pub(crate) mod condition;
+pub(crate) mod data;
+pub(crate) mod datetime;
+pub(crate) mod definition;
 pub(crate) mod direction;
+pub(crate) mod distance;
 pub(crate) mod edge;
 pub(crate) mod expression;
 pub(crate) mod fields;
+pub(crate) mod filter;
 pub(crate) mod func;
+pub(crate) mod function;
+pub(crate) mod group;
 pub(crate) mod id;
 pub(crate) mod ident;
 pub(crate) mod idiom;
+pub(crate) mod index;
+pub(crate) mod kind;
 pub(crate) mod number;
 pub(crate) mod object;
 pub(crate) mod operator;
+pub(crate) mod order;
+pub(crate) mod output;
 pub(crate) mod part;
+pub(crate) mod query;
 pub(crate) mod record;
 pub(crate) mod serde;
 pub(crate) mod statements;
 pub(crate) mod path;
+pub(crate) mod recurse;
 pub(crate) mod strand;
 pub(crate) mod table;
+pub(crate) mod tokenizer;
 pub(crate) mod traits;
 pub(crate) mod uuid;
 pub(crate) mod value;
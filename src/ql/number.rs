@@ -293,4 +293,11 @@ impl Number {
             Number::Float(i) => *i as usize,
         }
     }
+
+    pub fn to_float(&self) -> f64 {
+        match self {
+            Number::Int(i) => *i as f64,
+            Number::Float(i) => *i,
+        }
+    }
 }
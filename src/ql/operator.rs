@@ -1,3 +1,4 @@
+use crate::ql::distance::Distance;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -21,6 +22,9 @@ pub enum Operator {
     Gt,
     LtEq,
     GtEq,
+
+    Matches(Option<u8>),
+    Knn(u32, Option<Distance>),
 }
 
 impl fmt::Display for Operator {
@@ -40,6 +44,10 @@ impl fmt::Display for Operator {
             Operator::LtEq => write!(f, "<="),
             Operator::GtEq => write!(f, ">="),
             Operator::NtEq => write!(f, "!="),
+            Operator::Matches(None) => write!(f, "@@"),
+            Operator::Matches(Some(r)) => write!(f, "@{r}@"),
+            Operator::Knn(k, None) => write!(f, "<|{k}|>"),
+            Operator::Knn(k, Some(dist)) => write!(f, "<|{k},{dist}|>"),
         }
     }
 }
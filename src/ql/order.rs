@@ -0,0 +1,130 @@
+use crate::ql::{idiom::Idiom, value::Value};
+use std::{cmp::Ordering, fmt, ops::Deref};
+
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Orders(pub Vec<Order>);
+
+impl Deref for Orders {
+    type Target = Vec<Order>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Orders {
+    pub fn compare(&self, a: &[Value], b: &[Value]) -> Ordering {
+        self.iter()
+            .zip(a.iter().zip(b.iter()))
+            .map(|(order, (a, b))| order.compare(a, b))
+            .find(|ord| ord.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    pub idiom: Idiom,
+    pub ascending: bool,
+    pub collate: bool,
+    pub numeric: bool,
+}
+
+impl Order {
+    pub fn new(idiom: Idiom) -> Self {
+        Order {
+            idiom,
+            ascending: true,
+            collate: false,
+            numeric: false,
+        }
+    }
+
+    pub fn compare(&self, a: &Value, b: &Value) -> Ordering {
+        let ord = match (a, b) {
+            (Value::String(a), Value::String(b)) => match (self.collate, self.numeric) {
+                (true, true) => natural(&a.to_lowercase(), &b.to_lowercase()),
+                (true, false) => a.to_lowercase().cmp(&b.to_lowercase()),
+                (false, true) => natural(a, b),
+                (false, false) => a.cmp(b),
+            },
+            (a, b) => a.cmp(b),
+        };
+        match self.ascending {
+            true => ord,
+            false => ord.reverse(),
+        }
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.idiom)?;
+        if self.collate {
+            write!(f, " COLLATE")?;
+        }
+        if self.numeric {
+            write!(f, " NUMERIC")?;
+        }
+        match self.ascending {
+            true => write!(f, " ASC"),
+            false => write!(f, " DESC"),
+        }
+    }
+}
+
+/// Compares strings so that runs of digits are ordered by their numeric value
+fn natural(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (x, rest_a) = split_digits(a);
+                let (y, rest_b) = split_digits(b);
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                let ord = x.len().cmp(&y.len()).then_with(|| x.cmp(y));
+                if ord.is_ne() {
+                    return ord;
+                }
+                (a, b) = (rest_a, rest_b);
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+            }
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ql::{ident::Ident, part::Part};
+
+    #[test]
+    fn order_test() {
+        let mut order = Order::new(Idiom::from(vec![Part::Field(Ident::new("name"))]));
+        let (a, b) = (Value::from("item10"), Value::from("Item9"));
+        assert_eq!(order.compare(&a, &b), Ordering::Greater);
+
+        order.numeric = true;
+        order.collate = true;
+        assert_eq!(order.compare(&a, &b), Ordering::Greater);
+
+        order.ascending = false;
+        assert_eq!(order.compare(&a, &b), Ordering::Less);
+        assert_eq!(natural("a02b", "a2c"), Ordering::Less);
+    }
+}
@@ -0,0 +1,22 @@
+use crate::ql::value::Value;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Output {
+    None,
+    Before,
+    #[default]
+    After,
+    Diff,
+}
+
+impl Output {
+    pub fn output(&self, before: &Value, after: &Value) -> Option<Value> {
+        match self {
+            Output::None => None,
+            Output::Before => Some(before.clone()),
+            Output::After => Some(after.clone()),
+            Output::Diff => Some(before.diff(after)),
+        }
+    }
+}
@@ -1,6 +1,11 @@
-use crate::{
-    dbs::ops::get::Get,
-    ql::{edge::Edge, fields::Field, ident::Ident, number::Number, path::Path, value::Value},
+use crate::ql::{
+    edge::Edge,
+    fields::{Field, Fields},
+    ident::Ident,
+    number::Number,
+    path::Path,
+    recurse::Recurse,
+    value::Value,
 };
 use std::fmt;
 
@@ -15,6 +20,7 @@ pub enum Part {
     Index(Number),
     Where(Value),
     Path(Path),
+    Recurse(Recurse),
     Edge(Edge),
     Start(Value),
     Value(Value),
@@ -34,11 +40,11 @@ impl<'a> Next<'a> for &'a [Part] {
 }
 
 pub trait ParseWalk<'a> {
-    fn parse_walk(&'a self) -> (Get, Vec<Path>, &'a [Part]);
+    fn parse_walk(&'a self) -> (Fields, Vec<Path>, &'a [Part]);
 }
 
 impl<'a> ParseWalk<'a> for &'a [Part] {
-    fn parse_walk(&'a self) -> (Get, Vec<Path>, &'a [Part]) {
+    fn parse_walk(&'a self) -> (Fields, Vec<Path>, &'a [Part]) {
         let walk_path = self
             .iter()
             .take_while(|&p| Part::is_path(p))
@@ -51,14 +57,14 @@ impl<'a> ParseWalk<'a> for &'a [Part] {
             })
             .collect::<Vec<Path>>();
 
-        let (get, path) = if let Some(part @ Part::Field(_)) = self.get(walk_path.len()) {
-            let field: Field = part.clone().try_into().unwrap();
-            (Get::new(field.into(), None), &self[walk_path.len() + 1..])
-        } else {
-            let field: Field = Part::Field("id".into()).try_into().unwrap();
-            (Get::new(field.into(), None), &self[walk_path.len()..])
+        // Only the field read next is fetched from the nodes reached, the
+        // rest of the path then reads it out of the fetched document
+        let field: Field = match self.get(walk_path.len()) {
+            Some(part @ Part::Field(_)) => part.clone().try_into().unwrap(),
+            _ => Part::Field("id".into()).try_into().unwrap(),
         };
-        (get, walk_path, path)
+        let rest = &self[walk_path.len()..];
+        (field.into(), walk_path, rest)
     }
 }
 
@@ -109,7 +115,7 @@ impl fmt::Display for Part {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Part::All => write!(f, "*"),
-            Part::Flatten => todo!(),
+            Part::Flatten => write!(f, "..."),
             Part::First => write!(f, "[0]"),
             Part::Last => write!(f, "[$]"),
             Part::Field(v) => write!(f, ".{v}"),
@@ -117,6 +123,7 @@ impl fmt::Display for Part {
             Part::Value(v) => write!(f, "{v}"),
             Part::Edge(v) => write!(f, "[{v}]"),
             Part::Path(v) => write!(f, "{v}"),
+            Part::Recurse(v) => write!(f, "{v}"),
             Part::Start(v) => write!(f, "{v}"),
             Part::Where(v) => write!(f, "[WHERE {v}]"),
         }
@@ -124,4 +131,41 @@ impl fmt::Display for Part {
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use crate::dbs::{executor::Executor, graph::Graph};
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn field_access_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup(
+                "CREATE person:1 SET name = 'a', address = { city: 'x', codes: [[1, 2], [3]] };
+                CREATE person:2 SET name = 'b';
+                CREATE post:1 SET author = person:1, authors = [person:1, person:2];
+                RELATE person:1->knows->person:2 SET since = 2020",
+            )
+            .await;
+        let v = |v: serde_json::Value| Ok(serde_json::json!([{ "v": v }]));
+        let record = executor.run("SELECT person:1.address.city AS v FROM post:1");
+        assert_eq!(record.await, v("x".into()));
+        let link = executor.run("SELECT author.name AS v FROM post:1");
+        assert_eq!(link.await, v("a".into()));
+        let index = executor.run("SELECT author.address['city'] AS v FROM post:1");
+        assert_eq!(index.await, v("x".into()));
+        let links = executor.run("SELECT authors.name AS v FROM post:1");
+        assert_eq!(links.await, v(serde_json::json!(["a", "b"])));
+        let last = executor.run("SELECT authors[$].name AS v FROM post:1");
+        assert_eq!(last.await, v("b".into()));
+        let filtered = executor.run("SELECT authors[WHERE name = 'a'].name AS v FROM post:1");
+        assert_eq!(filtered.await, v(serde_json::json!(["a"])));
+        let flattened = executor.run("SELECT address.codes... AS v FROM person:1");
+        assert_eq!(flattened.await, v(serde_json::json!([1, 2, 3])));
+        let unmatched = executor.run("SELECT address[WHERE city = 'y'] AS v FROM person:1");
+        assert_eq!(unmatched.await, v(serde_json::Value::Null));
+        let walked = executor.run("SELECT ->knows->person.name AS v FROM person:1");
+        assert_eq!(walked.await, v(serde_json::json!(["b"])));
+        let edges = executor.run("SELECT (person:1->knows).since AS v FROM post:1");
+        assert_eq!(edges.await, v(serde_json::json!([2020])));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    dbs::{
+        graph::Graph,
+        transaction::{self, Txn},
+    },
+    doc::document::Cursor,
+    err::Error,
+    ql::{
+        statements::{
+            create::Create, define::Define, delete::Delete, relate::Relate, select::Select,
+            update::Update,
+        },
+        value::Value,
+    },
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+use std::ops::Deref;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Query {
+    Select(Select),
+    Create(Create),
+    Update(Update),
+    Delete(Delete),
+    Relate(Relate),
+    Define(Define),
+    Begin,
+    Commit,
+    Cancel,
+}
+
+impl Query {
+    /// Whether the query reads or writes records, rather than changing
+    /// definitions or the transaction itself
+    pub fn is_transactional(&self) -> bool {
+        !matches!(
+            self,
+            Query::Define(_) | Query::Begin | Query::Commit | Query::Cancel
+        )
+    }
+
+    /// Whether the query changes records or definitions
+    pub fn is_write(&self) -> bool {
+        !matches!(
+            self,
+            Query::Select(_) | Query::Begin | Query::Commit | Query::Cancel
+        )
+    }
+
+    pub async fn compute(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        cur: Option<&Cursor>,
+        txn: &mut Txn,
+    ) -> Result<Value, Error> {
+        // Records linked to from the statement are read in its transaction
+        let cursor = cur.cloned().unwrap_or_else(|| Cursor::from(Value::None));
+        let cursor = cursor.with_txn(*txn);
+        let cur = Some(&cursor);
+        match self {
+            Query::Select(stm) => stm.compute(stk, graph, cur, txn).await,
+            Query::Create(stm) => stm.compute(stk, graph, cur, txn).await,
+            Query::Update(stm) => stm.compute(stk, graph, cur, txn).await,
+            Query::Delete(stm) => stm.compute(stk, graph, cur, txn).await,
+            Query::Relate(stm) => stm.compute(stk, graph, cur, txn).await,
+            // Definitions take effect at once and can not be rolled back
+            Query::Define(_) if txn.id.is_some() => Err(Error::DefineInTransaction),
+            Query::Define(stm) => stm.compute(stk, graph, cur).await,
+            Query::Begin => transaction::begin(txn).await.map(|_| Value::None),
+            Query::Commit => transaction::commit(graph, txn).await.map(|_| Value::None),
+            Query::Cancel => transaction::cancel(graph, txn).await.map(|_| Value::None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Queries(pub Vec<Query>);
+
+impl Deref for Queries {
+    type Target = Vec<Query>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IntoIterator for Queries {
+    type Item = Query;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dbs::{executor::Executor, graph::Graph},
+        err::Error,
+        par,
+    };
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn define_in_transaction_test() {
+        let executor = Executor::new(Graph::new().start());
+        let queries = par::parse(
+            "BEGIN; CREATE person:1 SET email = 'a'; DEFINE INDEX u ON person FIELDS email UNIQUE; CANCEL;",
+        )
+        .unwrap();
+        let responses = executor.execute(queries).await;
+        assert_eq!(responses[0].result, Err(Error::QueryNotExecuted));
+        assert_eq!(responses[1].result, Err(Error::DefineInTransaction));
+
+        // The index was never defined, so duplicates are still allowed
+        let queries =
+            par::parse("CREATE person:2 SET email = 'a'; CREATE person:3 SET email = 'a'").unwrap();
+        for response in executor.execute(queries).await {
+            assert!(response.result.is_ok());
+        }
+    }
+}
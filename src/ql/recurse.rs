@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// Repeats the paths that follow it in an idiom, written `{min..max}`, with
+/// either bound left out for `{..}` and a single number for an exact depth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Recurse {
+    pub min: u32,
+    pub max: Option<u32>,
+    pub collect: Collect,
+}
+
+/// What a recursive walk returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Collect {
+    /// Every node reached within the depth range, once each
+    #[default]
+    Nodes,
+    /// Every route to the nodes reached that does not visit a node twice
+    Paths,
+}
+
+impl Recurse {
+    /// The depth a walk without an upper bound stops at
+    pub const LIMIT: u32 = 256;
+
+    pub fn max(&self) -> u32 {
+        self.max.unwrap_or(Recurse::LIMIT)
+    }
+}
+
+impl fmt::Display for Recurse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{{{max}")?,
+            Some(max) => write!(f, "{{{}..{max}", self.min)?,
+            None => write!(f, "{{{}..", self.min)?,
+        }
+        match self.collect {
+            Collect::Nodes => write!(f, "}}"),
+            Collect::Paths => write!(f, "+path}}"),
+        }
+    }
+}
@@ -1,2 +1,3 @@
 pub mod arc_str_serde;
 pub mod de;
+pub mod ser;
@@ -0,0 +1,54 @@
+use crate::ql::{number::Number, value::Value};
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Value::None | Value::Null => serializer.serialize_none(),
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::Number(Number::Int(v)) => serializer.serialize_i64(*v),
+            Value::Number(Number::Float(v)) => serializer.serialize_f64(*v),
+            Value::String(v) => serializer.serialize_str(v.as_str()),
+            Value::Uuid(v) => serializer.serialize_str(&v.0.to_string()),
+            Value::Datetime(v) => serializer.serialize_str(&v.to_rfc3339()),
+            Value::Array(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for val in v.iter() {
+                    seq.serialize_element(val)?;
+                }
+                seq.end()
+            }
+            Value::Object(v) => {
+                let mut map = serializer.serialize_map(Some(v.len()))?;
+                for (key, val) in v.iter() {
+                    map.serialize_entry(key.as_ref(), val)?;
+                }
+                map.end()
+            }
+            v => serializer.collect_str(v),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ql::{object::Object, record::Record, value::Value};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn serialize_value_test() {
+        let value = Value::Object(Object(BTreeMap::from([
+            ("id".into(), Record::new("person", 1).into()),
+            ("tags".into(), vec!["a".into(), Value::None].into()),
+            ("age".into(), 30.into()),
+        ])));
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"age":30,"id":"person:1","tags":["a",null]}"#);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    dbs::{
+        graph::Graph,
+        ops::{create, describe::Describe},
+        transaction::Txn,
+    },
+    doc::document::Cursor,
+    err::Error,
+    ql::{
+        array::Array,
+        data::Data,
+        id::Id,
+        object::Object,
+        record::Record,
+        value::{Value, Values},
+    },
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Create {
+    pub what: Values,
+    pub data: Option<Data>,
+}
+
+impl Create {
+    pub async fn compute(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        cur: Option<&Cursor>,
+        txn: &Txn,
+    ) -> Result<Value, Error> {
+        let mut created = Array::with_capacity(self.what.len());
+        for val in self.what.iter() {
+            let id = match stk.run(|stk| val.evaluate(stk, graph, cur)).await? {
+                Value::Table(table) => Record::new(table.0, Id::default()),
+                Value::Record(record) => *record,
+                val => {
+                    return Err(Error::IncorrectValueType {
+                        expected: String::from("Table or Record"),
+                        result: val,
+                    })
+                }
+            };
+
+            let mut doc = Value::Object(Object::default());
+            if let Some(data) = &self.data {
+                stk.run(|stk| data.compute(stk, graph, cur, &mut doc))
+                    .await?;
+            }
+            let schema = graph.send(Describe(id.table.to_string())).await.unwrap();
+            let doc = stk
+                .run(|stk| schema.process(stk, graph, txn, &id, None, doc))
+                .await?;
+            let Value::Object(Object(fields)) = doc else {
+                unreachable!()
+            };
+
+            let response = graph
+                .send(create::Create::new(id, fields.into_iter().collect()).with_txn(*txn))
+                .await
+                .unwrap()?;
+            created.push(response.try_into()?);
+        }
+        Ok(created.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dbs::{executor::Executor, graph::Graph},
+        err::Error,
+    };
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn create_test() {
+        let executor = Executor::new(Graph::new().start());
+        let person = serde_json::json!({"id": "person:1", "meta": {"age": 20}, "name": "a"});
+        let created = executor.run("CREATE person:1 SET name = 'a', meta.age = 20");
+        assert_eq!(created.await, Ok(serde_json::json!([person])));
+        let again = executor.run("CREATE person:1").await;
+        assert!(matches!(again, Err(Error::CreateError { .. })));
+        let people = executor.run("SELECT * FROM person").await;
+        assert_eq!(people, Ok(serde_json::json!([person])));
+    }
+}
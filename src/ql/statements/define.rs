@@ -0,0 +1,102 @@
+use crate::{
+    dbs::{graph::Graph, ops::define},
+    doc::document::Cursor,
+    err::Error,
+    idx::analyzer::Analyzer,
+    ql::{
+        definition::Definition, filter::Filter, idiom::Idiom, index::Index, tokenizer::Tokenizer,
+        value::Value,
+    },
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+use std::time::Duration;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Define {
+    Table(String),
+    Analyzer {
+        name: String,
+        tokenizers: Vec<Tokenizer>,
+        filters: Vec<Filter>,
+    },
+    Index {
+        name: String,
+        table: String,
+        fields: Vec<Idiom>,
+        index: Index,
+    },
+    /// The clauses of `DEFINE TABLE`, which apply whether the table exists
+    /// or not
+    Options {
+        table: String,
+        schemafull: Option<bool>,
+        history: Option<Duration>,
+    },
+    Field {
+        table: String,
+        definition: Definition,
+        /// The statement as written, which is what gets persisted
+        source: String,
+    },
+}
+
+impl Define {
+    pub async fn compute(
+        &self,
+        _stk: &mut Stk,
+        graph: &Addr<Graph>,
+        _cur: Option<&Cursor>,
+    ) -> Result<Value, Error> {
+        let defines = match self.clone() {
+            Define::Table(table) => vec![define::Define::Table(table)],
+            Define::Analyzer {
+                name,
+                tokenizers,
+                filters,
+            } => vec![define::Define::Analyzer(Analyzer::new(
+                name, tokenizers, filters,
+            ))],
+            Define::Index {
+                name,
+                table,
+                fields,
+                index,
+            } => vec![define::Define::Index {
+                name,
+                table,
+                fields,
+                index,
+            }],
+            Define::Options {
+                table,
+                schemafull,
+                history,
+            } => {
+                let schema = schemafull.map(|schemafull| define::Define::Schema {
+                    table: table.clone(),
+                    schemafull,
+                });
+                let history = history.map(|history| define::Define::History {
+                    table: table.clone(),
+                    history,
+                });
+                schema.into_iter().chain(history).collect()
+            }
+            Define::Field {
+                table,
+                definition,
+                source,
+            } => vec![define::Define::Field {
+                table,
+                definition,
+                source,
+            }],
+        };
+        for define in defines {
+            graph.send(define).await.unwrap()?;
+        }
+        Ok(Value::None)
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    dbs::{
+        entity::Entity,
+        graph::Graph,
+        ops::{delete::Purge, get::Get, retrieve::Retrieve},
+        transaction::Txn,
+    },
+    doc::document::Cursor,
+    err::Error,
+    ql::{
+        array::Array,
+        condition::Condition,
+        fields::{Field, Fields},
+        object::Object,
+        output::Output,
+        record::Record,
+        value::{Value, Values},
+    },
+    resp::Response,
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delete {
+    pub what: Values,
+    pub conditions: Option<Condition>,
+    pub output: Option<Output>,
+}
+
+impl Delete {
+    pub async fn compute(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        cur: Option<&Cursor>,
+        txn: &Txn,
+    ) -> Result<Value, Error> {
+        let mut deleted = Array::new();
+        for val in self.what.iter() {
+            let val = stk.run(|stk| val.evaluate(stk, graph, cur)).await?;
+            let retrieve = Retrieve::try_from(val)?;
+            let nodes = graph.send(retrieve).await.unwrap().into_nodes();
+            for node in nodes {
+                if let Some(val) = stk.run(|stk| self.delete(stk, graph, txn, node)).await? {
+                    deleted.push(val);
+                }
+            }
+        }
+        Ok(deleted.into())
+    }
+
+    async fn delete(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        node: Addr<Entity>,
+    ) -> Result<Option<Value>, Error> {
+        // Records the transaction does not see are left alone
+        let get = Get::new(Fields(vec![Field::WildCard]), None, graph.clone()).with_txn(*txn);
+        let before: Value = match node.send(get).await.unwrap()? {
+            Response::None => return Ok(None),
+            response => response.try_into()?,
+        };
+        let Value::Object(Object(fields)) = &before else {
+            return Ok(None);
+        };
+
+        if let Some(cond) = &self.conditions {
+            let cur = Cursor::from(before.clone()).with_txn(*txn);
+            let check = stk
+                .run(|stk| cond.evaluate(stk, graph, fields, Some(&cur)))
+                .await?;
+            if !check.is_truthy() {
+                return Ok(None);
+            }
+        }
+
+        let id: Record = fields.get("id").cloned().unwrap_or_default().try_into()?;
+        graph.send(Purge(id, *txn)).await.unwrap()?;
+        let output = self.output.clone().unwrap_or(Output::None);
+        Ok(output.output(&before, &Value::None))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dbs::{executor::Executor, graph::Graph};
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn delete_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup("CREATE person:1 SET age = 20; CREATE person:2 SET age = 40")
+            .await;
+        let deleted = executor
+            .run("DELETE person WHERE age > 30 RETURN BEFORE")
+            .await;
+        let before = serde_json::json!([{"id": "person:2", "age": 40}]);
+        assert_eq!(deleted, Ok(before));
+        let missing = executor.run("DELETE person:3").await;
+        assert_eq!(missing, Ok(serde_json::json!([])));
+        let people = executor.run("SELECT * FROM person").await;
+        let left = serde_json::json!([{"id": "person:1", "age": 20}]);
+        assert_eq!(people, Ok(left));
+        let all = executor.run("DELETE FROM person").await;
+        assert_eq!(all, Ok(serde_json::json!([])));
+        let people = executor.run("SELECT * FROM person").await;
+        assert_eq!(people, Ok(serde_json::json!([])));
+    }
+}
@@ -1,2 +1,7 @@
+pub mod create;
+pub mod define;
+pub mod delete;
+pub mod relate;
 pub mod select;
 pub mod statement;
+pub mod update;
@@ -0,0 +1,156 @@
+use crate::{
+    dbs::{
+        entity::Entity,
+        graph::Graph,
+        ops::{describe::Describe, relate::Connect, retrieve::Retrieve},
+        transaction::{Guard, Txn},
+    },
+    doc::document::Cursor,
+    err::Error,
+    ql::{
+        array::Array, data::Data, id::Id, object::Object, output::Output, record::Record,
+        value::Value,
+    },
+    resp::Response,
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relate {
+    pub from: Value,
+    pub kind: Value,
+    pub with: Value,
+    pub data: Option<Data>,
+    pub output: Option<Output>,
+}
+
+impl Relate {
+    pub async fn compute(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        cur: Option<&Cursor>,
+        txn: &Txn,
+    ) -> Result<Value, Error> {
+        let from = stk.run(|stk| self.from.evaluate(stk, graph, cur)).await?;
+        let with = stk.run(|stk| self.with.evaluate(stk, graph, cur)).await?;
+        let kind = stk.run(|stk| self.kind.evaluate(stk, graph, cur)).await?;
+
+        let mut related = Array::new();
+        for org_id in records(from)? {
+            let origin = node(graph, &org_id, txn).await?;
+            for dest_id in records(with.clone())? {
+                let destination = node(graph, &dest_id, txn).await?;
+                let id = match &kind {
+                    Value::Table(table) => Record::new(table.0.as_str(), Id::default()),
+                    Value::Record(id) => *id.clone(),
+                    kind => {
+                        return Err(Error::IncorrectValueType {
+                            expected: String::from("Table or Record"),
+                            result: kind.clone(),
+                        })
+                    }
+                };
+
+                let mut doc = Value::Object(Object::default());
+                if let Some(data) = &self.data {
+                    stk.run(|stk| data.compute(stk, graph, cur, &mut doc))
+                        .await?;
+                }
+                // The schema sees the records the edge links, whatever the
+                // content says they are
+                if let Value::Object(Object(fields)) = &mut doc {
+                    fields.insert("in".into(), org_id.clone().into());
+                    fields.insert("out".into(), dest_id.clone().into());
+                }
+                let schema = graph.send(Describe(id.table.to_string())).await.unwrap();
+                let doc = stk
+                    .run(|stk| schema.process(stk, graph, txn, &id, None, doc))
+                    .await?;
+                let Value::Object(Object(fields)) = doc else {
+                    unreachable!()
+                };
+
+                let connect = Connect {
+                    id,
+                    fields: fields.into_iter().collect(),
+                    org_id: org_id.clone(),
+                    origin: origin.clone(),
+                    dest_id,
+                    destination,
+                    txn: *txn,
+                };
+                let after: Value = graph.send(connect).await.unwrap()?.try_into()?;
+                let output = self.output.clone().unwrap_or_default();
+                if let Some(val) = output.output(&Value::None, &after) {
+                    related.push(val);
+                }
+            }
+        }
+        Ok(related.into())
+    }
+}
+
+fn records(val: Value) -> Result<Vec<Record>, Error> {
+    match val {
+        Value::Record(id) => Ok(vec![*id]),
+        Value::Array(Array(array)) => array.into_iter().map(Record::try_from).collect(),
+        val => Err(Error::IncorrectValueType {
+            expected: String::from("Record"),
+            result: val,
+        }),
+    }
+}
+
+/// The record to link to, guarded so it is not deleted before the link is
+/// committed
+async fn node(graph: &Addr<Graph>, id: &Record, txn: &Txn) -> Result<Addr<Entity>, Error> {
+    match graph.send(Retrieve::Record(id.clone())).await.unwrap() {
+        Response::Node(node) => {
+            node.send(Guard(*txn)).await.unwrap()?;
+            Ok(node)
+        }
+        _ => Err(Error::RecordNotFound(id.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dbs::{executor::Executor, graph::Graph},
+        err::Error,
+    };
+    use actix::Actor;
+
+    #[actix_rt::test]
+    async fn relate_test() {
+        let executor = Executor::new(Graph::new().start());
+        executor
+            .setup("CREATE person:1; CREATE person:2; CREATE person:3")
+            .await;
+        let edge = serde_json::json!({
+            "id": "knows:a",
+            "in": "person:1",
+            "out": "person:2",
+            "since": 2020
+        });
+        let related = executor.run("RELATE person:1->knows:a->person:2 SET since = 2020");
+        assert_eq!(related.await, Ok(serde_json::json!([edge])));
+        let many = executor.run("RELATE person:1->knows->[person:2, person:3] RETURN NONE");
+        assert_eq!(many.await, Ok(serde_json::json!([])));
+        let missing = executor.run("RELATE person:1->knows->person:4").await;
+        let missing = missing.unwrap_err();
+        assert_eq!(missing, Error::RecordNotFound(String::from("person:4")));
+        let selected = executor.run("SELECT * FROM knows:a").await;
+        assert_eq!(selected, Ok(serde_json::json!([edge])));
+        let edges = executor.run("SELECT * FROM knows").await.unwrap();
+        assert_eq!(edges.as_array().unwrap().len(), 3);
+
+        // Deleting a record deletes the edges linking it
+        executor.setup("DELETE person:1").await;
+        let edges = executor.run("SELECT * FROM knows").await;
+        assert_eq!(edges, Ok(serde_json::json!([])));
+    }
+}
@@ -2,13 +2,18 @@ use crate::{
     dbs::{
         graph::Graph,
         iterator::{Iterable, Iterator},
+        transaction::Txn,
     },
     doc::document::Cursor,
     err::Error,
     ql::{
         array::Array,
         condition::Condition,
+        datetime::Datetime,
         fields::Fields,
+        group::Groups,
+        order::Orders,
+        path::Path,
         statements::statement::Statement,
         value::{Value, Values},
     },
@@ -17,36 +22,66 @@ use actix::Addr;
 use reblessive::tree::Stk;
 
 #[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Select {
     pub fields: Fields,
     pub what: Values,
     pub conditions: Option<Condition>,
+    pub group: Option<Groups>,
+    pub order: Option<Orders>,
     pub limit: Option<usize>,
     pub start: Option<usize>,
+    pub version: Option<Value>,
 }
 
 impl Select {
-    // TODO need to implement multi step edges
-    async fn compute(
+    pub async fn compute(
         &self,
         stk: &mut Stk,
-        graph: Addr<Graph>,
+        graph: &Addr<Graph>,
         cur: Option<&Cursor>,
+        txn: &Txn,
     ) -> Result<Value, Error> {
         let mut iter = Iterator::new();
         let stm = Statement::from(self);
+        let txn = match &self.version {
+            Some(version) => Self::version(stk, graph, cur, version).await?,
+            None => *txn,
+        };
+        // Indexes only hold the latest state of each record, a lookup at an
+        // earlier time would miss records that matched then but have changed
+        // since. The conditions are not passed to the table, so every record
+        // is checked against the version it held at that time, and a KNN
+        // operator, which needs the table to pick the nearest, is refused
+        let conditions = self.conditions.as_ref().filter(|_| self.version.is_none());
         for val in self.what.0.iter() {
-            let val = stk.run(|stk| val.evaluate(stk, &graph, cur)).await?;
+            // Paths from a record are walked so the reached records can be
+            // filtered and projected, rather than reduced to their ids
+            if let Some((start, path)) = Self::walk(val) {
+                match stk.run(|stk| start.evaluate(stk, graph, cur)).await? {
+                    Value::Record(from) => iter.ingest_walk(*from, path)?,
+                    result => {
+                        return Err(Error::IncorrectValueType {
+                            expected: String::from("Record"),
+                            result,
+                        })
+                    }
+                }
+                continue;
+            }
+            let val = stk.run(|stk| val.evaluate(stk, graph, cur)).await?;
             match val {
-                Value::Record(id) => iter.ingest_record(*id, &graph).await?,
-                Value::Table(table) => iter.ingest_table(table, &graph).await?,
+                Value::Record(id) => iter.ingest_record(*id, graph).await?,
+                Value::Table(table) => iter.ingest_table(table, conditions, graph, &txn).await?,
                 Value::Edge(edge) => iter.ingest_edge(*edge)?,
                 Value::Array(Array(array)) => {
                     for val in array {
                         match val {
-                            Value::Record(id) => iter.ingest_record(*id, &graph).await?,
+                            Value::Record(id) => iter.ingest_record(*id, graph).await?,
                             Value::Edge(edge) => iter.ingest_edge(*edge)?,
-                            Value::Table(table) => iter.ingest_table(table, &graph).await?,
+                            Value::Table(table) => {
+                                iter.ingest_table(table, conditions, graph, &txn).await?
+                            }
                             _ => iter.ingest(Iterable::Value(val)),
                         }
                     }
@@ -55,9 +90,33 @@ impl Select {
             }
         }
 
-        let result = stk.run(|stk| iter.process(stk, &graph, &stm)).await;
+        stk.run(|stk| iter.process(stk, graph, &txn, &stm)).await
+    }
 
-        result
+    /// Reads what was committed at the time the version evaluates to
+    async fn version(
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        cur: Option<&Cursor>,
+        version: &Value,
+    ) -> Result<Txn, Error> {
+        match stk.run(|stk| version.evaluate(stk, graph, cur)).await? {
+            Value::Datetime(Datetime(at)) => Ok(Txn {
+                id: None,
+                at: u64::try_from(at).unwrap_or_default(),
+            }),
+            result => Err(Error::IncorrectValueType {
+                expected: String::from("Datetime"),
+                result,
+            }),
+        }
+    }
+
+    fn walk(val: &Value) -> Option<(&Value, Vec<Path>)> {
+        match val {
+            Value::Idiom(idiom) => idiom.walk(),
+            _ => None,
+        }
     }
 }
 
@@ -106,6 +165,7 @@ mod test {
             conditions: None,
             limit: None,
             start: None,
+            version: None,
         };
         let res: Vec<Value> = select.compute(graph).await.unwrap().try_into().unwrap();
 
@@ -123,6 +183,7 @@ mod test {
             conditions: None,
             limit: Some(limit),
             start: None,
+            version: None,
         };
         let res: Vec<Value> = select.compute(graph).await.unwrap().try_into().unwrap();
 
@@ -171,6 +232,7 @@ mod test {
             conditions: None,
             limit: None,
             start: None,
+            version: None,
         };
         let res: Vec<Value> = select.compute(graph).await.unwrap().try_into().unwrap();
 
@@ -205,6 +267,7 @@ mod test {
             conditions: None,
             limit: None,
             start: None,
+            version: None,
         };
         let res: Vec<Value> = select.compute(graph).await.unwrap().try_into().unwrap();
 
@@ -1,4 +1,7 @@
-use crate::ql::{condition::Condition, fields::Fields, statements::select::Select, value::Values};
+use crate::ql::{
+    condition::Condition, fields::Fields, group::Groups, order::Orders, statements::select::Select,
+    value::Values,
+};
 
 pub enum Statement<'a> {
     Select(&'a Select),
@@ -33,6 +36,18 @@ impl Statement<'_> {
         }
     }
 
+    pub fn group(&self) -> Option<&Groups> {
+        match self {
+            Statement::Select(stm) => stm.group.as_ref(),
+        }
+    }
+
+    pub fn order(&self) -> Option<&Orders> {
+        match self {
+            Statement::Select(stm) => stm.order.as_ref(),
+        }
+    }
+
     pub fn limit(&self) -> Option<&usize> {
         match self {
             Statement::Select(stm) => stm.limit.as_ref(),
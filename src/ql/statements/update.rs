@@ -0,0 +1,106 @@
+use crate::{
+    dbs::{
+        entity::Entity,
+        graph::Graph,
+        ops::{describe::Describe, get::Get, modify::Modify, retrieve::Retrieve, update},
+        transaction::Txn,
+    },
+    doc::document::Cursor,
+    err::Error,
+    ql::{
+        array::Array,
+        condition::Condition,
+        data::Data,
+        fields::{Field, Fields},
+        object::Object,
+        output::Output,
+        record::Record,
+        value::{Value, Values},
+    },
+    resp::Response,
+};
+use actix::Addr;
+use reblessive::tree::Stk;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Update {
+    pub what: Values,
+    pub data: Option<Data>,
+    pub conditions: Option<Condition>,
+    pub output: Option<Output>,
+}
+
+impl Update {
+    pub async fn compute(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        cur: Option<&Cursor>,
+        txn: &Txn,
+    ) -> Result<Value, Error> {
+        let mut updated = Array::new();
+        for val in self.what.iter() {
+            let val = stk.run(|stk| val.evaluate(stk, graph, cur)).await?;
+            let retrieve = Retrieve::try_from(val)?;
+            let nodes = graph.send(retrieve).await.unwrap().into_nodes();
+            for node in nodes {
+                if let Some(val) = stk.run(|stk| self.update(stk, graph, txn, node)).await? {
+                    updated.push(val);
+                }
+            }
+        }
+        Ok(updated.into())
+    }
+
+    async fn update(
+        &self,
+        stk: &mut Stk,
+        graph: &Addr<Graph>,
+        txn: &Txn,
+        node: Addr<Entity>,
+    ) -> Result<Option<Value>, Error> {
+        // Records the transaction does not see are left alone
+        let get = Get::new(Fields(vec![Field::WildCard]), None, graph.clone()).with_txn(*txn);
+        let before: Value = match node.send(get).await.unwrap()? {
+            Response::None => return Ok(None),
+            response => response.try_into()?,
+        };
+        let Value::Object(Object(fields)) = &before else {
+            return Ok(None);
+        };
+        let id: Record = fields.get("id").cloned().unwrap_or_default().try_into()?;
+        let cur = Cursor::from(before.clone()).with_txn(*txn);
+
+        if let Some(cond) = &self.conditions {
+            let check = stk
+                .run(|stk| cond.evaluate(stk, graph, fields, Some(&cur)))
+                .await?;
+            if !check.is_truthy() {
+                return Ok(None);
+            }
+        }
+
+        let mut doc = before.clone();
+        if let Some(data) = &self.data {
+            stk.run(|stk| data.compute(stk, graph, Some(&cur), &mut doc))
+                .await?;
+        }
+        let schema = graph.send(Describe(id.table.to_string())).await.unwrap();
+        let doc = stk
+            .run(|stk| schema.process(stk, graph, txn, &id, Some(&before), doc))
+            .await?;
+        let Value::Object(Object(fields)) = doc else {
+            unreachable!()
+        };
+
+        let update = update::Update::replace(fields.into_iter().collect());
+        let modify = Modify(id, update, *txn);
+        let after: Value = graph.send(modify).await.unwrap()?.try_into()?;
+        Ok(self
+            .output
+            .clone()
+            .unwrap_or_default()
+            .output(&before, &after))
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Tokenizer {
+    Blank,
+    Camel,
+    Class,
+    Punct,
+}
+
+impl Tokenizer {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "blank" => Tokenizer::Blank,
+            "camel" => Tokenizer::Camel,
+            "class" => Tokenizer::Class,
+            "punct" => Tokenizer::Punct,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Tokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tokenizer::Blank => write!(f, "blank"),
+            Tokenizer::Camel => write!(f, "camel"),
+            Tokenizer::Class => write!(f, "class"),
+            Tokenizer::Punct => write!(f, "punct"),
+        }
+    }
+}
@@ -3,9 +3,9 @@ use crate::{
     doc::document::Cursor,
     err::Error,
     ql::{
-        array::Array, edge::Edge, expression::Expression, id::Id, ident::Ident, idiom::Idiom,
-        number::Number, object::Object, part::Part, record::Record, strand::Strand, table::Table,
-        uuid::Uuid,
+        array::Array, datetime::Datetime, edge::Edge, expression::Expression, function::Function,
+        id::Id, ident::Ident, idiom::Idiom, number::Number, object::Object, part::Part,
+        record::Record, strand::Strand, table::Table, uuid::Uuid,
     },
 };
 use actix::Addr;
@@ -45,6 +45,7 @@ pub enum Value {
     Null,
     Record(Box<Record>),
     Uuid(Uuid),
+    Datetime(Datetime),
     Number(Number),
     String(Strand),
     Bool(bool),
@@ -52,8 +53,10 @@ pub enum Value {
     Object(Object),
     Idiom(Idiom),
     Expression(Box<Expression>),
+    Function(Box<Function>),
     Edge(Box<Edge>),
     Table(Table),
+    Param(Arc<str>),
 }
 
 impl Value {
@@ -73,6 +76,10 @@ impl Value {
         matches!(self, Value::Uuid(_))
     }
 
+    pub fn is_datetime(&self) -> bool {
+        matches!(self, Value::Datetime(_))
+    }
+
     pub fn is_number(&self) -> bool {
         matches!(self, Value::Number(_))
     }
@@ -101,6 +108,10 @@ impl Value {
         matches!(self, Value::Expression(_))
     }
 
+    pub fn is_function(&self) -> bool {
+        matches!(self, Value::Function(_))
+    }
+
     pub fn is_edge(&self) -> bool {
         matches!(self, Value::Edge(_))
     }
@@ -108,6 +119,24 @@ impl Value {
     pub fn is_table(&self) -> bool {
         matches!(self, Value::Table(_))
     }
+
+    pub fn is_param(&self) -> bool {
+        matches!(self, Value::Param(_))
+    }
+
+    /// The numbers of an array, when every element is a number
+    pub fn to_vector(&self) -> Option<Vec<f64>> {
+        match self {
+            Value::Array(v) => v
+                .iter()
+                .map(|v| match v {
+                    Value::Number(v) => Some(v.to_float()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
 }
 
 impl<T> From<Option<T>> for Value
@@ -239,12 +268,24 @@ impl From<uuid::Uuid> for Value {
     }
 }
 
+impl From<Datetime> for Value {
+    fn from(datetime: Datetime) -> Self {
+        Value::Datetime(datetime)
+    }
+}
+
 impl From<Expression> for Value {
     fn from(expression: Expression) -> Self {
         Value::Expression(Box::new(expression))
     }
 }
 
+impl From<Function> for Value {
+    fn from(function: Function) -> Self {
+        Value::Function(Box::new(function))
+    }
+}
+
 impl From<Id> for Value {
     fn from(id: Id) -> Self {
         match id {
@@ -277,6 +318,7 @@ impl Value {
             Value::Bool(v) => *v,
             Value::Record(_) => true,
             Value::Uuid(_) => true,
+            Value::Datetime(_) => true,
             Value::Number(_) => true,
             Value::Object(v) => !v.is_empty(),
             Value::String(v) => !v.is_empty(),
@@ -297,6 +339,11 @@ impl Value {
             Value::Object(v) => stk.run(|stk| v.evaluate(stk, graph, cur)).await,
             Value::Idiom(v) => stk.run(|stk| v.evaluate(stk, graph, cur)).await,
             Value::Expression(v) => stk.run(|stk| v.evaluate(stk, graph, cur)).await,
+            Value::Function(v) => stk.run(|stk| v.evaluate(stk, graph, cur)).await,
+            Value::Param(v) => Ok(cur
+                .and_then(|cur| cur.params.get(v))
+                .cloned()
+                .unwrap_or_default()),
             _ => Ok(self.to_owned()),
         }
     }
@@ -347,6 +394,37 @@ impl Value {
         })
     }
 
+    pub fn try_inc(self, right: Value) -> Result<Value, Error> {
+        Ok(match (self, right) {
+            (Value::None | Value::Null, right) => right,
+            (Value::Array(mut left), Value::Array(right)) => {
+                left.extend(right);
+                Value::Array(left)
+            }
+            (Value::Array(mut left), right) => {
+                left.push(right);
+                Value::Array(left)
+            }
+            (left, right) => left.try_add(right)?,
+        })
+    }
+
+    pub fn try_dec(self, right: Value) -> Result<Value, Error> {
+        Ok(match (self, right) {
+            (Value::Array(left), Value::Array(right)) => left
+                .into_iter()
+                .filter(|v| !right.contains(v))
+                .collect::<Vec<_>>()
+                .into(),
+            (Value::Array(left), right) => left
+                .into_iter()
+                .filter(|v| *v != right)
+                .collect::<Vec<_>>()
+                .into(),
+            (left, right) => left.try_sub(right)?,
+        })
+    }
+
     pub fn try_mul(self, right: Value) -> Result<Value, Error> {
         Ok(match (self, right) {
             (Value::Number(left), Value::Number(right)) => Value::Number(left.mul(right)),
@@ -369,6 +447,7 @@ impl fmt::Display for Value {
             Value::Null => write!(f, "NULL"),
             Value::Record(v) => write!(f, "{v}"),
             Value::Uuid(v) => write!(f, "{v}"),
+            Value::Datetime(v) => write!(f, "{v}"),
             Value::Number(v) => write!(f, "{v}"),
             Value::String(v) => write!(f, "{v}"),
             Value::Bool(v) => write!(f, "{v}"),
@@ -376,7 +455,9 @@ impl fmt::Display for Value {
             Value::Object(v) => write!(f, "{v}"),
             Value::Idiom(v) => write!(f, "{v}"),
             Value::Expression(v) => write!(f, "{v}"),
+            Value::Function(v) => write!(f, "{v}"),
             Value::Table(Table(v)) => write!(f, "{v}"),
+            Value::Param(v) => write!(f, "${v}"),
             Value::Edge(v) => write!(f, "{v}"),
         }
     }
@@ -4,7 +4,8 @@ use crate::{
     ql::{record::Record, value::Value},
 };
 use actix::{dev::MessageResponse, Actor, Addr, Message};
-use std::{collections::BTreeMap, fmt, sync::Arc};
+use serde::{ser::SerializeStruct, Serialize};
+use std::{collections::BTreeMap, fmt, sync::Arc, time::Duration};
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum Response {
@@ -90,6 +91,16 @@ impl TryFrom<Response> for Addr<Entity> {
     }
 }
 
+impl Response {
+    pub fn into_nodes(self) -> Vec<Addr<Entity>> {
+        match self {
+            Response::Nodes(nodes) => nodes,
+            Response::Node(node) => vec![node],
+            _ => Vec::new(),
+        }
+    }
+}
+
 impl From<Vec<Addr<Entity>>> for Response {
     fn from(records: Vec<Addr<Entity>>) -> Self {
         Response::Nodes(records)
@@ -106,3 +117,31 @@ impl fmt::Display for Response {
         }
     }
 }
+
+#[derive(Debug)]
+pub struct QueryResponse {
+    pub time: Duration,
+    pub result: Result<Value, Error>,
+}
+
+impl Serialize for QueryResponse {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("QueryResponse", 3)?;
+        match &self.result {
+            Ok(value) => {
+                state.serialize_field("status", "OK")?;
+                state.serialize_field("time", &format!("{:?}", self.time))?;
+                state.serialize_field("result", value)?;
+            }
+            Err(err) => {
+                state.serialize_field("status", "ERR")?;
+                state.serialize_field("time", &format!("{:?}", self.time))?;
+                state.serialize_field("result", err)?;
+            }
+        }
+        state.end()
+    }
+}
@@ -1,7 +1,7 @@
 use actix_web::{post, web, HttpResponse, Responder};
 use serde::Deserialize;
 
-use crate::AppState;
+use crate::{dbs::executor::Executor, par, AppState};
 
 #[derive(Debug, Deserialize)]
 struct Query {
@@ -13,7 +13,10 @@ async fn query(
     state: web::Data<AppState>,
     web::Json(Query { query }): web::Json<Query>,
 ) -> impl Responder {
-    let _graph = state.graph.clone();
-    let _ = query;
-    HttpResponse::Ok().body("hello world")
+    let queries = match par::parse(&query) {
+        Ok(queries) => queries,
+        Err(err) => return HttpResponse::BadRequest().json(err),
+    };
+    let executor = Executor::new(state.graph.clone());
+    HttpResponse::Ok().json(executor.execute(queries).await)
 }